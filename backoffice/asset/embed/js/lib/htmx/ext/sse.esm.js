@@ -0,0 +1,238 @@
+/*
+Server Sent Events Extension
+============================
+ES module port of htmx-ext-sse 2.2 (BSD Zero Clause License), registered against
+the bundled htmx instead of a global one.
+
+Attributes:
+  sse-connect="<url>"        opens an EventSource on the element
+  sse-swap="<event>[,...]"   swaps the data of named events into the element
+  hx-trigger="sse:<event>"   triggers a request when a named event arrives
+  sse-close="<event>"        closes the EventSource when a named event arrives
+*/
+import htmx from '../htmx.esm.js'
+
+/** @type {import("../htmx.esm").HtmxInternalApi} */
+let api
+
+htmx.defineExtension('sse', {
+    /**
+     * @param {import("../htmx.esm").HtmxInternalApi} apiRef
+     */
+    init: function (apiRef) {
+        api = apiRef
+
+        if (htmx.createEventSource === undefined) {
+            htmx.createEventSource = createEventSource
+        }
+    },
+
+    getSelectors: function () {
+        return ['[sse-connect]', '[data-sse-connect]', '[sse-swap]', '[data-sse-swap]']
+    },
+
+    /**
+     * @param {string} name
+     * @param {Event} evt
+     */
+    onEvent: function (name, evt) {
+        const parent = evt.target || evt.detail.elt
+        switch (name) {
+            case 'htmx:beforeCleanupElement': {
+                const internalData = api.getInternalData(parent)
+                const source = internalData.sseEventSource
+                if (source) {
+                    api.triggerEvent(parent, 'htmx:sseClose', {source, type: 'nodeReplaced'})
+                    source.close()
+                }
+                return
+            }
+            case 'htmx:afterProcessNode':
+                ensureEventSourceOnElement(parent)
+        }
+    }
+})
+
+/**
+ * @param {string} url
+ * @returns {EventSource}
+ */
+function createEventSource(url) {
+    return new EventSource(url, {withCredentials: true})
+}
+
+/**
+ * Registers the `sse-swap` and `hx-trigger="sse:..."` listeners of an element.
+ *
+ * @param {HTMLElement} elt
+ */
+function registerSSE(elt) {
+    if (api.getAttributeValue(elt, 'sse-swap')) {
+        const sourceElement = api.getClosestMatch(elt, hasEventSource)
+        if (sourceElement == null) {
+            return
+        }
+        const source = api.getInternalData(sourceElement).sseEventSource
+        const sseEventNames = api.getAttributeValue(elt, 'sse-swap').split(',')
+
+        for (const name of sseEventNames) {
+            const sseEventName = name.trim()
+            const listener = function (event) {
+                if (maybeCloseSSESource(sourceElement)) {
+                    return
+                }
+                if (!api.bodyContains(elt)) {
+                    source.removeEventListener(sseEventName, listener)
+                    return
+                }
+                if (!api.triggerEvent(elt, 'htmx:sseBeforeMessage', event)) {
+                    return
+                }
+                swap(elt, event.data)
+                api.triggerEvent(elt, 'htmx:sseMessage', event)
+            }
+
+            api.getInternalData(elt).sseEventListener = listener
+            source.addEventListener(sseEventName, listener)
+        }
+    }
+
+    if (api.getAttributeValue(elt, 'hx-trigger')) {
+        const sourceElement = api.getClosestMatch(elt, hasEventSource)
+        if (sourceElement == null) {
+            return
+        }
+        const source = api.getInternalData(sourceElement).sseEventSource
+
+        for (const triggerSpec of api.getTriggerSpecs(elt)) {
+            if (triggerSpec.trigger.slice(0, 4) !== 'sse:') {
+                continue
+            }
+            const sseEventName = triggerSpec.trigger.slice(4)
+            const listener = function (event) {
+                if (maybeCloseSSESource(sourceElement)) {
+                    return
+                }
+                if (!api.bodyContains(elt)) {
+                    source.removeEventListener(sseEventName, listener)
+                    return
+                }
+                htmx.trigger(elt, triggerSpec.trigger, event)
+                htmx.trigger(elt, 'htmx:sseMessage', event)
+            }
+
+            api.getInternalData(elt).sseEventListener = listener
+            source.addEventListener(sseEventName, listener)
+        }
+    }
+}
+
+/**
+ * @param {HTMLElement} elt
+ * @param {number} [retryCount]
+ */
+function ensureEventSourceOnElement(elt, retryCount) {
+    if (elt == null) {
+        return
+    }
+
+    if (api.getAttributeValue(elt, 'sse-connect')) {
+        const sseURL = api.getAttributeValue(elt, 'sse-connect')
+        if (sseURL == null) {
+            return
+        }
+        ensureEventSource(elt, sseURL, retryCount)
+    }
+
+    registerSSE(elt)
+}
+
+/**
+ * Opens the EventSource and reconnects with an exponential backoff, capped at 128 seconds.
+ *
+ * @param {HTMLElement} elt
+ * @param {string} url
+ * @param {number} [retryCount]
+ */
+function ensureEventSource(elt, url, retryCount) {
+    const source = htmx.createEventSource(url)
+
+    source.onerror = function (err) {
+        api.triggerErrorEvent(elt, 'htmx:sseError', {error: err, source})
+
+        if (maybeCloseSSESource(elt)) {
+            return
+        }
+
+        if (source.readyState === EventSource.CLOSED) {
+            retryCount = retryCount || 0
+            retryCount = Math.max(Math.min(retryCount * 2, 128), 1)
+            const timeout = retryCount * 500
+            window.setTimeout(function () {
+                ensureEventSourceOnElement(elt, retryCount)
+            }, timeout)
+        }
+    }
+
+    source.onopen = function () {
+        api.triggerEvent(elt, 'htmx:sseOpen', {source})
+
+        if (retryCount && retryCount > 0) {
+            const childrenToFix = elt.querySelectorAll('[sse-swap], [data-sse-swap], [hx-trigger], [data-hx-trigger]')
+            for (const child of childrenToFix) {
+                registerSSE(child)
+            }
+            retryCount = 0
+        }
+    }
+
+    api.getInternalData(elt).sseEventSource = source
+
+    const closeAttribute = api.getAttributeValue(elt, 'sse-close')
+    if (closeAttribute) {
+        source.addEventListener(closeAttribute, function () {
+            maybeCloseSSESource(elt, true)
+        })
+    }
+}
+
+/**
+ * Closes the EventSource once its element left the page, or when asked to.
+ *
+ * @param {HTMLElement} elt
+ * @param {boolean} [force]
+ * @returns {boolean}
+ */
+function maybeCloseSSESource(elt, force) {
+    if (!api.bodyContains(elt) || force) {
+        const source = api.getInternalData(elt).sseEventSource
+        if (source !== undefined) {
+            api.triggerEvent(elt, 'htmx:sseClose', {source, type: force ? 'message' : 'nodeMissing'})
+            source.close()
+        }
+        return true
+    }
+    return false
+}
+
+/**
+ * @param {HTMLElement} elt
+ * @param {string} content
+ */
+function swap(elt, content) {
+    api.withExtensions(elt, function (extension) {
+        content = extension.transformResponse(content, null, elt)
+    })
+
+    const swapSpec = api.getSwapSpecification(elt)
+    const target = api.getTarget(elt)
+    api.swap(target, content, swapSpec)
+}
+
+/**
+ * @param {HTMLElement} elt
+ * @returns {boolean}
+ */
+function hasEventSource(elt) {
+    return api.getInternalData(elt).sseEventSource != null
+}
@@ -1,4 +1,5 @@
 import htmx from './lib/htmx/htmx.esm.js'
+import './lib/htmx/ext/sse.esm.js'
 import Alpine from './lib/alpine/alpine.esm.js'
 import morph from './lib/alpine/plugin/morph.esm.js'
 
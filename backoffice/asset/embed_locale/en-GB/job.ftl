@@ -0,0 +1,22 @@
+job-list-title = Background Jobs
+job-list-head-name = Job
+job-list-head-schedule = Schedule
+job-list-head-last-run = Last Run
+job-list-head-status = Status
+job-list-head-message = Result
+job-list-head-next-run = Next Run
+job-list-head-action = Action
+job-list-action-run = Run Now
+job-list-never = Never
+job-list-not-scheduled = Not scheduled
+job-list-running = Running
+
+job-name-session-cleanup = Expired session cleanup
+job-name-stack-retention = Error stack retention
+
+job-status-running = Running
+job-status-succeeded = Succeeded
+job-status-failed = Failed
+
+job-flash-success-started = Job started
+job-flash-error-already-running = Job is already running
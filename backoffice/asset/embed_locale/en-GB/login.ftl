@@ -4,9 +4,19 @@ login-username = Username
 login-password = Password
 login-confirm-button = Login
 
+# Login Two-Factor Locale
+login-two-factor-title = Two-Factor Authentication
+login-two-factor-intro = Enter the code from your authenticator app, or one of your recovery codes.
+login-two-factor-code = Authentication code
+login-two-factor-confirm-button = Verify
+
 # Login Post Locale
 login-post-flash-success = Login success
 login-post-flash-failed = Login failed
+login-post-flash-throttled = Too many failed logins, try again in { $seconds } seconds
+login-post-flash-locked = This account is locked after too many failed logins, ask an administrator to unlock it
+login-post-flash-two-factor-invalid = Invalid authentication code
+login-post-flash-two-factor-expired = The login took too long, please enter your password again
 
 # Logout Locale
 login-logout-post-success = Logout success
\ No newline at end of file
@@ -0,0 +1,14 @@
+session-title = My Sessions
+session-title-for-user = Sessions of '{ $username }'
+session-head-created-at = Signed In
+session-head-last-seen-at = Last Seen
+session-head-user-agent = Device
+session-head-client-ip = IP Address
+session-head-action = Action
+session-current = This session
+session-unknown = Unknown
+session-empty = There are no active sessions.
+session-action-revoke = Revoke Session
+session-revoke-confirm = Are you sure you want to revoke this session?
+
+session-flash-success-revoked = Session revoked
@@ -0,0 +1,9 @@
+# Setup Form Locale
+setup-form-title = First Run Setup
+setup-form-intro = Create the first root user. The setup token is printed in the server log.
+setup-form-setup-token = Setup Token:
+setup-form-setup-token-placeholder = Setup Token
+setup-form-submit = Create Root User
+
+# Setup Route Locale
+setup-route-flash-success = Setup complete, you can now log in
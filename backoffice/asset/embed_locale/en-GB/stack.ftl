@@ -1,18 +1,80 @@
 stack-list-error-stack-title = List Error Stack
 
 stack-list-error-stack-head-id = ID
+stack-list-error-stack-head-severity = Severity
 stack-list-error-stack-head-name = Name
 stack-list-error-stack-head-summary = Summary
 stack-list-error-stack-head-reported = Reported At
+stack-list-error-stack-head-occurrences = Occurrences
+stack-list-error-stack-head-first-seen = First Seen
+stack-list-error-stack-head-last-seen = Last Seen
 stack-list-error-stack-head-action = Action
+stack-list-error-stack-head-status = Status
 
 stack-list-error-stack-action-details = View Error Details
-stack-list-error-stack-action-clear = Clear Older than 30 days
+stack-list-error-stack-action-occurrences = View Occurrences
+stack-list-error-stack-action-clear = Clear older than { $days } days
+stack-list-error-stack-action-export = Export { $format }
+
+stack-list-error-stack-filter-severity = Severity:
+stack-list-error-stack-filter-status = Status:
+stack-list-error-stack-filter-all = All
+
+stack-list-error-stack-filter-from = Seen from
+stack-list-error-stack-filter-to = Seen until
+
+stack-list-error-stack-search-placeholder = Search name or summary
+stack-list-error-stack-search-request-id = Request ID
+stack-list-error-stack-action-search = Search
+stack-list-error-stack-action-reset = Reset
+stack-list-error-stack-action-previous = Previous
+stack-list-error-stack-action-next = Next
+stack-list-error-stack-head-live = New Errors
+stack-list-error-stack-live-hint = Errors reported while this page is open appear here.
+stack-list-error-stack-page = Page { $page } of { $page_count } ({ $total } groups)
+stack-list-error-stack-search-results = Found { $found } error stacks for request { $request_id }.
+
+stack-severity-critical = Critical
+stack-severity-error = Error
+stack-severity-warning = Warning
+
+stack-status-new = New
+stack-status-acknowledged = Acknowledged
+stack-status-resolved = Resolved
+stack-status-ignored = Ignored
+stack-status-filter-unresolved = Unresolved
+stack-status-filter-all = All statuses
+
+stack-group-title = Error Group: { $name }
+stack-group-head-occurrences = Occurrences
+stack-group-head-change-status = Change Status
+stack-group-head-history = Status History
+stack-group-head-changed-at = Changed At
+stack-group-head-changed-by = Changed By
+stack-group-head-note = Note
+stack-group-note-placeholder = Optional note
+stack-group-action-change-status = Save
+stack-group-changed-by-system = Reopened automatically
+stack-group-samples-message = Showing the latest { $shown } of { $total } occurrences.
 
 stack-list-error-stack-fetch-title = Error Stack: { $name }
+stack-list-error-stack-fetch-action-group = View all occurrences
+stack-list-error-stack-fetch-action-download = Download
 
 stack-list-error-stack-fetch-head-reported = Reported At
+stack-list-error-stack-fetch-head-severity = Severity
 stack-list-error-stack-fetch-head-summary = Summary
 stack-list-error-stack-fetch-head-stack = Stack
+stack-list-error-stack-fetch-head-request = Request
+stack-list-error-stack-fetch-head-server = Server
+stack-list-error-stack-fetch-head-method = Method
+stack-list-error-stack-fetch-head-path = Path
+stack-list-error-stack-fetch-head-user-id = User ID
+stack-list-error-stack-fetch-head-request-id = Request ID
+stack-list-error-stack-fetch-head-htmx = Htmx
+stack-list-error-stack-fetch-head-headers = Headers
+
+stack-route-clear-confirm-message = Are you sure you want to clear all error stacks older than { $days } days?
 
-stack-route-logout-confirm-message = Are you sure you want to clear all error stacks older than 30 days?
\ No newline at end of file
+stack-flash-success-status = Status changed to { $status }
+stack-flash-success-clear = Cleared { $deleted } error stacks older than { $days } days
\ No newline at end of file
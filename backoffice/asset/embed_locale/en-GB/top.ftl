@@ -8,5 +8,6 @@ top-navigation-home = Home
 top-navigation-user = User
 top-navigation-url = URL Redirect
 top-navigation-stack = Stack
+top-navigation-job = Jobs
 
 top-date-time = { DATETIME($date) }
\ No newline at end of file
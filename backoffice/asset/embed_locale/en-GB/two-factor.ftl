@@ -0,0 +1,17 @@
+two-factor-title = Two-Factor Authentication
+two-factor-status-disabled = Two-factor authentication is not enabled for your account.
+two-factor-status-enabled = Two-factor authentication is enabled, { $count } recovery codes left.
+two-factor-enrol-button = Set Up Two-Factor
+two-factor-enrol-intro = Scan the QR code with your authenticator app, then enter the code it shows to finish the setup.
+two-factor-secret = Or enter this key manually:
+two-factor-code = Authentication code
+two-factor-confirm-button = Enable
+two-factor-recovery-title = Recovery Codes
+two-factor-recovery-intro = Store these codes somewhere safe. Each one can be used once instead of an authentication code, and they will not be shown again.
+two-factor-recovery-continue = Done
+two-factor-disable-intro = Enter an authentication code or a recovery code to turn two-factor authentication off.
+two-factor-disable-button = Disable
+
+two-factor-flash-success-enabled = Two-factor authentication enabled
+two-factor-flash-success-disabled = Two-factor authentication disabled
+two-factor-flash-error-invalid-code = Invalid authentication code
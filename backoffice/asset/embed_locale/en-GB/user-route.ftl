@@ -2,10 +2,17 @@ user-route-list-user-title = List of Users
 user-route-list-head-id = Id
 user-route-list-head-username = Username
 user-route-list-head-role = Role
+user-route-list-head-status = Status
+user-route-list-status-locked = Locked
+user-route-list-status-two-factor = 2FA
 
 user-route-list-action-edit = Edit User
 user-route-list-action-password = Edit Password
 user-route-list-action-sign-out = Sign Out User
+user-route-list-action-unlock = Unlock User
+user-route-list-action-two-factor = Two-Factor Authentication
+user-route-list-action-sessions = Sessions
+user-route-list-action-reset-two-factor = Reset Two-Factor
 user-route-list-action-add-user = Add User
 
 user-route-flash-edit-success = Successfully edited user id: { $user_id }
@@ -13,5 +20,10 @@ user-route-flash-password-success = Successfully edited password for user id: {
 user-route-flash-add-success = Successfully created user: { $username }
 user-route-flash-sign-out-error = Failed to sign out user id: { $user_id }
 user-route-flash-sign-out-success = Successfully signed out user id: { $user_id }
+user-route-flash-unlock-error = Failed to unlock user id: { $user_id }
+user-route-flash-unlock-success = Successfully unlocked user id: { $user_id }
+user-route-flash-reset-two-factor-error = Failed to reset two-factor authentication for user id: { $user_id }
+user-route-flash-reset-two-factor-success = Successfully reset two-factor authentication for user id: { $user_id }
 
-user-route-logout-confirm-message = Are you sure you want to log out '{ $username }' ?
\ No newline at end of file
+user-route-logout-confirm-message = Are you sure you want to log out '{ $username }' ?
+user-route-reset-two-factor-confirm-message = Are you sure you want to reset two-factor authentication for '{ $username }' ?
\ No newline at end of file
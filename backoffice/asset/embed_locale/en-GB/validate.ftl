@@ -20,6 +20,9 @@ validate-must-have-digit = Must contain at least one digit
 
 validate-password-does-not-match = Does not match
 validate-username-taken = Already taken
+validate-invalid-setup-token = Invalid setup token
+validate-url-path-taken = Already taken
+validate-invalid-url = Must be a valid URL
 
 validate-password-entropy = Password entropy score must be over { $min }, try using a password manager?
 
@@ -0,0 +1,231 @@
+use crate::common::locale::build_locale_resources;
+use crate::user::form::add_user::AddUserForm;
+use crate::user::form::edit_password_manager::EditPasswordManagerForm;
+use crate::user::repository::user_manager_repository::UserManagerRepository;
+use crate::user::role::Role;
+use crate::user::service::user_manager_service::add_user_service::AddUserService;
+use crate::user::service::user_manager_service::edit_password_service::EditPasswordService;
+use error_stack::{Report, ResultExt};
+use shared::utils::cipher::SecretCipher;
+use shared::utils::config::ConfigPointer;
+use shared::utils::context::fetch_context;
+use shared::utils::db::migration::{Migrations, migration_statuses};
+use shared::utils::db::open_read_only;
+use shared::utils::session::cookie_config;
+use shared::utils::session::storage::SessionTimeouts;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("Config error")]
+    ConfigError,
+    #[error("DB error")]
+    DbError,
+    #[error("Invalid role `{0}`, expected `root` or `user`")]
+    InvalidRole(String),
+    #[error("Validation failed:\n{0}")]
+    ValidationFailed(String),
+    #[error("User `{0}` not found")]
+    UserNotFound(String),
+    #[error("Check failed")]
+    CheckFailed,
+}
+
+fn format_messages(fields: &[(&str, &Arc<[String]>)]) -> String {
+    fields
+        .iter()
+        .flat_map(|(field, messages)| {
+            messages
+                .iter()
+                .map(move |message| format!("  {}: {}", field, message))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+async fn find_user_id(username: &str) -> Result<i64, Report<CliError>> {
+    let user_manager_repository: UserManagerRepository =
+        fetch_context().await.change_context(CliError::DbError)?;
+    user_manager_repository
+        .find_user_id(username.to_string())
+        .change_context(CliError::DbError)?
+        .ok_or_else(|| Report::new(CliError::UserNotFound(username.to_string())))
+}
+
+pub async fn create_user(
+    username: String,
+    password: String,
+    role: String,
+) -> Result<(), Report<CliError>> {
+    let role = Role::try_from(role.as_str()).map_err(|_| CliError::InvalidRole(role.clone()))?;
+    let add_user_form = AddUserForm {
+        username,
+        password: password.clone(),
+        password_confirm: password,
+        role,
+    };
+
+    let add_user_service: AddUserService =
+        fetch_context().await.change_context(CliError::DbError)?;
+    let validated = add_user_form
+        .as_validated(&add_user_service)
+        .await
+        .0
+        .map_err(|error| {
+            let messages = error.as_original_message();
+            CliError::ValidationFailed(format_messages(&[
+                ("username", &messages.username),
+                ("password", &messages.password),
+                ("password_confirm", &messages.password_confirm),
+            ]))
+        })?;
+
+    add_user_service
+        .add_user_submit(&validated)
+        .change_context(CliError::DbError)?;
+
+    println!(
+        "Created user `{}` with role `{}`",
+        validated.username.as_str(),
+        validated.role.as_stringed()
+    );
+    Ok(())
+}
+
+pub async fn reset_password(username: String, password: String) -> Result<(), Report<CliError>> {
+    let user_id = find_user_id(&username).await?;
+    let edit_password_form = EditPasswordManagerForm {
+        password: password.clone(),
+        password_confirm: password,
+    };
+
+    let validated = edit_password_form.as_validated().await.0.map_err(|error| {
+        let messages = error.as_original_message();
+        CliError::ValidationFailed(format_messages(&[
+            ("password", &messages.password),
+            ("password_confirm", &messages.password_confirm),
+        ]))
+    })?;
+
+    let edit_password_service: EditPasswordService =
+        fetch_context().await.change_context(CliError::DbError)?;
+    edit_password_service
+        .edit_password_submit(user_id, &validated)
+        .change_context(CliError::DbError)?;
+
+    println!("Password reset for `{}`", username);
+    revoke_tokens(username).await
+}
+
+pub async fn revoke_tokens(username: String) -> Result<(), Report<CliError>> {
+    let user_id = find_user_id(&username).await?;
+
+    let user_manager_repository: UserManagerRepository =
+        fetch_context().await.change_context(CliError::DbError)?;
+    user_manager_repository
+        .revoke_all_token_by_id(user_id)
+        .change_context(CliError::DbError)?;
+
+    println!("Revoked all login tokens for `{}`", username);
+    Ok(())
+}
+
+pub async fn print_config() -> Result<(), Report<CliError>> {
+    let config: ConfigPointer = fetch_context()
+        .await
+        .change_context(CliError::ConfigError)?;
+
+    print!(
+        "{}",
+        config
+            .as_redacted_toml()
+            .change_context(CliError::ConfigError)?
+    );
+    Ok(())
+}
+
+/// Verifies config, database, schema and locale resources without starting any server.
+///
+/// Only reads: a missing database or key file is reported, never created.
+pub async fn check(registered: &[&Migrations]) -> Result<(), Report<CliError>> {
+    let mut failed = false;
+    let mut report = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("[ok]   {}: {}", name, detail),
+        Err(detail) => {
+            failed = true;
+            println!("[fail] {}: {}", name, detail)
+        }
+    };
+
+    let config = fetch_context::<ConfigPointer>().await;
+    report(
+        "config",
+        config
+            .as_ref()
+            .map(|config| format!("sqlite at {}", config.sqlite.path))
+            .map_err(|err| format!("{:#}", err)),
+    );
+
+    if let Ok(config) = &config {
+        report(
+            "cipher",
+            SecretCipher::read_key_file(&config.cipher)
+                .map(|cipher| match cipher {
+                    Some(_) => "key loaded".to_string(),
+                    None => format!("{} missing, created on first start", config.cipher.key_file),
+                })
+                .map_err(|err| format!("{:#}", err)),
+        );
+    }
+
+    if let Ok(config) = &config {
+        report(
+            "session",
+            cookie_config(&config.session)
+                .and_then(|_| SessionTimeouts::new(&config.session))
+                .map(|_| format!("{} cookie keyed", config.session.cookie_name))
+                .map_err(|err| format!("{:#}", err)),
+        );
+    }
+
+    if let Ok(config) = &config {
+        match open_read_only(&config.sqlite) {
+            Ok(conn) => {
+                report("database", Ok("opened read-only".to_string()));
+                match migration_statuses(&conn, registered) {
+                    Ok(statuses) => {
+                        for status in statuses {
+                            let detail = format!(
+                                "db version {}, binary version {}",
+                                status.db_version, status.binary_version
+                            );
+                            let name = format!("schema {}", status.scope);
+                            if status.is_newer() {
+                                report(&name, Err(format!("{} (database is newer)", detail)));
+                            } else if status.is_pending() {
+                                report(&name, Err(format!("{} (run `migrate`)", detail)));
+                            } else {
+                                report(&name, Ok(detail));
+                            }
+                        }
+                    }
+                    Err(err) => report("schema", Err(format!("{:#}", err))),
+                }
+            }
+            Err(err) => report("database", Err(format!("{:#} (run `migrate`)", err))),
+        }
+    }
+
+    report(
+        "locale",
+        build_locale_resources()
+            .map(|_| "backoffice resources ok".to_string())
+            .map_err(|err| err.to_string()),
+    );
+
+    if failed {
+        return Err(Report::new(CliError::CheckFailed));
+    }
+    Ok(())
+}
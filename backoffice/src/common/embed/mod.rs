@@ -1,6 +1,6 @@
 use poem::endpoint::{EmbeddedFileEndpoint, EmbeddedFilesEndpoint};
 use rust_embed::Embed;
-use shared::utils::embed::EmbedAsString;
+use shared::utils::embed::{SharedAssetLocale, add_locale_files};
 use std::collections::HashMap;
 
 pub const EMBED_PATH: &'static str = "/assets/";
@@ -26,17 +26,8 @@ pub struct AssetLocale;
 impl AssetLocale {
     pub fn locale_map() -> HashMap<String, String> {
         let mut map = HashMap::new();
-        for value in Self::iter() {
-            let locale = value.split("/").next().unwrap_or_default();
-            let mut str = map
-                .get(locale)
-                .map(|v: &String| v.to_string())
-                .unwrap_or_default();
-            let file = Self::get(String::from(value.clone()).as_str());
-            str.push_str(&file.as_string());
-            str.push('\n');
-            map.insert(locale.to_string(), str);
-        }
+        add_locale_files::<SharedAssetLocale>(&mut map);
+        add_locale_files::<Self>(&mut map);
         map
     }
 }
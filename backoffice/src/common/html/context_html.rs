@@ -1,6 +1,9 @@
 use crate::common::html::HtmlBuilder;
 use crate::common::html::locale::top::TopBuildLocale;
-use crate::common::icon::{exclamation_circle_icon, home_icon, user_minus_icon, users_icon};
+use crate::common::icon::{
+    clock_icon, exclamation_circle_icon, home_icon, link_icon, user_minus_icon, users_icon,
+};
+use crate::stack::route::stack::{LIVE_EVENT, STACK_ROUTE};
 use crate::user::pointer::user_pointer::UserPointer;
 use crate::user::role::Role;
 use crate::user::route::login::LOGIN_ROUTE;
@@ -21,6 +24,8 @@ pub struct NavigationItem {
     locale: String,
     role: Role,
     icon: Markup,
+    /// Loaded into a badge next to the name, and reloaded on each live stack event.
+    badge_url: Option<String>,
 }
 
 impl NavigationItem {
@@ -33,6 +38,7 @@ impl NavigationItem {
                 locale: "top-navigation-home".to_string(),
                 role: Role::Visitor,
                 icon: home_icon(),
+                badge_url: None,
             },
             Self {
                 name: "User".to_string(),
@@ -41,14 +47,34 @@ impl NavigationItem {
                 locale: "top-navigation-user".to_string(),
                 role: Role::User,
                 icon: users_icon(),
+                badge_url: None,
+            },
+            Self {
+                name: "URL Redirect".to_string(),
+                url: "/shorty".to_string(),
+                tag: "id-tag-shorty".to_string(),
+                locale: "top-navigation-url".to_string(),
+                role: Role::User,
+                icon: link_icon(),
+                badge_url: None,
             },
             Self {
                 name: "Stack".to_string(),
-                url: "/stack".to_string(),
+                url: STACK_ROUTE.to_string(),
                 tag: "id-tag-stack".to_string(),
                 locale: "top-navigation-stack".to_string(),
                 role: Role::Root,
                 icon: exclamation_circle_icon(),
+                badge_url: Some(STACK_ROUTE.to_owned() + "/unread"),
+            },
+            Self {
+                name: "Jobs".to_string(),
+                url: "/job".to_string(),
+                tag: "id-tag-job".to_string(),
+                locale: "top-navigation-job".to_string(),
+                role: Role::Root,
+                icon: clock_icon(),
+                badge_url: None,
             },
         ]
         .into()
@@ -196,11 +222,14 @@ impl ContextHtmlBuilder {
                     };
                 }
 
+                // Only root can open the stack, see `must_be_root`.
+                let live_stack = self.user_id_context.role >= Role::Root;
                 let new_content = html! {
                     div #alert {
                         (flash.flash_message_html())
                     }
-                    div .wrapper {
+                    div .wrapper hx-ext=[live_stack.then_some("sse")]
+                        sse-connect=[live_stack.then(|| STACK_ROUTE.to_owned() + "/live")] {
                         div .sidebar-wrapper {
                             (self.build_navigation(current_tag))
                         }
@@ -243,21 +272,15 @@ impl ContextHtmlBuilder {
             if self.user_id_context.role < item.role {
                 continue;
             }
-            let html = if item.tag == tag {
-                html! {
-                    div .nav-item .nav-item-active id=(item.tag) {
-                        a href=(item.url) hx-push-url="true" hx-target="#main-content" {
-                            span .icon { (item.icon) }
-                            (self.locale.text_with_default(item.locale.as_str(), &item.name))
-                        }
-                    }
-                }
-            } else {
-                html! {
-                    div .nav-item id=(item.tag) {
-                        a href=(item.url) hx-push-url="true" hx-target="#main-content" {
-                            span .icon { (item.icon) }
-                            (self.locale.text_with_default(item.locale.as_str(), &item.name))
+            let html = html! {
+                div .nav-item .nav-item-active[item.tag == tag] id=(item.tag) {
+                    a href=(item.url) hx-push-url="true" hx-target="#main-content" {
+                        span .icon { (item.icon) }
+                        (self.locale.text_with_default(item.locale.as_str(), &item.name))
+                        @if let Some(badge_url) = &item.badge_url {
+                            span .nav-badge id=(format!("{}-badge", item.tag))
+                                hx-get=(badge_url) hx-trigger=(format!("load, sse:{}", LIVE_EVENT))
+                                hx-target="this" hx-swap="innerHTML" hx-push-url="false" {}
                         }
                     }
                 }
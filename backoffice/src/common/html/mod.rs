@@ -1,5 +1,6 @@
 pub mod context_html;
 pub mod locale;
+pub mod qr_code;
 pub mod validate;
 
 use crate::common::embed::AssetHidden;
@@ -0,0 +1,28 @@
+use maud::{Markup, html};
+use qrcode::{Color, QrCode};
+
+const QUIET_ZONE: usize = 4;
+
+/// Renders `data` as an inline SVG QR code, one user unit per module.
+pub fn qr_code_svg(data: &str) -> Option<Markup> {
+    let code = QrCode::new(data.as_bytes()).ok()?;
+    let width = code.width();
+    let size = width + QUIET_ZONE * 2;
+
+    let mut path = String::new();
+    for (index, color) in code.to_colors().iter().enumerate() {
+        if *color == Color::Dark {
+            let x = index % width + QUIET_ZONE;
+            let y = index / width + QUIET_ZONE;
+            path.push_str(format!("M{x} {y}h1v1h-1z").as_str());
+        }
+    }
+
+    Some(html! {
+        svg xmlns="http://www.w3.org/2000/svg" viewBox=(format!("0 0 {size} {size}"))
+            width="200" height="200" shape-rendering="crispEdges" {
+            rect width=(size) height=(size) fill="#fff" {}
+            path d=(path) fill="#000" {}
+        }
+    })
+}
@@ -23,7 +23,6 @@ pub fn flag_icon() -> Markup {
     get_icon("icon/flag.svg")
 }
 
-#[allow(dead_code)]
 pub fn trash_icon() -> Markup {
     get_icon("icon/trash.svg")
 }
@@ -48,6 +47,38 @@ pub fn home_icon() -> Markup {
     get_icon("icon/home.svg")
 }
 
+pub fn link_icon() -> Markup {
+    get_icon("icon/link.svg")
+}
+
 pub fn exclamation_circle_icon() -> Markup {
     get_icon("icon/exclamation_circle.svg")
 }
+
+pub fn lock_open_icon() -> Markup {
+    get_icon("icon/lock_open.svg")
+}
+
+pub fn shield_check_icon() -> Markup {
+    get_icon("icon/shield_check.svg")
+}
+
+pub fn shield_exclamation_icon() -> Markup {
+    get_icon("icon/shield_exclamation.svg")
+}
+
+pub fn computer_desktop_icon() -> Markup {
+    get_icon("icon/computer_desktop.svg")
+}
+
+pub fn clock_icon() -> Markup {
+    get_icon("icon/clock.svg")
+}
+
+pub fn play_icon() -> Markup {
+    get_icon("icon/play.svg")
+}
+
+pub fn arrow_down_tray_icon() -> Markup {
+    get_icon("icon/arrow_down_tray.svg")
+}
@@ -0,0 +1,24 @@
+use crate::stack::job::stack_retention_job::{STACK_RETENTION_JOB, StackRetentionJob};
+use crate::user::job::session_cleanup_job::{SESSION_CLEANUP_JOB, SessionCleanupJob};
+use error_stack::Report;
+use shared::utils::config::jobs::JobsConfig;
+use shared::utils::jobs::schedule::Schedule;
+use shared::utils::jobs::{JobError, Jobs};
+use shared::utils::session::job::{SESSION_STORE_CLEANUP_JOB, SessionStoreCleanupJob};
+
+/// Every job the backoffice runs, with schedules taken from the config.
+pub fn backoffice_jobs(config: &JobsConfig) -> Result<Jobs, Report<JobError>> {
+    Ok(Jobs::new()
+        .register::<SessionCleanupJob>(
+            SESSION_CLEANUP_JOB,
+            Schedule::parse(&config.session_cleanup_schedule)?,
+        )
+        .register::<StackRetentionJob>(
+            STACK_RETENTION_JOB,
+            Schedule::parse(&config.stack_retention_schedule)?,
+        )
+        .register::<SessionStoreCleanupJob>(
+            SESSION_STORE_CLEANUP_JOB,
+            Schedule::parse(&config.session_store_cleanup_schedule)?,
+        ))
+}
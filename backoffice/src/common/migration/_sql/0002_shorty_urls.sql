@@ -0,0 +1,9 @@
+create table if not exists shorty_urls
+(
+    id           integer primary key autoincrement not null,
+    url_path     text unique                       not null,
+    url_redirect text                              not null,
+    created_at   text                              not null,
+    created_by   integer                           not null,
+    foreign key (created_by) references backoffice_users (id)
+);
@@ -0,0 +1,11 @@
+alter table backoffice_users
+    add column locked_at text;
+
+create table login_attempts
+(
+    scope          text    not null,
+    key            text    not null,
+    failed_count   integer not null,
+    last_failed_at text    not null,
+    primary key (scope, key)
+);
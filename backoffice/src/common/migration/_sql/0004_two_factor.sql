@@ -0,0 +1,19 @@
+create table user_two_factor
+(
+    user_id        integer not null primary key,
+    secret         blob    not null,
+    enabled_at     text,
+    last_used_step integer,
+    foreign key (user_id) references backoffice_users (id) on delete cascade
+);
+
+create table user_recovery_codes
+(
+    id         integer not null primary key autoincrement,
+    user_id    integer not null,
+    code_hash  text    not null,
+    used_at    text,
+    foreign key (user_id) references backoffice_users (id) on delete cascade
+);
+
+create index user_recovery_codes_user_id on user_recovery_codes (user_id);
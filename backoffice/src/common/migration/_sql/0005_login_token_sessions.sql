@@ -0,0 +1,23 @@
+create table user_login_tokens_new
+(
+    id           integer     not null primary key autoincrement,
+    user_id      integer     not null,
+    token        text unique not null,
+    expire_after text        not null,
+    created_at   text        not null,
+    last_seen_at text        not null,
+    user_agent   text,
+    client_ip    text,
+    foreign key (user_id) references backoffice_users (id) on delete cascade
+);
+
+insert into user_login_tokens_new (user_id, token, expire_after, created_at, last_seen_at)
+select user_id, token, expire_after, datetime(expire_after, '-30 day'), datetime(expire_after, '-30 day')
+from user_login_tokens;
+
+drop table user_login_tokens;
+
+alter table user_login_tokens_new
+    rename to user_login_tokens;
+
+create index user_login_tokens_user_id on user_login_tokens (user_id);
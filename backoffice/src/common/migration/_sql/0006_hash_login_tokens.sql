@@ -0,0 +1,6 @@
+-- Tokens were stored in plain text, they cannot be hashed from SQL so every session is signed out.
+delete
+from user_login_tokens;
+
+alter table user_login_tokens
+    rename column token to token_hash;
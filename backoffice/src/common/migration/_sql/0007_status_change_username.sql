@@ -0,0 +1,5 @@
+-- Fills in the username of status changes recorded before it was stored with them.
+update error_group_status_change
+set username = (select u.username from backoffice_users u where u.id = error_group_status_change.user_id)
+where username is null
+  and user_id is not null;
@@ -0,0 +1,42 @@
+use shared::utils::db::migration::{Migration, Migrations};
+
+pub static BACKOFFICE_MIGRATIONS: Migrations = Migrations {
+    scope: "backoffice",
+    migrations: &[
+        Migration {
+            version: 1,
+            name: "backoffice_users",
+            sql: include_str!("_sql/0001_backoffice_users.sql"),
+        },
+        Migration {
+            version: 2,
+            name: "shorty_urls",
+            sql: include_str!("_sql/0002_shorty_urls.sql"),
+        },
+        Migration {
+            version: 3,
+            name: "login_attempts",
+            sql: include_str!("_sql/0003_login_attempts.sql"),
+        },
+        Migration {
+            version: 4,
+            name: "two_factor",
+            sql: include_str!("_sql/0004_two_factor.sql"),
+        },
+        Migration {
+            version: 5,
+            name: "login_token_sessions",
+            sql: include_str!("_sql/0005_login_token_sessions.sql"),
+        },
+        Migration {
+            version: 6,
+            name: "hash_login_tokens",
+            sql: include_str!("_sql/0006_hash_login_tokens.sql"),
+        },
+        Migration {
+            version: 7,
+            name: "status_change_username",
+            sql: include_str!("_sql/0007_status_change_username.sql"),
+        },
+    ],
+};
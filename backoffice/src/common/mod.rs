@@ -1,5 +1,7 @@
 pub mod embed;
 pub mod html;
 pub mod icon;
+pub mod job;
 pub mod js;
 pub mod locale;
+pub mod migration;
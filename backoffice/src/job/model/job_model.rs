@@ -0,0 +1,7 @@
+use shared::utils::jobs::JobInfo;
+use shared::utils::jobs::model::JobRun;
+
+pub struct JobOverview {
+    pub info: JobInfo,
+    pub last_run: Option<JobRun>,
+}
@@ -0,0 +1,122 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::common::icon::play_icon;
+use crate::job::route::locale::job_locale::{
+    JobFlashLocale, JobLocale, job_name_text, job_status_text,
+};
+use crate::job::service::job_service::JobService;
+use maud::{Markup, html};
+use poem::i18n::Locale;
+use poem::session::Session;
+use poem::web::{CsrfToken, Path, Redirect};
+use poem::{Response, Route, get, handler, post};
+use shared::utils::context::Dep;
+use shared::utils::csrf::{CsrfTokenHtml, csrf_header_check_strict};
+use shared::utils::error::{ExtraResultExt, FromErrorStack};
+use shared::utils::flash::{Flash, FlashMessageExt};
+use shared::utils::htmx::HtmxHeader;
+
+pub const JOB_ROUTE: &str = "/job";
+
+#[handler]
+fn list_jobs(
+    Dep(job_service): Dep<JobService>,
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    let jobs = job_service
+        .list_jobs()
+        .log_it()
+        .map_err(poem::Error::from_error_stack)?;
+    let run_icon = play_icon();
+
+    let l = &context_html_builder.locale;
+    let lc = JobLocale::new(l);
+    let title = lc.title.as_str();
+
+    Ok(context_html_builder
+        .attach_title(title)
+        .set_current_tag("id-tag-job")
+        .attach_content(html! {
+            h1 { (title) }
+            table .table-full {
+                thead {
+                    th { (lc.head_name) }
+                    th { (lc.head_schedule) }
+                    th { (lc.head_last_run) }
+                    th { (lc.head_status) }
+                    th { (lc.head_message) }
+                    th { (lc.head_next_run) }
+                    th .action { (lc.head_action) }
+                }
+                tbody {
+                    @for job in jobs.iter() {
+                        tr {
+                            td { (job_name_text(l, job.info.name)) }
+                            td { code { (job.info.schedule) } }
+                            @if let Some(last_run) = &job.last_run {
+                                td x-init="$store.util.formatToLocalTime($el)" { (last_run.started_at.to_rfc3339()) }
+                                td { (job_status_text(l, last_run.status)) }
+                                td { (last_run.message.as_deref().unwrap_or_default()) }
+                            } @else {
+                                td { (lc.never) }
+                                td {}
+                                td {}
+                            }
+                            @if let Some(next_run) = job.info.next_run {
+                                td x-init="$store.util.formatToLocalTime($el)" { (next_run.to_rfc3339()) }
+                            } @else {
+                                td { (lc.not_scheduled) }
+                            }
+                            td .action {
+                                @if job.info.running {
+                                    (lc.running)
+                                } @else {
+                                    a .icon href=(format!("{}/run/{}", JOB_ROUTE, job.info.name))
+                                        title=(lc.action_run)
+                                        hx-post=(format!("{}/run/{}", JOB_ROUTE, job.info.name)) { (run_icon) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .attach_footer(html! {
+            (csrf_token.as_html_command())
+        })
+        .build())
+}
+
+#[handler]
+fn run_job(
+    Dep(job_service): Dep<JobService>,
+    Path(job_name): Path<String>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> poem::Result<Response> {
+    let started = job_service
+        .run_now(&job_name)
+        .map_err(poem::Error::from_error_stack)?;
+
+    let flash_locale = JobFlashLocale::new(&locale);
+    session.flash(if started {
+        Flash::Success {
+            msg: flash_locale.success_started,
+        }
+    } else {
+        Flash::Error {
+            msg: flash_locale.error_already_running,
+        }
+    });
+    Ok(htmx_header.do_location(
+        Redirect::see_other(JOB_ROUTE.to_owned() + "/"),
+        "#main-content",
+    ))
+}
+
+pub fn job_route() -> Route {
+    Route::new()
+        .at("/", get(list_jobs))
+        .at("/run/:job_name", post(csrf_header_check_strict(run_job)))
+}
@@ -0,0 +1,64 @@
+use poem::i18n::Locale;
+use shared::utils::jobs::model::JobRunStatus;
+use shared::utils::locale::LocaleExt;
+
+pub struct JobLocale {
+    pub title: String,
+    pub head_name: String,
+    pub head_schedule: String,
+    pub head_last_run: String,
+    pub head_status: String,
+    pub head_message: String,
+    pub head_next_run: String,
+    pub head_action: String,
+    pub action_run: String,
+    pub never: String,
+    pub not_scheduled: String,
+    pub running: String,
+}
+
+impl JobLocale {
+    pub fn new(l: &Locale) -> Self {
+        Self {
+            title: l.text_with_default("job-list-title", "Background Jobs"),
+            head_name: l.text_with_default("job-list-head-name", "Job"),
+            head_schedule: l.text_with_default("job-list-head-schedule", "Schedule"),
+            head_last_run: l.text_with_default("job-list-head-last-run", "Last Run"),
+            head_status: l.text_with_default("job-list-head-status", "Status"),
+            head_message: l.text_with_default("job-list-head-message", "Result"),
+            head_next_run: l.text_with_default("job-list-head-next-run", "Next Run"),
+            head_action: l.text_with_default("job-list-head-action", "Action"),
+            action_run: l.text_with_default("job-list-action-run", "Run Now"),
+            never: l.text_with_default("job-list-never", "Never"),
+            not_scheduled: l.text_with_default("job-list-not-scheduled", "Not scheduled"),
+            running: l.text_with_default("job-list-running", "Running"),
+        }
+    }
+}
+
+pub fn job_name_text(l: &Locale, name: &str) -> String {
+    l.text_with_default(&format!("job-name-{}", name.replace('_', "-")), name)
+}
+
+pub fn job_status_text(l: &Locale, status: JobRunStatus) -> String {
+    match status {
+        JobRunStatus::Running => l.text_with_default("job-status-running", "Running"),
+        JobRunStatus::Succeeded => l.text_with_default("job-status-succeeded", "Succeeded"),
+        JobRunStatus::Failed => l.text_with_default("job-status-failed", "Failed"),
+    }
+}
+
+pub struct JobFlashLocale {
+    pub success_started: String,
+    pub error_already_running: String,
+}
+
+impl JobFlashLocale {
+    pub fn new(l: &Locale) -> Self {
+        Self {
+            success_started: l.text_with_default("job-flash-success-started", "Job started"),
+            error_already_running: l
+                .text_with_default("job-flash-error-already-running", "Job is already running"),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::job::model::job_model::JobOverview;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::jobs::JobScheduler;
+use shared::utils::jobs::repository::job_run_repository::JobRunRepository;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JobServiceError {
+    #[error("DB error")]
+    DbError,
+    #[error("Not found")]
+    NotFound,
+}
+
+pub struct JobService {
+    job_scheduler: JobScheduler,
+    job_run_repository: JobRunRepository,
+}
+
+impl JobService {
+    pub fn new(job_scheduler: JobScheduler, job_run_repository: JobRunRepository) -> Self {
+        Self {
+            job_scheduler,
+            job_run_repository,
+        }
+    }
+
+    pub fn list_jobs(&self) -> Result<Box<[JobOverview]>, Report<JobServiceError>> {
+        let last_runs = self
+            .job_run_repository
+            .list_last_runs()
+            .change_context(JobServiceError::DbError)?;
+        Ok(self
+            .job_scheduler
+            .jobs()
+            .into_iter()
+            .map(|info| JobOverview {
+                last_run: last_runs
+                    .iter()
+                    .find(|run| run.job_name == info.name)
+                    .cloned(),
+                info,
+            })
+            .collect())
+    }
+
+    /// Starts a job in the background, returns false when it was already running.
+    pub fn run_now(&self, job_name: &str) -> Result<bool, Report<JobServiceError>> {
+        self.job_scheduler
+            .run_now(job_name)
+            .change_context(JobServiceError::NotFound)
+            .attach(StatusCode::NOT_FOUND)
+    }
+}
+
+impl FromContext for JobService {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?, ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::job::session_cleanup_job::{SESSION_CLEANUP_JOB, SessionCleanupJob};
+    use chrono::{TimeDelta, Utc};
+    use shared::utils::config::jobs::JobsConfig;
+    use shared::utils::config::sqlite::SqliteConfig;
+    use shared::utils::db::SqliteClient;
+    use shared::utils::db::migration::SHARED_MIGRATIONS;
+    use shared::utils::jobs::Jobs;
+    use shared::utils::jobs::model::JobRunStatus;
+    use shared::utils::jobs::schedule::Schedule;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_list_jobs_pairs_last_run_by_name() {
+        let dir = std::env::temp_dir().join(format!("job-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let sqlite_client = SqliteClient::new(&SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+        sqlite_client.migrate(&SHARED_MIGRATIONS).unwrap();
+        let job_run_repository = JobRunRepository::new(sqlite_client);
+
+        let now = Utc::now();
+        let run_id = job_run_repository
+            .start_run(SESSION_CLEANUP_JOB, now, now - TimeDelta::hours(1))
+            .unwrap()
+            .unwrap();
+        job_run_repository
+            .finish_run(run_id, JobRunStatus::Succeeded, "done".to_string())
+            .unwrap();
+
+        let jobs = Jobs::new()
+            .register::<SessionCleanupJob>(
+                SESSION_CLEANUP_JOB,
+                Schedule::parse("every 1h").unwrap(),
+            )
+            .register::<SessionCleanupJob>("never_ran", Schedule::parse("every 1d").unwrap());
+        let service = JobService::new(
+            JobScheduler::new(jobs, &JobsConfig::default()).unwrap(),
+            job_run_repository,
+        );
+        let overviews = service.list_jobs().unwrap();
+        assert_eq!(overviews.len(), 2);
+        assert_eq!(overviews[0].info.name, SESSION_CLEANUP_JOB);
+        let last_run = overviews[0].last_run.as_ref().unwrap();
+        assert_eq!(last_run.id, run_id);
+        assert_eq!(last_run.status, JobRunStatus::Succeeded);
+        assert_eq!(overviews[1].info.name, "never_ran");
+        assert!(overviews[1].last_run.is_none());
+        drop(service);
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_now_unknown_job_not_found() {
+        let dir = std::env::temp_dir().join(format!("job-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let sqlite_client = SqliteClient::new(&SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let service = JobService::new(
+            JobScheduler::new(Jobs::new(), &JobsConfig::default()).unwrap(),
+            JobRunRepository::new(sqlite_client),
+        );
+        let err = service.run_now("missing").unwrap_err();
+        assert!(matches!(err.current_context(), JobServiceError::NotFound));
+        assert_eq!(
+            err.downcast_ref::<StatusCode>(),
+            Some(&StatusCode::NOT_FOUND)
+        );
+        drop(service);
+        remove_dir_all(&dir).unwrap();
+    }
+}
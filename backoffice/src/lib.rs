@@ -1,29 +1,60 @@
+pub(crate) mod cli;
 pub(crate) mod common;
 pub(crate) mod home;
+pub(crate) mod job;
+pub(crate) mod setup;
+pub(crate) mod shorty;
 pub(crate) mod stack;
 pub(crate) mod user;
 
 use crate::common::embed::{AssetFilesEndPoint, EMBED_PATH};
+use crate::common::html::HtmlBuilder;
+use crate::common::job::backoffice_jobs;
 use crate::common::locale::build_locale_resources;
 use crate::home::home_route;
+use crate::job::route::job::{JOB_ROUTE, job_route};
+use crate::setup::guard::setup_redirect;
+use crate::setup::route::setup::{SETUP_ROUTE, setup_route};
+use crate::setup::service::setup_service::SetupService;
+use crate::shorty::route::shorty::{SHORTY_ROUTE, shorty_route};
 use crate::stack::route::stack::{STACK_ROUTE, stack_route};
+use crate::user::pointer::user_pointer::UserPointer;
+use crate::user::role::Role;
 use crate::user::role::user_role_check::must_be_root;
 use crate::user::role::visitor_only::visitor_redirect;
 use crate::user::route::login::login_route;
 use crate::user::route::user::{USER_ROUTE, user_route};
 use error_stack::{Report, ResultExt};
+use log::warn;
+use maud::html;
+use poem::i18n::Locale;
 use poem::listener::TcpListener;
 use poem::middleware::{CatchPanic, CookieJarManager, Csrf};
-use poem::session::{CookieConfig, CookieSession};
-use poem::{EndpointExt, IntoResponse, Server};
-use shared::utils::config::Config;
+use poem::{Endpoint, EndpointExt, FromRequest, IntoResponse, Request, Response, Server};
+use shared::utils::cipher::SecretCipher;
+use shared::utils::config::{Config, ConfigPointer};
+use shared::utils::context::fetch_context;
 use shared::utils::embed::enforce_min_js_on_prod;
 use shared::utils::error::boot_error::MainError;
-use shared::utils::log::log_poem_error;
-use shared::utils::request_cache::init_request_cache;
+use shared::utils::error::error_page::internal_error_page;
+use shared::utils::jobs::JobScheduler;
+use shared::utils::log::access_log::access_log;
+use shared::utils::log::model::RequestContext;
+use shared::utils::log::panic::catch_panic;
+use shared::utils::log::scope::log_scope;
+use shared::utils::log::{log_panic, log_poem_error};
+use shared::utils::request_cache::{RequestCache, RequestCacheExt, init_request_cache};
+use shared::utils::request_id::init_request_id;
+use shared::utils::session::SessionMiddleware;
+use std::sync::Arc;
 use user::route::login::LOGIN_ROUTE;
 
 pub mod export {
+    pub use crate::cli::{
+        CliError, check, create_user, print_config, reset_password, revoke_tokens,
+    };
+    pub use crate::common::migration::BACKOFFICE_MIGRATIONS;
+    pub use shared::utils::db::migration::run_migrations;
     pub use shared::utils::error::boot_error::MainError;
     pub use shared::utils::log::init_log;
 }
@@ -33,24 +64,60 @@ pub async fn boot() -> Result<(), Report<MainError>> {
         .await
         .change_context(MainError::ConfigError)?;
 
+    let config_pointer: ConfigPointer = fetch_context()
+        .await
+        .change_context(MainError::ConfigError)?;
+    let session: SessionMiddleware = fetch_context()
+        .await
+        .change_context(MainError::ConfigError)?;
+
+    fetch_context::<SecretCipher>()
+        .await
+        .change_context(MainError::ConfigError)?;
+
+    if let Some(setup_token) = fetch_context::<SetupService>()
+        .await
+        .change_context(MainError::DbError)?
+        .begin_setup()
+        .change_context(MainError::DbError)?
+    {
+        warn!("No root user exists, open {SETUP_ROUTE}/ on the backoffice to create one.");
+        warn!("Setup token: {setup_token}");
+    }
+
+    JobScheduler::start(
+        backoffice_jobs(&config_pointer.jobs).change_context(MainError::ConfigError)?,
+        &config_pointer.jobs,
+    )
+    .change_context(MainError::ConfigError)?;
+
     let route = home_route();
 
     let route = route
+        .nest(SETUP_ROUTE, setup_route())
         .nest(LOGIN_ROUTE, login_route())
         .nest(USER_ROUTE, visitor_redirect(user_route()))
+        .nest(SHORTY_ROUTE, visitor_redirect(shorty_route()))
         .nest(STACK_ROUTE, visitor_redirect(must_be_root(stack_route())))
+        .nest(JOB_ROUTE, visitor_redirect(must_be_root(job_route())))
         .nest(
             EMBED_PATH,
             enforce_min_js_on_prod(AssetFilesEndPoint::new()),
         );
 
-    let route = route
-        .around(init_request_cache)
-        .data(build_locale_resources().change_context(MainError::LocaleError)?)
+    // `catch_all_error` wraps every layer that can fail. Only the layers it reads from
+    // sit outside it, and those turn errors into responses or never fail.
+    let route = setup_redirect(route)
         .with(CookieJarManager::new())
-        .with(CookieSession::new(CookieConfig::new()))
+        .with(session)
         .with(Csrf::new())
-        .catch_all_error(catch_all_error)
+        .around(catch_all_error);
+
+    let route = log_scope(SERVER_NAME, access_log(route))
+        .around(init_request_id)
+        .around(init_request_cache)
+        .data(build_locale_resources().change_context(MainError::LocaleError)?)
+        .data(Arc::clone(&config_pointer.poem_backoffice))
         .with(CatchPanic::new());
 
     match config.upgrade() {
@@ -68,7 +135,38 @@ pub async fn boot() -> Result<(), Report<MainError>> {
     }
 }
 
-async fn catch_all_error(err: poem::Error) -> impl IntoResponse {
-    log_poem_error(&err).await;
-    err.into_response()
+const SERVER_NAME: &str = "backoffice";
+
+async fn catch_all_error<EP: Endpoint>(next: EP, req: Request) -> poem::Result<Response> {
+    let mut request_context = RequestContext::new(SERVER_NAME, &req);
+    let request_cache = req.request_cache();
+    let locale = Locale::from_request_without_body(&req).await.ok();
+    // Boxed, with the session and CSRF layers inside the future overflows the worker
+    // stack in debug builds.
+    match catch_panic(Box::pin(next.call(req))).await {
+        Ok(Ok(resp)) => Ok(resp.into_response()),
+        Ok(Err(err)) => {
+            request_context.user_id = cached_user_id(request_cache).await;
+            log_poem_error(&err, &request_context).await;
+            Ok(err.into_response())
+        }
+        Err(log_data) => {
+            request_context.user_id = cached_user_id(request_cache).await;
+            log_panic(&log_data, &request_context).await;
+            Ok(internal_error_page(
+                locale.as_ref(),
+                request_context.request_id.as_deref(),
+                |title, content| {
+                    HtmlBuilder::new(title, html! { div .container .main-content { (content) } })
+                        .build()
+                },
+            ))
+        }
+    }
+}
+
+/// Only known when the handler already looked up the user.
+async fn cached_user_id(request_cache: Option<RequestCache>) -> Option<i64> {
+    let user_pointer = request_cache?.get::<UserPointer>().await?;
+    (user_pointer.role != Role::Visitor).then_some(user_pointer.id)
 }
@@ -0,0 +1,26 @@
+use poem::i18n::Locale;
+use shared::utils::locale::LocaleExt;
+
+pub struct SetupFormLocale {
+    pub title: String,
+    pub intro: String,
+    pub setup_token: String,
+    pub setup_token_placeholder: String,
+    pub submit: String,
+}
+
+impl SetupFormLocale {
+    pub fn new(locale: &Locale) -> Self {
+        Self {
+            title: locale.text_with_default("setup-form-title", "First Run Setup"),
+            intro: locale.text_with_default(
+                "setup-form-intro",
+                "Create the first root user. The setup token is printed in the server log.",
+            ),
+            setup_token: locale.text_with_default("setup-form-setup-token", "Setup Token:"),
+            setup_token_placeholder: locale
+                .text_with_default("setup-form-setup-token-placeholder", "Setup Token"),
+            submit: locale.text_with_default("setup-form-submit", "Create Root User"),
+        }
+    }
+}
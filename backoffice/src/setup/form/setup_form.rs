@@ -0,0 +1,129 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::common::html::validate::ValidateErrorMessageExt;
+use crate::setup::form::locale::SetupFormLocale;
+use crate::setup::rule::setup::IsSetupTokenValid;
+use crate::user::form::add_user::{AddUserError, AddUserForm, AddUserMessage, AddUserValidated};
+use crate::user::form::locale::UserFormLocale;
+use crate::user::role::Role;
+use cjtoolkit_structured_validator::types::username::IsUsernameTakenAsync;
+use maud::{Markup, html};
+use poem::i18n::Locale;
+use serde::{Deserialize, Serialize};
+use shared::utils::locale::LocaleExt;
+use std::sync::Arc;
+
+#[derive(Deserialize, Default)]
+pub struct SetupForm {
+    pub setup_token: String,
+    pub username: String,
+    pub password: String,
+    pub password_confirm: String,
+}
+
+impl SetupForm {
+    fn as_add_user_form(&self) -> AddUserForm {
+        AddUserForm {
+            username: self.username.clone(),
+            password: self.password.clone(),
+            password_confirm: self.password_confirm.clone(),
+            role: Role::Root,
+        }
+    }
+
+    pub async fn as_validated<T: IsUsernameTakenAsync + IsSetupTokenValid>(
+        &self,
+        service: &T,
+    ) -> SetupResult {
+        let setup_token_valid = service.is_setup_token_valid(self.setup_token.trim());
+        let add_user_result = self.as_add_user_form().as_validated(service).await.0;
+
+        SetupResult(match (setup_token_valid, add_user_result) {
+            (true, Ok(validated)) => Ok(validated),
+            (setup_token_valid, add_user_result) => Err(SetupError {
+                setup_token_valid,
+                add_user: add_user_result.err(),
+            }),
+        })
+    }
+
+    pub async fn as_form_html(
+        &self,
+        context_html_builder: &ContextHtmlBuilder,
+        errors: Option<SetupMessage>,
+        token: Option<Markup>,
+    ) -> Markup {
+        let errors = errors.unwrap_or_default();
+        let setup_form_locale = SetupFormLocale::new(&context_html_builder.locale);
+        let user_form_locale = UserFormLocale::new(&context_html_builder.locale);
+        let token = token.unwrap_or_default();
+        context_html_builder
+            .attach_title(&setup_form_locale.title)
+            .attach_content(html! {
+                h1 .mt-3 { (setup_form_locale.title) }
+                p { (setup_form_locale.intro) }
+                form .form method="post" {
+                    (token)
+                    div .form-group {
+                        label .label for="setup-token" { (setup_form_locale.setup_token) }
+                        input .form-item .w-full type="password" name="setup_token" #setup-token
+                        placeholder=(setup_form_locale.setup_token_placeholder) {}
+                        (errors.setup_token.into_error_html())
+                    }
+                    div .form-group {
+                        label .label for="username" { (user_form_locale.username) }
+                        input .form-item .w-full type="text" name="username" #username value=(self.username)
+                        placeholder=(user_form_locale.username_placeholder) {}
+                        (errors.add_user.username.into_error_html())
+                    }
+                    div .form-group {
+                        label .label for="password" { (user_form_locale.password) }
+                        input .form-item .w-full type="password" name="password" #password
+                        placeholder=(user_form_locale.password_placeholder) {}
+                        (errors.add_user.password.into_error_html())
+                    }
+                    div .form-group {
+                        label .label for="password-confirm" { (user_form_locale.password_confirm) }
+                        input .form-item .w-full type="password" name="password_confirm" #password-confirm
+                        placeholder=(user_form_locale.password_confirm_placeholder) {}
+                        (errors.add_user.password_confirm.into_error_html())
+                    }
+                    div .form-group {
+                        input .btn .btn-sky-blue type="submit" value=(setup_form_locale.submit) {}
+                    }
+                }
+            })
+            .build()
+    }
+}
+
+pub struct SetupError {
+    pub setup_token_valid: bool,
+    pub add_user: Option<AddUserError>,
+}
+
+impl SetupError {
+    pub fn as_message(&self, locale: &Locale) -> SetupMessage {
+        SetupMessage {
+            setup_token: if self.setup_token_valid {
+                Arc::new([])
+            } else {
+                Arc::new([
+                    locale.text_with_default("validate-invalid-setup-token", "Invalid setup token")
+                ])
+            },
+            add_user: self
+                .add_user
+                .as_ref()
+                .map(|add_user| add_user.as_message(locale))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+pub struct SetupResult(pub Result<AddUserValidated, SetupError>);
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct SetupMessage {
+    pub setup_token: Arc<[String]>,
+    pub add_user: AddUserMessage,
+}
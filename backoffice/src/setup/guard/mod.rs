@@ -0,0 +1,58 @@
+use crate::common::embed::EMBED_PATH;
+use crate::setup::route::setup::SETUP_ROUTE;
+use crate::setup::service::setup_service::SetupService;
+use poem::web::Redirect;
+use poem::{Endpoint, Error, FromRequest, IntoEndpoint, IntoResponse, Request};
+use shared::utils::context::Dep;
+
+struct SetupRedirect<E: Endpoint>(E);
+
+impl<E: Endpoint> Endpoint for SetupRedirect<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        let path = req.uri().path();
+        if !path.starts_with(SETUP_ROUTE) && !path.starts_with(EMBED_PATH) {
+            let Dep(setup_service) = Dep::<SetupService>::from_request_without_body(&req).await?;
+            if setup_service.is_setup_pending() {
+                return Err(Error::from_response(
+                    Redirect::see_other(SETUP_ROUTE.to_owned() + "/").into_response(),
+                ));
+            }
+        }
+        self.0.call(req).await
+    }
+}
+
+/// Sends every request to the setup page while no root user exists.
+pub fn setup_redirect<E>(endpoint: E) -> impl Endpoint
+where
+    E: IntoEndpoint,
+    E::Endpoint: 'static,
+{
+    SetupRedirect(endpoint.into_endpoint())
+}
+
+struct SetupOnly<E: Endpoint>(E);
+
+impl<E: Endpoint> Endpoint for SetupOnly<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        let Dep(setup_service) = Dep::<SetupService>::from_request_without_body(&req).await?;
+        if !setup_service.is_setup_pending() {
+            return Err(Error::from_response(
+                Redirect::see_other("/").into_response(),
+            ));
+        }
+        self.0.call(req).await
+    }
+}
+
+pub fn setup_only<E>(endpoint: E) -> impl Endpoint
+where
+    E: IntoEndpoint,
+    E::Endpoint: 'static,
+{
+    SetupOnly(endpoint.into_endpoint())
+}
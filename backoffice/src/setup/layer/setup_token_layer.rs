@@ -0,0 +1,69 @@
+use error_stack::Report;
+use shared::utils::context::{Context, ContextError, FromContext};
+use std::sync::{LazyLock, RwLock};
+use uuid::Uuid;
+
+/// Setup token of the running process, only set while no root user exists.
+static SETUP_TOKEN: LazyLock<RwLock<Option<String>>> = LazyLock::new(|| RwLock::new(None));
+
+#[mry::mry]
+pub struct SetupTokenLayer {}
+
+impl SetupTokenLayer {
+    pub fn new() -> Self {
+        Self {
+            mry: Default::default(),
+        }
+    }
+}
+
+#[mry::mry]
+impl SetupTokenLayer {
+    pub fn generate_token(&self) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        if let Ok(mut setup_token) = SETUP_TOKEN.write() {
+            *setup_token = Some(token.clone());
+        }
+        token
+    }
+
+    pub fn is_pending(&self) -> bool {
+        SETUP_TOKEN
+            .read()
+            .map(|setup_token| setup_token.is_some())
+            .unwrap_or_default()
+    }
+
+    pub fn verify_token(&self, token: &str) -> bool {
+        SETUP_TOKEN
+            .read()
+            .map(|setup_token| match setup_token.as_deref() {
+                Some(setup_token) => constant_time_eq(setup_token.as_bytes(), token.as_bytes()),
+                None => false,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn clear_token(&self) {
+        if let Ok(mut setup_token) = SETUP_TOKEN.write() {
+            *setup_token = None;
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+impl SetupTokenLayer {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {})
+    }
+}
+
+impl FromContext for SetupTokenLayer {
+    async fn from_context(_ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new())
+    }
+}
@@ -0,0 +1,6 @@
+pub mod form;
+pub mod guard;
+pub mod layer;
+pub mod route;
+pub mod rule;
+pub mod service;
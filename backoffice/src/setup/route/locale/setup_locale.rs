@@ -0,0 +1,17 @@
+use poem::i18n::Locale;
+use shared::utils::locale::LocaleExt;
+
+pub struct SetupFlashLocale {
+    pub success_setup: String,
+}
+
+impl SetupFlashLocale {
+    pub fn new(locale: &Locale) -> Self {
+        Self {
+            success_setup: locale.text_with_default(
+                "setup-route-flash-success",
+                "Setup complete, you can now log in",
+            ),
+        }
+    }
+}
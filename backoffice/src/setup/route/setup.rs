@@ -0,0 +1,87 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::setup::form::setup_form::SetupForm;
+use crate::setup::guard::setup_only;
+use crate::setup::route::locale::setup_locale::SetupFlashLocale;
+use crate::setup::service::setup_service::SetupService;
+use crate::user::route::login::LOGIN_ROUTE;
+use maud::Markup;
+use poem::http::StatusCode;
+use poem::session::Session;
+use poem::web::{CsrfToken, Redirect};
+use poem::{Error, IntoResponse, Response, Route, get, handler};
+use shared::utils::context::Dep;
+use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml};
+use shared::utils::error::{ExtraResultExt, FromErrorStack};
+use shared::utils::flash::{Flash, FlashMessageExt};
+
+pub const SETUP_ROUTE: &str = "/setup";
+
+enum PostResponse {
+    Validation(Markup),
+}
+
+impl IntoResponse for PostResponse {
+    fn into_response(self) -> Response {
+        match self {
+            PostResponse::Validation(validation) => validation
+                .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+                .into_response(),
+        }
+    }
+}
+
+#[handler]
+async fn setup_get(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    csrf_token: &CsrfToken,
+) -> Markup {
+    SetupForm::default()
+        .as_form_html(
+            &context_html_builder,
+            None,
+            Some(csrf_token.as_html_input()),
+        )
+        .await
+}
+
+#[handler]
+async fn setup_post(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(setup_service): Dep<SetupService>,
+    CsrfFormQs(setup_form): CsrfFormQs<SetupForm>,
+    session: &Session,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Response> {
+    let validated_result = setup_form.as_validated(&setup_service).await.0;
+    match validated_result {
+        Ok(validated) => {
+            setup_service
+                .complete_setup(&validated)
+                .log_it()
+                .map_err(Error::from_error_stack)?;
+
+            session.flash(Flash::Success {
+                msg: SetupFlashLocale::new(&context_html_builder.locale).success_setup,
+            });
+            Ok(Redirect::see_other(LOGIN_ROUTE.to_owned() + "/").into_response())
+        }
+        Err(error) => {
+            let errors = error.as_message(&context_html_builder.locale);
+            context_html_builder.attach_form_flash_error();
+            Ok(PostResponse::Validation(
+                setup_form
+                    .as_form_html(
+                        &context_html_builder,
+                        Some(errors),
+                        Some(csrf_token.as_html_input()),
+                    )
+                    .await,
+            )
+            .into_response())
+        }
+    }
+}
+
+pub fn setup_route() -> Route {
+    Route::new().at("/", setup_only(get(setup_get).post(setup_post)))
+}
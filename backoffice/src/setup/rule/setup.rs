@@ -0,0 +1,3 @@
+pub trait IsSetupTokenValid {
+    fn is_setup_token_valid(&self, setup_token: &str) -> bool;
+}
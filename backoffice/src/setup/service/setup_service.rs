@@ -0,0 +1,269 @@
+use crate::setup::layer::setup_token_layer::SetupTokenLayer;
+use crate::setup::rule::setup::IsSetupTokenValid;
+use crate::user::form::add_user::AddUserValidated;
+use crate::user::layer::password_layer::PasswordLayer;
+use crate::user::repository::user_manager_repository::UserManagerRepository;
+use cjtoolkit_structured_validator::types::username::IsUsernameTakenAsync;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::error::ExtraResultExt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SetupServiceError {
+    #[error("DB error")]
+    DbError,
+    #[error("Setup already complete")]
+    AlreadyComplete,
+    #[error("Password Hash Error")]
+    PasswordHashError,
+    #[error("Password Serialize Error")]
+    PasswordSerializeError,
+}
+
+pub struct SetupService {
+    user_manager_repository: UserManagerRepository,
+    password_layer: PasswordLayer,
+    setup_token_layer: SetupTokenLayer,
+}
+
+impl SetupService {
+    pub fn new(
+        user_manager_repository: UserManagerRepository,
+        password_layer: PasswordLayer,
+        setup_token_layer: SetupTokenLayer,
+    ) -> Self {
+        Self {
+            user_manager_repository,
+            password_layer,
+            setup_token_layer,
+        }
+    }
+
+    /// Generates the setup token when no root user exists yet.
+    pub fn begin_setup(&self) -> Result<Option<String>, Report<SetupServiceError>> {
+        let root_exists = self
+            .user_manager_repository
+            .root_exists()
+            .change_context(SetupServiceError::DbError)?;
+        if root_exists {
+            self.setup_token_layer.clear_token();
+            return Ok(None);
+        }
+        Ok(Some(self.setup_token_layer.generate_token()))
+    }
+
+    pub fn is_setup_pending(&self) -> bool {
+        if !self.setup_token_layer.is_pending() {
+            return false;
+        }
+        match self.user_manager_repository.root_exists() {
+            Ok(true) => {
+                // A root user was created some other way, e.g. from the command line.
+                self.setup_token_layer.clear_token();
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Creates the root user, unless one exists; the insert checks again, so of two
+    /// concurrent submissions only one adds a user.
+    pub fn complete_setup(
+        &self,
+        add_user_validated: &AddUserValidated,
+    ) -> Result<(), Report<SetupServiceError>> {
+        let root_exists = self
+            .user_manager_repository
+            .root_exists()
+            .change_context(SetupServiceError::DbError)?;
+        if root_exists {
+            return Err(self.already_complete());
+        }
+
+        let password = self
+            .password_layer
+            .hash_password(add_user_validated.password.as_str())
+            .change_context(SetupServiceError::PasswordHashError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?
+            .encode_to_msg_pack()
+            .change_context(SetupServiceError::PasswordSerializeError)
+            .log_it()
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let added = self
+            .user_manager_repository
+            .add_first_root(add_user_validated.username.as_str().to_string(), password)
+            .change_context(SetupServiceError::DbError)?;
+        if !added {
+            return Err(self.already_complete());
+        }
+        self.setup_token_layer.clear_token();
+        Ok(())
+    }
+
+    fn already_complete(&self) -> Report<SetupServiceError> {
+        self.setup_token_layer.clear_token();
+        Report::new(SetupServiceError::AlreadyComplete).attach(StatusCode::CONFLICT)
+    }
+}
+
+impl IsUsernameTakenAsync for SetupService {
+    async fn is_username_taken_async(&self, username: &str) -> bool {
+        self.user_manager_repository
+            .username_taken(username.to_string())
+            .ok()
+            .unwrap_or_default()
+    }
+}
+
+impl IsSetupTokenValid for SetupService {
+    fn is_setup_token_valid(&self, setup_token: &str) -> bool {
+        self.setup_token_layer.verify_token(setup_token)
+    }
+}
+
+impl FromContext for SetupService {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(
+            ctx.inject().await?,
+            ctx.inject().await?,
+            ctx.inject().await?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mry::Any;
+    use shared::utils::password::Password;
+
+    #[test]
+    fn test_begin_setup_without_root() {
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        let password_layer = PasswordLayer::new_mock();
+        let mut setup_token_layer = SetupTokenLayer::new_mock();
+
+        user_manager_repository
+            .mock_root_exists()
+            .returns_once(Ok(false));
+        setup_token_layer
+            .mock_generate_token()
+            .returns_once("token".to_string());
+
+        let service = SetupService::new(user_manager_repository, password_layer, setup_token_layer);
+        let result = service.begin_setup();
+        assert_eq!(result.unwrap(), Some("token".to_string()));
+    }
+
+    #[test]
+    fn test_begin_setup_with_root() {
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        let password_layer = PasswordLayer::new_mock();
+        let mut setup_token_layer = SetupTokenLayer::new_mock();
+
+        user_manager_repository
+            .mock_root_exists()
+            .returns_once(Ok(true));
+        setup_token_layer.mock_clear_token().returns_once(());
+
+        let service = SetupService::new(user_manager_repository, password_layer, setup_token_layer);
+        let result = service.begin_setup();
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_is_setup_pending_cleared_when_root_exists() {
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        let password_layer = PasswordLayer::new_mock();
+        let mut setup_token_layer = SetupTokenLayer::new_mock();
+
+        setup_token_layer.mock_is_pending().returns_once(true);
+        user_manager_repository
+            .mock_root_exists()
+            .returns_once(Ok(true));
+        setup_token_layer.mock_clear_token().returns_once(());
+
+        let service = SetupService::new(user_manager_repository, password_layer, setup_token_layer);
+        assert!(!service.is_setup_pending());
+    }
+
+    #[test]
+    fn test_complete_setup_success() {
+        let add_user_validated = AddUserValidated::new_test_data();
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+        let mut setup_token_layer = SetupTokenLayer::new_mock();
+
+        user_manager_repository
+            .mock_root_exists()
+            .returns_once(Ok(false));
+        password_layer
+            .mock_hash_password(add_user_validated.password.as_str())
+            .returns_once(Ok(Password::Version1 {
+                argon2: "argon2".to_string(),
+            }));
+        user_manager_repository
+            .mock_add_first_root(add_user_validated.username.as_str().to_string(), Any)
+            .returns_once(Ok(true));
+        setup_token_layer.mock_clear_token().returns_once(());
+
+        let service = SetupService::new(user_manager_repository, password_layer, setup_token_layer);
+        let result = service.complete_setup(&add_user_validated);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_complete_setup_root_added_meanwhile() {
+        let add_user_validated = AddUserValidated::new_test_data();
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+        let mut setup_token_layer = SetupTokenLayer::new_mock();
+
+        user_manager_repository
+            .mock_root_exists()
+            .returns_once(Ok(false));
+        password_layer
+            .mock_hash_password(add_user_validated.password.as_str())
+            .returns_once(Ok(Password::Version1 {
+                argon2: "argon2".to_string(),
+            }));
+        user_manager_repository
+            .mock_add_first_root(add_user_validated.username.as_str().to_string(), Any)
+            .returns_once(Ok(false));
+        setup_token_layer.mock_clear_token().returns_once(());
+
+        let service = SetupService::new(user_manager_repository, password_layer, setup_token_layer);
+        let result = service.complete_setup(&add_user_validated);
+        let result = result.err().unwrap();
+        assert!(matches!(
+            result.current_context(),
+            SetupServiceError::AlreadyComplete
+        ));
+        assert_eq!(
+            result.downcast_ref::<StatusCode>().unwrap(),
+            &StatusCode::CONFLICT
+        );
+    }
+
+    #[test]
+    fn test_complete_setup_already_complete() {
+        let add_user_validated = AddUserValidated::new_test_data();
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        let password_layer = PasswordLayer::new_mock();
+        let mut setup_token_layer = SetupTokenLayer::new_mock();
+
+        user_manager_repository
+            .mock_root_exists()
+            .returns_once(Ok(true));
+        setup_token_layer.mock_clear_token().returns_once(());
+
+        let service = SetupService::new(user_manager_repository, password_layer, setup_token_layer);
+        let result = service.complete_setup(&add_user_validated);
+        let result = result.err().unwrap();
+        let error_code = result.downcast_ref::<StatusCode>().unwrap();
+        assert_eq!(error_code, &StatusCode::CONFLICT);
+    }
+}
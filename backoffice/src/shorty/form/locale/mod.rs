@@ -0,0 +1,28 @@
+use poem::i18n::Locale;
+use shared::utils::locale::LocaleExt;
+
+pub struct ShortyFormLocale {
+    pub title_add: String,
+    pub title_edit: String,
+    pub url_path: String,
+    pub url_path_placeholder: String,
+    pub url_redirect: String,
+    pub url_redirect_placeholder: String,
+    pub submit_button: String,
+}
+
+impl ShortyFormLocale {
+    pub fn new(locale: &Locale) -> Self {
+        Self {
+            title_add: locale.text_with_default("shorty-form-title-add", "Add URL"),
+            title_edit: locale.text_with_default("shorty-form-title-edit", "Edit URL"),
+            url_path: locale.text_with_default("shorty-form-url-path", "Path:"),
+            url_path_placeholder: locale
+                .text_with_default("shorty-form-url-path-placeholder", "Path"),
+            url_redirect: locale.text_with_default("shorty-form-url-redirect", "Redirect To:"),
+            url_redirect_placeholder: locale
+                .text_with_default("shorty-form-url-redirect-placeholder", "Redirect To"),
+            submit_button: locale.text_with_default("shorty-form-submit-button", "Save"),
+        }
+    }
+}
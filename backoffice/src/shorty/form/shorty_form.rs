@@ -0,0 +1,134 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::common::html::validate::ValidateErrorMessageExt;
+use crate::shorty::form::locale::ShortyFormLocale;
+use crate::shorty::rule::shorty::{
+    IsUrlPathTakenAsync, UrlPathShortyRulesExt, UrlRedirectShortyRulesExt,
+};
+use cjtoolkit_structured_validator::common::flag_error::FlagCounter;
+use cjtoolkit_structured_validator::types::name::{Name, NameError};
+use cjtoolkit_structured_validator::types::url::{Url, UrlError};
+use maud::{Markup, html};
+use poem::i18n::Locale;
+use serde::{Deserialize, Serialize};
+use shared::utils::flag::Flag;
+use shared::utils::locale::LocaleExtForResult;
+use std::sync::Arc;
+
+#[derive(Deserialize, Default)]
+pub struct ShortyForm {
+    pub url_path: String,
+    pub url_redirect: String,
+}
+
+impl ShortyForm {
+    pub async fn as_validated<T: IsUrlPathTakenAsync>(
+        &self,
+        service: &T,
+        current_url_path: Option<&str>,
+    ) -> ShortyResult {
+        ShortyResult(
+            async {
+                let mut flag = FlagCounter::new();
+
+                let url_path = flag.check(
+                    Name::parse_url_path(Some(self.url_path.trim()), service, current_url_path)
+                        .await,
+                );
+                let url_redirect =
+                    flag.check(Url::parse_url_redirect(Some(self.url_redirect.trim())));
+
+                if flag.is_flagged() {
+                    return Err(ShortyError {
+                        url_path,
+                        url_redirect,
+                    });
+                }
+
+                Ok(ShortyValidated {
+                    url_path: url_path.expect("Url Path is not empty"),
+                    url_redirect: url_redirect.expect("Url Redirect is not empty"),
+                })
+            }
+            .await,
+        )
+    }
+
+    pub async fn as_form_html(
+        &self,
+        context_html_builder: &ContextHtmlBuilder,
+        flag: Flag,
+        errors: Option<ShortyMessage>,
+        token: Option<Markup>,
+    ) -> Markup {
+        let errors = errors.unwrap_or_default();
+        let shorty_form_locale = ShortyFormLocale::new(&context_html_builder.locale);
+        let title = if flag.is_edit() {
+            &shorty_form_locale.title_edit
+        } else {
+            &shorty_form_locale.title_add
+        };
+        let token = token.unwrap_or_default();
+        context_html_builder
+            .attach_title(title)
+            .set_current_tag("id-tag-shorty")
+            .attach_content(html! {
+                h1 .mt-3 { (title) }
+                form hx-boost="true" hx-target="#main-content" .form method="post" {
+                    (token)
+                    div .form-group {
+                        label .label for="url-path" { (shorty_form_locale.url_path) }
+                        input .form-item .w-full type="text" name="url_path" #url-path value=(self.url_path)
+                        placeholder=(shorty_form_locale.url_path_placeholder) {}
+                        (errors.url_path.into_error_html())
+                    }
+                    div .form-group {
+                        label .label for="url-redirect" { (shorty_form_locale.url_redirect) }
+                        input .form-item .w-full type="url" name="url_redirect" #url-redirect value=(self.url_redirect)
+                        placeholder=(shorty_form_locale.url_redirect_placeholder) {}
+                        (errors.url_redirect.into_error_html())
+                    }
+                    div .form-group {
+                        input .btn .btn-sky-blue type="submit" value=(shorty_form_locale.submit_button) {}
+                    }
+                }
+            })
+            .build()
+    }
+}
+
+pub struct ShortyValidated {
+    pub url_path: Name,
+    pub url_redirect: Url,
+}
+
+#[cfg(test)]
+impl ShortyValidated {
+    pub fn new_test_data() -> Self {
+        Self {
+            url_path: Name::parse(Some("hello-world")).expect("test url path"),
+            url_redirect: Url::parse(Some("https://example.com/")).expect("test url redirect"),
+        }
+    }
+}
+
+pub struct ShortyError {
+    pub url_path: Result<Name, NameError>,
+    pub url_redirect: Result<Url, UrlError>,
+}
+
+impl ShortyError {
+    pub fn as_message(&self, locale: &Locale) -> ShortyMessage {
+        ShortyMessage {
+            url_path: self.url_path.as_translated_message(locale),
+            url_redirect: self.url_redirect.as_translated_message(locale),
+        }
+    }
+}
+
+pub struct ShortyResult(pub Result<ShortyValidated, ShortyError>);
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ShortyMessage {
+    pub url_path: Arc<[String]>,
+    pub url_redirect: Arc<[String]>,
+}
@@ -0,0 +1,6 @@
+pub mod form;
+pub mod model;
+pub mod repository;
+pub mod route;
+pub mod rule;
+pub mod service;
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+
+pub struct ListShortyModel {
+    pub id: i64,
+    pub url_path: String,
+    pub url_redirect: String,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+}
+
+pub struct FetchShortyModel {
+    pub url_path: String,
+    pub url_redirect: String,
+}
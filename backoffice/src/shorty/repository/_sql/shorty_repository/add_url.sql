@@ -0,0 +1,2 @@
+insert into shorty_urls(url_path, url_redirect, created_at, created_by)
+values (:url_path, :url_redirect, datetime(), :created_by)
\ No newline at end of file
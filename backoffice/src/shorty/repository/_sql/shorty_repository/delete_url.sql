@@ -0,0 +1,3 @@
+delete
+from shorty_urls
+where id = :id
\ No newline at end of file
@@ -0,0 +1,4 @@
+update shorty_urls
+set url_path     = :url_path,
+    url_redirect = :url_redirect
+where id = :id
\ No newline at end of file
@@ -0,0 +1,3 @@
+select url_path, url_redirect
+from shorty_urls
+where id = :id
\ No newline at end of file
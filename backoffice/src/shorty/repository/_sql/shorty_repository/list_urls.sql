@@ -0,0 +1,4 @@
+select s.id, s.url_path, s.url_redirect, s.created_at, coalesce(u.username, '') as created_by
+from shorty_urls as s
+         left join backoffice_users u on u.id = s.created_by
+order by s.id desc
\ No newline at end of file
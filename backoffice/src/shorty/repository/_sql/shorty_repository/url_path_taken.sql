@@ -0,0 +1,3 @@
+select 1 as taken
+from shorty_urls
+where url_path = :url_path
\ No newline at end of file
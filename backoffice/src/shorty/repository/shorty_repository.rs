@@ -0,0 +1,193 @@
+use crate::shorty::model::shorty_model::{FetchShortyModel, ListShortyModel};
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use rusqlite::{Connection, OptionalExtension, named_params};
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::db::{BorrowConnectionExt, SqliteClient};
+use std::sync::{Arc, MutexGuard};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ShortyRepositoryError {
+    #[error("Query Error")]
+    QueryError,
+    #[error("Row Value Error")]
+    RowValueError,
+    #[error("Borrow Conn Error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct ShortyRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl ShortyRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(&'_ self) -> Result<MutexGuard<'_, Connection>, Report<ShortyRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(ShortyRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl ShortyRepository {
+    pub fn list_urls(&self) -> Result<Arc<[ListShortyModel]>, Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/shorty_repository/list_urls.sql"))
+            .change_context(ShortyRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let rows_iter = stmt
+            .query_map(named_params! {}, |row| {
+                Ok(ListShortyModel {
+                    id: row.get("id")?,
+                    url_path: row.get("url_path")?,
+                    url_redirect: row.get("url_redirect")?,
+                    created_at: row.get("created_at")?,
+                    created_by: row.get("created_by")?,
+                })
+            })
+            .change_context(ShortyRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let items = rows_iter
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(ShortyRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(items.into())
+    }
+
+    pub fn fetch_url(
+        &self,
+        id: i64,
+    ) -> Result<Option<FetchShortyModel>, Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/shorty_repository/fetch_url.sql"))
+            .change_context(ShortyRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row = stmt
+            .query_one(
+                named_params! {
+                    ":id": id
+                },
+                |row| {
+                    Ok(FetchShortyModel {
+                        url_path: row.get("url_path")?,
+                        url_redirect: row.get("url_redirect")?,
+                    })
+                },
+            )
+            .optional()
+            .change_context(ShortyRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row)
+    }
+
+    pub fn add_url(
+        &self,
+        url_path: String,
+        url_redirect: String,
+        created_by: i64,
+    ) -> Result<(), Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/shorty_repository/add_url.sql"),
+            named_params! {
+                ":url_path": url_path,
+                ":url_redirect": url_redirect,
+                ":created_by": created_by,
+            },
+        )
+        .change_context(ShortyRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
+    pub fn edit_url(
+        &self,
+        id: i64,
+        url_path: String,
+        url_redirect: String,
+    ) -> Result<(), Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/shorty_repository/edit_url.sql"),
+            named_params! {
+                ":id": id,
+                ":url_path": url_path,
+                ":url_redirect": url_redirect,
+            },
+        )
+        .change_context(ShortyRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
+    pub fn delete_url(&self, id: i64) -> Result<(), Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/shorty_repository/delete_url.sql"),
+            named_params! {
+                ":id": id,
+            },
+        )
+        .change_context(ShortyRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
+    pub fn url_path_taken(&self, url_path: String) -> Result<bool, Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/shorty_repository/url_path_taken.sql"))
+            .change_context(ShortyRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row: Option<bool> = stmt
+            .query_one(
+                named_params! {
+                    ":url_path": url_path
+                },
+                |row| row.get("taken"),
+            )
+            .optional()
+            .change_context(ShortyRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+impl ShortyRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for ShortyRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
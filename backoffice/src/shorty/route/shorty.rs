@@ -0,0 +1,233 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::common::icon::{pencil_square_icon, plus_icon, trash_icon};
+use crate::shorty::form::shorty_form::ShortyForm;
+use crate::shorty::route::locale::shorty_locale::{
+    ShortyFlashLocale, ShortyLocale, shorty_delete_confirm_message,
+};
+use crate::shorty::service::shorty_service::ShortyService;
+use crate::user::pointer::user_pointer::UserPointer;
+use crate::user::role::user_role_check::must_be_user;
+use maud::{Markup, html};
+use poem::http::StatusCode;
+use poem::i18n::Locale;
+use poem::session::Session;
+use poem::web::{CsrfToken, Path, Redirect};
+use poem::{Error, IntoResponse, Response, Route, delete, get, handler};
+use shared::utils::context::Dep;
+use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml, csrf_header_check, csrf_header_check_strict};
+use shared::utils::error::{ExtraResultExt, FromErrorStack};
+use shared::utils::flag::path_edit::PathEdit;
+use shared::utils::flag::{Flag, flag_add, flag_edit};
+use shared::utils::flash::{Flash, FlashMessageExt};
+use shared::utils::htmx::HtmxHeader;
+
+pub const SHORTY_ROUTE: &str = "/shorty";
+
+#[handler]
+fn list_urls(
+    Dep(shorty_service): Dep<ShortyService>,
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    csrf_token: &CsrfToken,
+) -> Markup {
+    let url_list = shorty_service.list_urls();
+    let edit_icon = pencil_square_icon();
+    let delete_icon = trash_icon();
+
+    let lc = ShortyLocale::new(&context_html_builder.locale);
+    let title = lc.title.as_str();
+
+    context_html_builder
+        .attach_title(title)
+        .set_current_tag("id-tag-shorty")
+        .attach_content(html! {
+            h1 { (title) }
+            table .table-full {
+                thead {
+                    th { (lc.head_id) }
+                    th { (lc.head_path) }
+                    th { (lc.head_redirect_url) }
+                    th { (lc.head_created_at) }
+                    th { (lc.head_created_by) }
+                    th .action { (lc.head_action) }
+                }
+                tbody {
+                    @for url in url_list.iter() {
+                        tr {
+                            td { (url.id) }
+                            td { (url.url_path) }
+                            td { (url.url_redirect) }
+                            td x-init="$store.util.formatToLocalTime($el)" { (url.created_at.to_rfc3339()) }
+                            td { (url.created_by) }
+                            td .action {
+                                a .icon href=(format!("{}/edit/{}", SHORTY_ROUTE, url.id))
+                                    title=(lc.action_edit) hx-boost="true"
+                                    hx-push-url="true" hx-target="#main-content" { (edit_icon) }
+                                " "
+                                a .icon hx-confirm=(shorty_delete_confirm_message(&context_html_builder.locale, &url.url_path))
+                                    href=(format!("{}/delete/{}", SHORTY_ROUTE, url.id))
+                                    title=(lc.action_delete) hx-delete=(format!("{}/delete/{}", SHORTY_ROUTE, url.id)) { (delete_icon) }
+                            }
+                        }
+                    }
+                }
+            }
+            div .text-right .mt-3 {
+                a .inline-block href=(format!("{}/add", SHORTY_ROUTE)) title=(lc.action_add)
+                    hx-boost="true" hx-push-url="true" hx-target="#main-content" { (plus_icon()) }
+            }
+        })
+        .attach_footer(html! {
+            (csrf_token.as_html_command())
+        })
+        .build()
+}
+
+enum PostResponse {
+    Validation(Markup),
+}
+
+impl IntoResponse for PostResponse {
+    fn into_response(self) -> Response {
+        match self {
+            PostResponse::Validation(validation) => validation
+                .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+                .into_response(),
+        }
+    }
+}
+
+#[handler]
+async fn shorty_form_get(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(shorty_service): Dep<ShortyService>,
+    PathEdit(url_id): PathEdit<i64>,
+    flag: Flag,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    let mut shorty_form = ShortyForm::default();
+    if flag.is_edit() {
+        let subject_url = shorty_service
+            .fetch_url(url_id)
+            .map_err(Error::from_error_stack)?;
+        shorty_form.url_path = subject_url.url_path;
+        shorty_form.url_redirect = subject_url.url_redirect;
+    }
+
+    Ok(shorty_form
+        .as_form_html(
+            &context_html_builder,
+            flag,
+            None,
+            Some(csrf_token.as_html_input()),
+        )
+        .await)
+}
+
+#[handler]
+#[allow(clippy::too_many_arguments)]
+async fn shorty_form_post(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(shorty_service): Dep<ShortyService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    PathEdit(url_id): PathEdit<i64>,
+    flag: Flag,
+    CsrfFormQs(shorty_form): CsrfFormQs<ShortyForm>,
+    session: &Session,
+    htmx_header: HtmxHeader,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Response> {
+    let current_url_path = if flag.is_edit() {
+        Some(
+            shorty_service
+                .fetch_url(url_id)
+                .map_err(Error::from_error_stack)?
+                .url_path,
+        )
+    } else {
+        None
+    };
+    let validated_result = shorty_form
+        .as_validated(&shorty_service, current_url_path.as_deref())
+        .await
+        .0;
+    let flash_locale = ShortyFlashLocale::new(&context_html_builder.locale);
+    match validated_result {
+        Ok(validated) => {
+            let msg = if flag.is_edit() {
+                shorty_service
+                    .edit_url_submit(url_id, &validated)
+                    .log_it()
+                    .map_err(Error::from_error_stack)?;
+                flash_locale.success_edit_url
+            } else {
+                shorty_service
+                    .add_url_submit(&validated, user_pointer.id)
+                    .log_it()
+                    .map_err(Error::from_error_stack)?;
+                flash_locale.success_add_url
+            };
+            session.flash(Flash::Success { msg });
+            Ok(htmx_header.do_location(
+                Redirect::see_other(SHORTY_ROUTE.to_owned() + "/"),
+                "#main-content",
+            ))
+        }
+        Err(error) => {
+            let errors = error.as_message(&context_html_builder.locale);
+            context_html_builder.attach_form_flash_error();
+            Ok(PostResponse::Validation(
+                shorty_form
+                    .as_form_html(
+                        &context_html_builder,
+                        flag,
+                        Some(errors),
+                        Some(csrf_token.as_html_input()),
+                    )
+                    .await,
+            )
+            .into_response())
+        }
+    }
+}
+
+#[handler]
+fn delete_url(
+    Dep(shorty_service): Dep<ShortyService>,
+    Path(url_id): Path<i64>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> poem::Result<Response> {
+    shorty_service
+        .delete_url(url_id)
+        .map_err(Error::from_error_stack)?;
+
+    session.flash(Flash::Success {
+        msg: ShortyFlashLocale::new(&locale).success_deleted_url,
+    });
+    Ok(htmx_header.do_location(
+        Redirect::see_other(SHORTY_ROUTE.to_owned() + "/"),
+        "#main-content",
+    ))
+}
+
+pub fn shorty_route() -> Route {
+    Route::new()
+        .at("/", get(must_be_user(list_urls)))
+        .at(
+            "/add",
+            must_be_user(flag_add(
+                get(shorty_form_get).post(csrf_header_check(shorty_form_post)),
+            )),
+        )
+        .at(
+            "/edit/:url_id",
+            must_be_user(flag_edit(
+                get(shorty_form_get).post(csrf_header_check(shorty_form_post)),
+            )),
+        )
+        .at(
+            "/delete/:url_id",
+            must_be_user(delete(csrf_header_check_strict(delete_url))),
+        )
+}
@@ -0,0 +1,94 @@
+use cjtoolkit_structured_validator::common::locale::{LocaleData, LocaleMessage};
+use cjtoolkit_structured_validator::common::validation_check::ValidationCheck;
+use cjtoolkit_structured_validator::common::validation_collector::ValidateErrorCollector;
+use cjtoolkit_structured_validator::types::name::{Name, NameError, NameRules};
+use cjtoolkit_structured_validator::types::url::{Url, UrlError, UrlRules};
+use regex::Regex;
+use std::sync::{Arc, LazyLock};
+
+static KEBAB_CASE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").expect("valid kebab case regex"));
+
+#[inline]
+fn url_path_rules() -> NameRules {
+    NameRules {
+        is_mandatory: true,
+        min_length: Some(1),
+        max_length: Some(64),
+    }
+}
+
+#[inline]
+fn url_redirect_rules() -> UrlRules {
+    UrlRules { is_mandatory: true }
+}
+
+struct UrlPathKebabCaseLocale;
+
+impl LocaleMessage for UrlPathKebabCaseLocale {
+    fn get_locale_data(&self) -> Arc<LocaleData> {
+        LocaleData::new("validate-must-be-kebab-case")
+    }
+}
+
+struct UrlPathTakenLocale;
+
+impl LocaleMessage for UrlPathTakenLocale {
+    fn get_locale_data(&self) -> Arc<LocaleData> {
+        LocaleData::new("validate-url-path-taken")
+    }
+}
+
+fn check_url_path_is_kebab_case(url_path: &str) -> Result<(), NameError> {
+    if !KEBAB_CASE.is_match(url_path) {
+        let mut messages = ValidateErrorCollector::new();
+        messages.push((
+            "Must be kebab case".to_string(),
+            Box::new(UrlPathKebabCaseLocale),
+        ));
+        NameError::validate_check(messages)?;
+    }
+    Ok(())
+}
+
+pub trait IsUrlPathTakenAsync {
+    fn is_url_path_taken_async(&self, url_path: &str) -> impl Future<Output = bool>;
+}
+
+pub trait UrlPathShortyRulesExt {
+    fn parse_url_path<T: IsUrlPathTakenAsync>(
+        url_path: Option<&str>,
+        service: &T,
+        current_url_path: Option<&str>,
+    ) -> impl Future<Output = Result<Name, NameError>>;
+}
+
+impl UrlPathShortyRulesExt for Name {
+    async fn parse_url_path<T: IsUrlPathTakenAsync>(
+        url_path: Option<&str>,
+        service: &T,
+        current_url_path: Option<&str>,
+    ) -> Result<Name, NameError> {
+        let url_path = Name::parse_custom(url_path, url_path_rules())?;
+        check_url_path_is_kebab_case(url_path.as_str())?;
+        if current_url_path == Some(url_path.as_str()) {
+            return Ok(url_path);
+        }
+        if service.is_url_path_taken_async(url_path.as_str()).await {
+            let mut messages = ValidateErrorCollector::new();
+            messages.push(("Already taken".to_string(), Box::new(UrlPathTakenLocale)));
+            NameError::validate_check(messages)?;
+        }
+        Ok(url_path)
+    }
+}
+
+pub trait UrlRedirectShortyRulesExt {
+    fn parse_url_redirect(s: Option<&str>) -> Result<Url, UrlError>;
+}
+
+impl UrlRedirectShortyRulesExt for Url {
+    fn parse_url_redirect(s: Option<&str>) -> Result<Url, UrlError> {
+        Self::parse_custom(s, url_redirect_rules())
+    }
+}
@@ -0,0 +1,168 @@
+use crate::shorty::form::shorty_form::ShortyValidated;
+use crate::shorty::model::shorty_model::{FetchShortyModel, ListShortyModel};
+use crate::shorty::repository::shorty_repository::ShortyRepository;
+use crate::shorty::rule::shorty::IsUrlPathTakenAsync;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::error::ExtraResultExt;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ShortyServiceError {
+    #[error("DB error")]
+    DbError,
+    #[error("Not found")]
+    NotFound,
+}
+
+pub struct ShortyService {
+    shorty_repository: ShortyRepository,
+}
+
+impl ShortyService {
+    pub fn new(shorty_repository: ShortyRepository) -> Self {
+        Self { shorty_repository }
+    }
+
+    pub fn list_urls(&self) -> Arc<[ListShortyModel]> {
+        self.shorty_repository.list_urls().unwrap_or_default()
+    }
+
+    pub fn fetch_url(&self, id: i64) -> Result<FetchShortyModel, Report<ShortyServiceError>> {
+        self.shorty_repository
+            .fetch_url(id)
+            .change_context(ShortyServiceError::DbError)
+            .log_it()?
+            .ok_or_else(|| Report::new(ShortyServiceError::NotFound).attach(StatusCode::NOT_FOUND))
+    }
+
+    pub fn add_url_submit(
+        &self,
+        shorty_validated: &ShortyValidated,
+        created_by: i64,
+    ) -> Result<(), Report<ShortyServiceError>> {
+        self.shorty_repository
+            .add_url(
+                shorty_validated.url_path.as_str().to_string(),
+                shorty_validated.url_redirect.as_str().to_string(),
+                created_by,
+            )
+            .change_context(ShortyServiceError::DbError)
+    }
+
+    pub fn edit_url_submit(
+        &self,
+        id: i64,
+        shorty_validated: &ShortyValidated,
+    ) -> Result<(), Report<ShortyServiceError>> {
+        self.shorty_repository
+            .edit_url(
+                id,
+                shorty_validated.url_path.as_str().to_string(),
+                shorty_validated.url_redirect.as_str().to_string(),
+            )
+            .change_context(ShortyServiceError::DbError)
+    }
+
+    pub fn delete_url(&self, id: i64) -> Result<(), Report<ShortyServiceError>> {
+        self.shorty_repository
+            .delete_url(id)
+            .change_context(ShortyServiceError::DbError)
+            .log_it()
+    }
+}
+
+impl IsUrlPathTakenAsync for ShortyService {
+    async fn is_url_path_taken_async(&self, url_path: &str) -> bool {
+        self.shorty_repository
+            .url_path_taken(url_path.to_string())
+            .ok()
+            .unwrap_or_default()
+    }
+}
+
+impl FromContext for ShortyService {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shorty::repository::shorty_repository::ShortyRepositoryError;
+
+    #[test]
+    fn test_shorty_service_fetch_url_success() {
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository
+            .mock_fetch_url(1)
+            .returns_once(Ok(Some(FetchShortyModel {
+                url_path: "hello-world".to_string(),
+                url_redirect: "https://example.com/".to_string(),
+            })));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.fetch_url(1);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_shorty_service_fetch_url_not_found() {
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository.mock_fetch_url(1).returns_once(Ok(None));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.fetch_url(1);
+        assert!(result.is_err());
+        let result = result.err().unwrap();
+        let error_code = result.downcast_ref::<StatusCode>().unwrap();
+        assert_eq!(error_code, &StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_shorty_service_add_url_success() {
+        let shorty_validated = ShortyValidated::new_test_data();
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository
+            .mock_add_url(
+                shorty_validated.url_path.as_str().to_string(),
+                shorty_validated.url_redirect.as_str().to_string(),
+                1,
+            )
+            .returns_once(Ok(()));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.add_url_submit(&shorty_validated, 1);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_shorty_service_edit_url_failure() {
+        let shorty_validated = ShortyValidated::new_test_data();
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository
+            .mock_edit_url(
+                1,
+                shorty_validated.url_path.as_str().to_string(),
+                shorty_validated.url_redirect.as_str().to_string(),
+            )
+            .returns_once(Err(Report::new(ShortyRepositoryError::QueryError)));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.edit_url_submit(1, &shorty_validated);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_shorty_service_delete_url_success() {
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository.mock_delete_url(1).returns_once(Ok(()));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.delete_url(1);
+        assert!(result.is_ok());
+    }
+}
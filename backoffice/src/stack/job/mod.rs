@@ -0,0 +1 @@
+pub mod stack_retention_job;
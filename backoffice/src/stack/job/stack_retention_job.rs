@@ -0,0 +1,33 @@
+use crate::stack::service::stack_service::StackService;
+use error_stack::{Report, ResultExt};
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::jobs::{Job, JobError};
+
+pub const STACK_RETENTION_JOB: &str = "stack_retention";
+
+/// Deletes error stack entries older than the configured retention period.
+pub struct StackRetentionJob {
+    stack_service: StackService,
+}
+
+impl Job for StackRetentionJob {
+    async fn run(&self) -> Result<String, Report<JobError>> {
+        let deleted = self
+            .stack_service
+            .clear_expired()
+            .change_context(JobError::Failed)?;
+        Ok(format!(
+            "Deleted {} error stack entries older than {} days",
+            deleted,
+            self.stack_service.retention_days()
+        ))
+    }
+}
+
+impl FromContext for StackRetentionJob {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self {
+            stack_service: ctx.inject().await?,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+pub mod job;
 pub mod model;
 pub mod repository;
 pub mod route;
@@ -1,17 +1,240 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shared::utils::error::Severity;
+use shared::utils::log::model::ErrorStackEvent;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
+#[derive(Serialize)]
 pub struct StackModel {
-    #[allow(dead_code)]
     pub id: i64,
+    pub group_id: Option<i64>,
+    pub severity: Severity,
     pub error_name: String,
     pub error_summary: String,
     pub error_stack: String,
     pub reported_at: DateTime<Utc>,
+    pub request: Option<StackRequestModel>,
+}
+
+/// The request that produced an error, missing for errors logged outside a request.
+#[derive(Serialize)]
+pub struct StackRequestModel {
+    pub server: String,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub user_id: Option<i64>,
+    pub request_id: Option<String>,
+    pub htmx: BTreeMap<String, serde_json::Value>,
+    pub headers: BTreeMap<String, String>,
 }
 
 pub struct ListStackModel {
     pub id: i64,
-    pub error_name: String,
+    pub severity: Severity,
     pub error_summary: String,
     pub reported_at: DateTime<Utc>,
 }
+
+impl From<ErrorStackEvent> for ListStackModel {
+    fn from(event: ErrorStackEvent) -> Self {
+        Self {
+            id: event.id,
+            severity: event.severity,
+            error_summary: event.error_summary,
+            reported_at: event.reported_at,
+        }
+    }
+}
+
+/// Occurrences of the same error, see `error_fingerprint`.
+pub struct ErrorGroupModel {
+    pub id: i64,
+    pub severity: Severity,
+    pub error_name: String,
+    pub error_summary: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub occurrences: i64,
+    pub status: StackStatus,
+}
+
+/// Triage state of an error group, a resolved group goes back to `New` when it happens again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StackStatus {
+    #[default]
+    New,
+    Acknowledged,
+    Resolved,
+    Ignored,
+}
+
+impl StackStatus {
+    pub const ALL: [StackStatus; 4] = [
+        StackStatus::New,
+        StackStatus::Acknowledged,
+        StackStatus::Resolved,
+        StackStatus::Ignored,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Acknowledged => "acknowledged",
+            Self::Resolved => "resolved",
+            Self::Ignored => "ignored",
+        }
+    }
+}
+
+impl From<&str> for StackStatus {
+    fn from(value: &str) -> Self {
+        match value {
+            "acknowledged" => Self::Acknowledged,
+            "resolved" => Self::Resolved,
+            "ignored" => Self::Ignored,
+            _ => Self::New,
+        }
+    }
+}
+
+/// Statuses shown by the error group list, `Unresolved` covers new and acknowledged groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusFilter {
+    #[default]
+    Unresolved,
+    All,
+    New,
+    Acknowledged,
+    Resolved,
+    Ignored,
+}
+
+impl StatusFilter {
+    pub const ALL: [StatusFilter; 6] = [
+        StatusFilter::Unresolved,
+        StatusFilter::All,
+        StatusFilter::New,
+        StatusFilter::Acknowledged,
+        StatusFilter::Resolved,
+        StatusFilter::Ignored,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unresolved => "unresolved",
+            Self::All => "all",
+            Self::New => "new",
+            Self::Acknowledged => "acknowledged",
+            Self::Resolved => "resolved",
+            Self::Ignored => "ignored",
+        }
+    }
+
+    /// The single status matched, if any.
+    pub fn status(&self) -> Option<StackStatus> {
+        match self {
+            Self::Unresolved | Self::All => None,
+            Self::New => Some(StackStatus::New),
+            Self::Acknowledged => Some(StackStatus::Acknowledged),
+            Self::Resolved => Some(StackStatus::Resolved),
+            Self::Ignored => Some(StackStatus::Ignored),
+        }
+    }
+}
+
+/// One entry of the status history of an error group, `user_id` is missing when the
+/// group reopened on its own.
+pub struct StatusChangeModel {
+    pub status: StackStatus,
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Column the error group list is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StackSort {
+    #[default]
+    LastSeen,
+    FirstSeen,
+    Occurrences,
+    Severity,
+    Name,
+}
+
+impl StackSort {
+    /// The query string value and the SQL ordering expression of each sort.
+    fn parts(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::LastSeen => ("last_seen", "last_seen"),
+            Self::FirstSeen => ("first_seen", "first_seen"),
+            Self::Occurrences => ("occurrences", "occurrences"),
+            Self::Severity => (
+                "severity",
+                "case severity when 'critical' then 2 when 'error' then 1 else 0 end",
+            ),
+            Self::Name => ("name", "error_name"),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.parts().0
+    }
+
+    pub fn column(&self) -> &'static str {
+        self.parts().1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::Asc => Self::Desc,
+            Self::Desc => Self::Asc,
+        }
+    }
+}
+
+/// Narrows and orders the error group list, every filter is optional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorGroupFilter {
+    /// Matched against the error name and summary.
+    pub search: Option<String>,
+    pub severity: Option<Severity>,
+    pub status: StatusFilter,
+    /// Keeps groups seen on or after this day.
+    pub from: Option<NaiveDate>,
+    /// Keeps groups seen on or before this day.
+    pub to: Option<NaiveDate>,
+    pub sort: StackSort,
+    pub order: SortOrder,
+}
+
+pub struct ErrorGroupPage {
+    pub items: Arc<[ErrorGroupModel]>,
+    /// One based, always within `1..=page_count`.
+    pub page: u32,
+    pub page_count: u32,
+    pub total: i64,
+}
@@ -1,3 +1,3 @@
 delete
 from error_stack
-where reported_at < datetime('now', '-30 day')
\ No newline at end of file
+where reported_at < datetime('now', '-' || :older_than_days || ' day')
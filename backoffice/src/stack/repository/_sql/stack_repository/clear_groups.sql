@@ -0,0 +1,4 @@
+delete
+from error_group
+where last_seen < datetime('now', '-' || :older_than_days || ' day')
+  and status != 'ignored'
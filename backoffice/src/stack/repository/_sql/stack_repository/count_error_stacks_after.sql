@@ -0,0 +1,4 @@
+select count(*)
+from error_stack
+where id > :after_id
+  and not exists (select 1 from error_group where error_group.id = error_stack.group_id and status = 'ignored')
@@ -0,0 +1,20 @@
+select e.id,
+       e.group_id,
+       e.severity,
+       e.error_name,
+       e.error_summary,
+       e.error_stack,
+       e.reported_at,
+       e.server,
+       e.method,
+       e.path,
+       e.query,
+       e.user_id,
+       e.request_id,
+       e.htmx,
+       e.headers
+from error_stack e
+         join listed_group g on g.id = e.group_id
+where e.reported_at > datetime('now', '-' || :retention_days || ' day')
+order by g.list_rank, e.id desc
+limit :limit offset :offset
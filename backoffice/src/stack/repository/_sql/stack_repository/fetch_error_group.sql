@@ -0,0 +1,3 @@
+select id, severity, error_name, error_summary, first_seen, last_seen, occurrences, status
+from error_group
+where id = :id
@@ -1,3 +1,17 @@
-select id, error_name, error_summary, error_stack, reported_at
+select id,
+       group_id,
+       severity,
+       error_name,
+       error_summary,
+       error_stack,
+       reported_at,
+       server,
+       method,
+       path,
+       query,
+       user_id,
+       request_id,
+       htmx,
+       headers
 from error_stack
-where id = :id
\ No newline at end of file
+where id = :id
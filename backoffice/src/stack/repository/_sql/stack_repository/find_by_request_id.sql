@@ -0,0 +1,4 @@
+select id, severity, error_summary, reported_at
+from error_stack
+where request_id = :request_id
+order by id desc
@@ -0,0 +1,8 @@
+select id, severity, error_name, error_summary, first_seen, last_seen, occurrences, status
+from error_group
+where last_seen > datetime('now', '-' || :retention_days || ' day')
+  and (:severity is null or severity = :severity)
+  and (:status = 'all' or (:status = 'unresolved' and status in ('new', 'acknowledged')) or status = :status)
+  and (:search is null or error_name like :search escape '\' or error_summary like :search escape '\')
+  and (:from is null or last_seen >= :from)
+  and (:to is null or first_seen < date(:to, '+1 day'))
@@ -0,0 +1,4 @@
+select id, severity, error_summary, reported_at
+from error_stack
+where group_id = :group_id
+order by id desc
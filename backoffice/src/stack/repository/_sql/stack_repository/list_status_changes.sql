@@ -0,0 +1,4 @@
+select status, user_id, username, note, changed_at
+from error_group_status_change
+where group_id = :group_id
+order by id desc
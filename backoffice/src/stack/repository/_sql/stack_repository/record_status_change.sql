@@ -0,0 +1,2 @@
+insert into error_group_status_change (group_id, status, user_id, username, note, changed_at)
+values (:group_id, :status, :user_id, :username, :note, datetime())
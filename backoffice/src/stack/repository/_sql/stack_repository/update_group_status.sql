@@ -0,0 +1,3 @@
+update error_group
+set status = :status
+where id = :group_id
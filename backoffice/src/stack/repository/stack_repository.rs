@@ -1,9 +1,13 @@
-use crate::stack::model::stack_model::{ListStackModel, StackModel};
+use crate::stack::model::stack_model::{
+    ErrorGroupFilter, ErrorGroupModel, ListStackModel, SortOrder, StackModel, StackRequestModel,
+    StackSort, StackStatus, StatusChangeModel,
+};
 use error_stack::{Report, ResultExt};
 use poem::http::StatusCode;
-use rusqlite::{Connection, OptionalExtension, named_params};
+use rusqlite::{Connection, OptionalExtension, Row, named_params};
 use shared::utils::context::{Context, ContextError, FromContext};
 use shared::utils::db::{BorrowConnectionExt, SqliteClient};
+use shared::utils::error::Severity;
 use std::sync::{Arc, MutexGuard};
 use thiserror::Error;
 
@@ -39,15 +43,40 @@ impl StackRepository {
 
 #[mry::mry]
 impl StackRepository {
-    pub fn clear(&self) -> Result<(), Report<StackRepositoryError>> {
-        let conn = self.borrow_conn()?;
-        conn.execute(
-            include_str!("_sql/stack_repository/clear.sql"),
-            named_params! {},
+    /// Deletes occurrences and groups not seen for `older_than_days`, returns how many
+    /// occurrences were deleted. Ignored groups are kept, so the next occurrence stays
+    /// ignored.
+    pub fn clear(&self, older_than_days: u32) -> Result<usize, Report<StackRepositoryError>> {
+        let mut conn = self.borrow_conn()?;
+        let tx = conn
+            .transaction()
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let deleted = tx
+            .execute(
+                include_str!("_sql/stack_repository/clear.sql"),
+                named_params! {
+                    ":older_than_days": older_than_days,
+                },
+            )
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.execute(
+            include_str!("_sql/stack_repository/clear_groups.sql"),
+            named_params! {
+                ":older_than_days": older_than_days,
+            },
         )
         .change_context(StackRepositoryError::QueryError)
         .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
-        Ok(())
+
+        tx.commit()
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(deleted)
     }
 
     pub fn fetch_error_stack(
@@ -66,15 +95,32 @@ impl StackRepository {
                 named_params! {
                     ":id": id
                 },
-                |row| {
-                    Ok(StackModel {
-                        id: row.get("id")?,
-                        error_name: row.get("error_name")?,
-                        error_summary: row.get("error_summary")?,
-                        error_stack: row.get("error_stack")?,
-                        reported_at: row.get("reported_at")?,
-                    })
+                stack_from_row,
+            )
+            .optional()
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row)
+    }
+
+    pub fn fetch_error_group(
+        &self,
+        id: i64,
+    ) -> Result<Option<ErrorGroupModel>, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare(include_str!("_sql/stack_repository/fetch_error_group.sql"))
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row = stmt
+            .query_one(
+                named_params! {
+                    ":id": id
                 },
+                error_group_from_row,
             )
             .optional()
             .change_context(StackRepositoryError::QueryError)
@@ -83,23 +129,109 @@ impl StackRepository {
         Ok(row)
     }
 
-    pub fn list_error_stack(&self) -> Result<Arc<[ListStackModel]>, Report<StackRepositoryError>> {
+    pub fn list_error_groups(
+        &self,
+        retention_days: u32,
+        filter: ErrorGroupFilter,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Arc<[ErrorGroupModel]>, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let sql = format!(
+            "{}\norder by {}\nlimit :limit offset :offset",
+            include_str!("_sql/stack_repository/list_error_groups.sql").trim_end(),
+            order_by(filter.sort, filter.order),
+        );
+        let mut stmt = conn
+            .prepare(&sql)
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let rows_iter = stmt
+            .query_map(
+                named_params! {
+                    ":retention_days": retention_days,
+                    ":severity": filter.severity.map(|severity| severity.as_str()),
+                    ":status": filter.status.as_str(),
+                    ":search": filter.search.as_deref().map(like_pattern),
+                    ":from": filter.from,
+                    ":to": filter.to,
+                    ":limit": limit,
+                    ":offset": offset,
+                },
+                error_group_from_row,
+            )
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let items = rows_iter
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(StackRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(items.into())
+    }
+
+    pub fn count_error_groups(
+        &self,
+        retention_days: u32,
+        filter: ErrorGroupFilter,
+    ) -> Result<i64, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.query_row(
+            include_str!("_sql/stack_repository/count_error_groups.sql"),
+            named_params! {
+                ":retention_days": retention_days,
+                ":severity": filter.severity.map(|severity| severity.as_str()),
+                ":status": filter.status.as_str(),
+                ":search": filter.search.as_deref().map(like_pattern),
+                ":from": filter.from,
+                ":to": filter.to,
+            },
+            |row| row.get(0),
+        )
+        .change_context(StackRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Lists up to `limit` occurrences of the groups the list shows for `filter`, in the
+    /// list's order and newest first within a group, skipping the first `offset`.
+    pub fn export_error_stacks(
+        &self,
+        retention_days: u32,
+        filter: ErrorGroupFilter,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Arc<[StackModel]>, Report<StackRepositoryError>> {
         let conn = self.borrow_conn()?;
 
+        let sql = format!(
+            "with listed_group as (select *, row_number() over (order by {}) as list_rank from ({}))\n{}",
+            order_by(filter.sort, filter.order),
+            include_str!("_sql/stack_repository/list_error_groups.sql").trim_end(),
+            include_str!("_sql/stack_repository/export_error_stacks.sql"),
+        );
         let mut stmt = conn
-            .prepare(include_str!("_sql/stack_repository/list_error_stack.sql"))
+            .prepare(&sql)
             .change_context(StackRepositoryError::QueryError)
             .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
 
         let rows_iter = stmt
-            .query_map(named_params! {}, |row| {
-                Ok(ListStackModel {
-                    id: row.get("id")?,
-                    error_name: row.get("error_name")?,
-                    error_summary: row.get("error_summary")?,
-                    reported_at: row.get("reported_at")?,
-                })
-            })
+            .query_map(
+                named_params! {
+                    ":retention_days": retention_days,
+                    ":severity": filter.severity.map(|severity| severity.as_str()),
+                    ":status": filter.status.as_str(),
+                    ":search": filter.search.as_deref().map(like_pattern),
+                    ":from": filter.from,
+                    ":to": filter.to,
+                    ":limit": limit,
+                    ":offset": offset,
+                },
+                stack_from_row,
+            )
             .change_context(StackRepositoryError::QueryError)
             .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -110,6 +242,256 @@ impl StackRepository {
 
         Ok(items.into())
     }
+
+    /// Sets the status of a group and records the change, returns false when there is no
+    /// such group.
+    pub fn update_group_status(
+        &self,
+        group_id: i64,
+        status: StackStatus,
+        user_id: i64,
+        username: String,
+        note: Option<String>,
+    ) -> Result<bool, Report<StackRepositoryError>> {
+        let mut conn = self.borrow_conn()?;
+        let tx = conn
+            .transaction()
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let updated = tx
+            .execute(
+                include_str!("_sql/stack_repository/update_group_status.sql"),
+                named_params! {
+                    ":group_id": group_id,
+                    ":status": status.as_str(),
+                },
+            )
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        tx.execute(
+            include_str!("_sql/stack_repository/record_status_change.sql"),
+            named_params! {
+                ":group_id": group_id,
+                ":status": status.as_str(),
+                ":user_id": user_id,
+                ":username": username,
+                ":note": note,
+            },
+        )
+        .change_context(StackRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.commit()
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(true)
+    }
+
+    pub fn list_status_changes(
+        &self,
+        group_id: i64,
+    ) -> Result<Arc<[StatusChangeModel]>, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare(include_str!(
+                "_sql/stack_repository/list_status_changes.sql"
+            ))
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let rows_iter = stmt
+            .query_map(
+                named_params! {
+                    ":group_id": group_id,
+                },
+                |row| {
+                    Ok(StatusChangeModel {
+                        status: StackStatus::from(row.get::<_, String>("status")?.as_str()),
+                        user_id: row.get("user_id")?,
+                        username: row.get("username")?,
+                        note: row.get("note")?,
+                        changed_at: row.get("changed_at")?,
+                    })
+                },
+            )
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let items = rows_iter
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(StackRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(items.into())
+    }
+
+    pub fn list_group_occurrences(
+        &self,
+        group_id: i64,
+    ) -> Result<Arc<[ListStackModel]>, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare(include_str!(
+                "_sql/stack_repository/list_group_occurrences.sql"
+            ))
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let rows_iter = stmt
+            .query_map(
+                named_params! {
+                    ":group_id": group_id,
+                },
+                list_stack_from_row,
+            )
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let items = rows_iter
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(StackRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(items.into())
+    }
+    pub fn latest_error_stack_id(&self) -> Result<i64, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.query_row(
+            include_str!("_sql/stack_repository/latest_error_stack_id.sql"),
+            [],
+            |row| row.get(0),
+        )
+        .change_context(StackRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Counts occurrences stored after `after_id`, leaving out ignored groups.
+    pub fn count_error_stacks_after(
+        &self,
+        after_id: i64,
+    ) -> Result<i64, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.query_row(
+            include_str!("_sql/stack_repository/count_error_stacks_after.sql"),
+            named_params! {
+                ":after_id": after_id,
+            },
+            |row| row.get(0),
+        )
+        .change_context(StackRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn find_by_request_id(
+        &self,
+        request_id: String,
+    ) -> Result<Arc<[ListStackModel]>, Report<StackRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare(include_str!("_sql/stack_repository/find_by_request_id.sql"))
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let rows_iter = stmt
+            .query_map(
+                named_params! {
+                    ":request_id": request_id,
+                },
+                list_stack_from_row,
+            )
+            .change_context(StackRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let items = rows_iter
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(StackRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(items.into())
+    }
+}
+
+fn stack_from_row(row: &Row) -> rusqlite::Result<StackModel> {
+    Ok(StackModel {
+        id: row.get("id")?,
+        group_id: row.get("group_id")?,
+        severity: Severity::from(row.get::<_, String>("severity")?.as_str()),
+        error_name: row.get("error_name")?,
+        error_summary: row.get("error_summary")?,
+        error_stack: row.get("error_stack")?,
+        reported_at: row.get("reported_at")?,
+        request: stack_request_from_row(row)?,
+    })
+}
+
+fn list_stack_from_row(row: &Row) -> rusqlite::Result<ListStackModel> {
+    Ok(ListStackModel {
+        id: row.get("id")?,
+        severity: Severity::from(row.get::<_, String>("severity")?.as_str()),
+        error_summary: row.get("error_summary")?,
+        reported_at: row.get("reported_at")?,
+    })
+}
+
+/// Sort columns are picked from a fixed list, they can't be bound as parameters.
+fn order_by(sort: StackSort, order: SortOrder) -> String {
+    let (column, order) = (sort.column(), order.as_str());
+    format!("{column} {order}, id {order}")
+}
+
+/// Matches `search` anywhere, treating `%` and `_` literally.
+fn like_pattern(search: &str) -> String {
+    let escaped = search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+fn stack_request_from_row(row: &Row) -> rusqlite::Result<Option<StackRequestModel>> {
+    let Some(server) = row.get::<_, Option<String>>("server")? else {
+        return Ok(None);
+    };
+    let htmx: Option<String> = row.get("htmx")?;
+    let headers: Option<String> = row.get("headers")?;
+    Ok(Some(StackRequestModel {
+        server,
+        method: row.get::<_, Option<String>>("method")?.unwrap_or_default(),
+        path: row.get::<_, Option<String>>("path")?.unwrap_or_default(),
+        query: row.get("query")?,
+        user_id: row.get("user_id")?,
+        request_id: row.get("request_id")?,
+        htmx: htmx
+            .and_then(|htmx| serde_json::from_str(&htmx).ok())
+            .unwrap_or_default(),
+        headers: headers
+            .and_then(|headers| serde_json::from_str(&headers).ok())
+            .unwrap_or_default(),
+    }))
+}
+
+fn error_group_from_row(row: &Row) -> rusqlite::Result<ErrorGroupModel> {
+    Ok(ErrorGroupModel {
+        id: row.get("id")?,
+        severity: Severity::from(row.get::<_, String>("severity")?.as_str()),
+        error_name: row.get("error_name")?,
+        error_summary: row.get("error_summary")?,
+        first_seen: row.get("first_seen")?,
+        last_seen: row.get("last_seen")?,
+        occurrences: row.get("occurrences")?,
+        status: StackStatus::from(row.get::<_, String>("status")?.as_str()),
+    })
 }
 
 #[cfg(test)]
@@ -126,3 +508,46 @@ impl FromContext for StackRepository {
         Ok(Self::new(ctx.inject().await?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::utils::config::sqlite::SqliteConfig;
+    use shared::utils::db::migration::SHARED_MIGRATIONS;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_clear_keeps_ignored_groups() {
+        let dir = std::env::temp_dir().join(format!("stack-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let sqlite_client = SqliteClient::new(&SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+        sqlite_client.migrate(&SHARED_MIGRATIONS).unwrap();
+        sqlite_client
+            .borrow_conn()
+            .unwrap()
+            .execute_batch(
+                "insert into error_group (id, fingerprint, severity, error_name, error_summary,
+                                          first_seen, last_seen, occurrences, status)
+                 values (1, 'old', 'error', 'E', 'old', datetime('now', '-40 day'), datetime('now', '-40 day'), 1, 'resolved'),
+                        (2, 'ignored', 'error', 'E', 'ignored', datetime('now', '-40 day'), datetime('now', '-40 day'), 1, 'ignored'),
+                        (3, 'recent', 'error', 'E', 'recent', datetime('now'), datetime('now'), 1, 'new');
+                 insert into error_group_status_change (group_id, status, changed_at)
+                 values (2, 'ignored', datetime('now', '-40 day'));",
+            )
+            .unwrap();
+        let repository = StackRepository::new(sqlite_client);
+
+        repository.clear(30).unwrap();
+        assert!(repository.fetch_error_group(1).unwrap().is_none());
+        assert!(repository.fetch_error_group(2).unwrap().is_some());
+        assert!(repository.fetch_error_group(3).unwrap().is_some());
+        assert_eq!(repository.list_status_changes(2).unwrap().len(), 1);
+        drop(repository);
+        remove_dir_all(&dir).unwrap();
+    }
+}
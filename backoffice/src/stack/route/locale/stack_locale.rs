@@ -1,15 +1,35 @@
+use crate::stack::model::stack_model::{StackStatus, StatusFilter};
 use poem::i18n::{I18NArgs, Locale};
+use shared::utils::error::Severity;
 use shared::utils::locale::LocaleExt;
 
 pub struct StackLocale {
     pub title: String,
     pub head_id: String,
+    pub head_severity: String,
     pub head_name: String,
     pub head_summary: String,
     pub head_reported: String,
+    pub head_occurrences: String,
+    pub head_first_seen: String,
+    pub head_last_seen: String,
     pub head_action: String,
     pub action_details: String,
-    pub action_clear: String,
+    pub action_occurrences: String,
+    pub head_status: String,
+    pub filter_severity: String,
+    pub filter_status: String,
+    pub filter_all: String,
+    pub filter_from: String,
+    pub filter_to: String,
+    pub search_placeholder: String,
+    pub search_request_id: String,
+    pub action_search: String,
+    pub action_reset: String,
+    pub action_previous: String,
+    pub action_next: String,
+    pub head_live: String,
+    pub live_hint: String,
 }
 
 impl StackLocale {
@@ -17,28 +37,129 @@ impl StackLocale {
         Self {
             title: l.text_with_default("stack-list-error-stack-title", "List Error Stack"),
             head_id: l.text_with_default("stack-list-error-stack-head-id", "ID"),
+            head_severity: l.text_with_default("stack-list-error-stack-head-severity", "Severity"),
             head_name: l.text_with_default("stack-list-error-stack-head-name", "Name"),
             head_summary: l.text_with_default("stack-list-error-stack-head-summary", "Summary"),
             head_reported: l
                 .text_with_default("stack-list-error-stack-head-reported", "Reported At"),
+            head_occurrences: l
+                .text_with_default("stack-list-error-stack-head-occurrences", "Occurrences"),
+            head_first_seen: l
+                .text_with_default("stack-list-error-stack-head-first-seen", "First Seen"),
+            head_last_seen: l
+                .text_with_default("stack-list-error-stack-head-last-seen", "Last Seen"),
             head_action: l.text_with_default("stack-list-error-stack-head-action", "Action"),
             action_details: l.text_with_default(
                 "stack-list-error-stack-action-details",
                 "View Error Details",
             ),
-            action_clear: l.text_with_default(
-                "stack-list-error-stack-action-clear",
-                "Clear Older than 30 days",
+            action_occurrences: l.text_with_default(
+                "stack-list-error-stack-action-occurrences",
+                "View Occurrences",
+            ),
+            filter_severity: l
+                .text_with_default("stack-list-error-stack-filter-severity", "Severity:"),
+            head_status: l.text_with_default("stack-list-error-stack-head-status", "Status"),
+            filter_status: l.text_with_default("stack-list-error-stack-filter-status", "Status:"),
+            filter_all: l.text_with_default("stack-list-error-stack-filter-all", "All"),
+            filter_from: l.text_with_default("stack-list-error-stack-filter-from", "Seen from"),
+            filter_to: l.text_with_default("stack-list-error-stack-filter-to", "Seen until"),
+            search_placeholder: l.text_with_default(
+                "stack-list-error-stack-search-placeholder",
+                "Search name or summary",
+            ),
+            search_request_id: l
+                .text_with_default("stack-list-error-stack-search-request-id", "Request ID"),
+            action_search: l.text_with_default("stack-list-error-stack-action-search", "Search"),
+            action_reset: l.text_with_default("stack-list-error-stack-action-reset", "Reset"),
+            action_previous: l
+                .text_with_default("stack-list-error-stack-action-previous", "Previous"),
+            action_next: l.text_with_default("stack-list-error-stack-action-next", "Next"),
+            head_live: l.text_with_default("stack-list-error-stack-head-live", "New Errors"),
+            live_hint: l.text_with_default(
+                "stack-list-error-stack-live-hint",
+                "Errors reported while this page is open appear here.",
             ),
         }
     }
 }
 
+pub fn stack_page_message(l: &Locale, page: u32, page_count: u32, total: i64) -> String {
+    l.text_with_default_args(
+        "stack-list-error-stack-page",
+        format!("Page {page} of {page_count} ({total} groups)").as_str(),
+        I18NArgs::from((("page", page), ("page_count", page_count), ("total", total))),
+    )
+}
+
+pub fn stack_search_results_message(l: &Locale, request_id: &str, found: usize) -> String {
+    l.text_with_default_args(
+        "stack-list-error-stack-search-results",
+        format!("Found {found} error stacks for request {request_id}.").as_str(),
+        I18NArgs::from((("request_id", request_id), ("found", found))),
+    )
+}
+
+pub struct StackGroupLocale {
+    pub title: String,
+    pub head_occurrences: String,
+    pub head_change_status: String,
+    pub head_history: String,
+    pub head_changed_at: String,
+    pub head_changed_by: String,
+    pub head_note: String,
+    pub note_placeholder: String,
+    pub action_change_status: String,
+    pub changed_by_system: String,
+}
+
+impl StackGroupLocale {
+    pub fn new(l: &Locale, name: &str) -> Self {
+        Self {
+            title: l.text_with_default_args(
+                "stack-group-title",
+                format!("Error Group: {name}").as_str(),
+                I18NArgs::from((("name", name),)),
+            ),
+            head_occurrences: l.text_with_default("stack-group-head-occurrences", "Occurrences"),
+            head_change_status: l
+                .text_with_default("stack-group-head-change-status", "Change Status"),
+            head_history: l.text_with_default("stack-group-head-history", "Status History"),
+            head_changed_at: l.text_with_default("stack-group-head-changed-at", "Changed At"),
+            head_changed_by: l.text_with_default("stack-group-head-changed-by", "Changed By"),
+            head_note: l.text_with_default("stack-group-head-note", "Note"),
+            note_placeholder: l.text_with_default("stack-group-note-placeholder", "Optional note"),
+            action_change_status: l.text_with_default("stack-group-action-change-status", "Save"),
+            changed_by_system: l
+                .text_with_default("stack-group-changed-by-system", "Reopened automatically"),
+        }
+    }
+}
+
+pub fn stack_group_samples_message(l: &Locale, shown: usize, total: i64) -> String {
+    l.text_with_default_args(
+        "stack-group-samples-message",
+        format!("Showing the latest {shown} of {total} occurrences.").as_str(),
+        I18NArgs::from((("shown", shown), ("total", total))),
+    )
+}
+
 pub struct StackFetchLocale {
     pub title: String,
+    pub action_group: String,
+    pub action_download: String,
     pub head_reported: String,
+    pub head_severity: String,
     pub head_summary: String,
     pub head_stack: String,
+    pub head_request: String,
+    pub head_server: String,
+    pub head_method: String,
+    pub head_path: String,
+    pub head_user_id: String,
+    pub head_request_id: String,
+    pub head_htmx: String,
+    pub head_headers: String,
 }
 
 impl StackFetchLocale {
@@ -49,18 +170,99 @@ impl StackFetchLocale {
                 format!("Error Stack: {name}").as_str(),
                 I18NArgs::from((("name", name),)),
             ),
+            action_group: l.text_with_default(
+                "stack-list-error-stack-fetch-action-group",
+                "View all occurrences",
+            ),
+            action_download: l
+                .text_with_default("stack-list-error-stack-fetch-action-download", "Download"),
             head_reported: l
                 .text_with_default("stack-list-error-stack-fetch-head-reported", "Reported At"),
+            head_severity: l
+                .text_with_default("stack-list-error-stack-fetch-head-severity", "Severity"),
             head_summary: l
                 .text_with_default("stack-list-error-stack-fetch-head-summary", "Summary"),
             head_stack: l.text_with_default("stack-list-error-stack-fetch-head-stack", "Stack"),
+            head_request: l
+                .text_with_default("stack-list-error-stack-fetch-head-request", "Request"),
+            head_server: l.text_with_default("stack-list-error-stack-fetch-head-server", "Server"),
+            head_method: l.text_with_default("stack-list-error-stack-fetch-head-method", "Method"),
+            head_path: l.text_with_default("stack-list-error-stack-fetch-head-path", "Path"),
+            head_user_id: l
+                .text_with_default("stack-list-error-stack-fetch-head-user-id", "User ID"),
+            head_request_id: l
+                .text_with_default("stack-list-error-stack-fetch-head-request-id", "Request ID"),
+            head_htmx: l.text_with_default("stack-list-error-stack-fetch-head-htmx", "Htmx"),
+            head_headers: l
+                .text_with_default("stack-list-error-stack-fetch-head-headers", "Headers"),
+        }
+    }
+}
+
+pub fn severity_text(l: &Locale, severity: Severity) -> String {
+    match severity {
+        Severity::Critical => l.text_with_default("stack-severity-critical", "Critical"),
+        Severity::Error => l.text_with_default("stack-severity-error", "Error"),
+        Severity::Warning => l.text_with_default("stack-severity-warning", "Warning"),
+    }
+}
+
+pub fn stack_clear_action_text(l: &Locale, days: u32) -> String {
+    l.text_with_default_args(
+        "stack-list-error-stack-action-clear",
+        format!("Clear older than {days} days").as_str(),
+        I18NArgs::from((("days", days),)),
+    )
+}
+
+pub fn status_text(l: &Locale, status: StackStatus) -> String {
+    match status {
+        StackStatus::New => l.text_with_default("stack-status-new", "New"),
+        StackStatus::Acknowledged => {
+            l.text_with_default("stack-status-acknowledged", "Acknowledged")
         }
+        StackStatus::Resolved => l.text_with_default("stack-status-resolved", "Resolved"),
+        StackStatus::Ignored => l.text_with_default("stack-status-ignored", "Ignored"),
     }
 }
 
-pub fn stack_clear_confirm_message(l: &Locale) -> String {
-    l.text_with_default(
-        "stack-route-logout-confirm-message",
-        "Are you sure you want to clear all error stacks older than 30 days?",
+pub fn status_filter_text(l: &Locale, filter: StatusFilter) -> String {
+    match (filter, filter.status()) {
+        (_, Some(status)) => status_text(l, status),
+        (StatusFilter::All, None) => l.text_with_default("stack-status-filter-all", "All statuses"),
+        _ => l.text_with_default("stack-status-filter-unresolved", "Unresolved"),
+    }
+}
+
+pub fn stack_status_changed_message(l: &Locale, status: StackStatus) -> String {
+    let status = status_text(l, status);
+    l.text_with_default_args(
+        "stack-flash-success-status",
+        format!("Status changed to {status}").as_str(),
+        I18NArgs::from((("status", status.as_str()),)),
+    )
+}
+
+pub fn stack_export_action_text(l: &Locale, format: &str) -> String {
+    l.text_with_default_args(
+        "stack-list-error-stack-action-export",
+        format!("Export {format}").as_str(),
+        I18NArgs::from((("format", format),)),
+    )
+}
+
+pub fn stack_clear_confirm_message(l: &Locale, days: u32) -> String {
+    l.text_with_default_args(
+        "stack-route-clear-confirm-message",
+        format!("Are you sure you want to clear all error stacks older than {days} days?").as_str(),
+        I18NArgs::from((("days", days),)),
+    )
+}
+
+pub fn stack_clear_success_message(l: &Locale, deleted: usize, days: u32) -> String {
+    l.text_with_default_args(
+        "stack-flash-success-clear",
+        format!("Cleared {deleted} error stacks older than {days} days").as_str(),
+        I18NArgs::from((("deleted", deleted), ("days", days))),
     )
 }
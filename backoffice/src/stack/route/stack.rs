@@ -1,32 +1,346 @@
 use crate::common::html::context_html::ContextHtmlBuilder;
-use crate::common::icon::{document_magnifying_glass_icon, no_symbol_icon};
+use crate::common::icon::{arrow_down_tray_icon, document_magnifying_glass_icon, no_symbol_icon};
+use crate::stack::model::stack_model::{
+    ErrorGroupFilter, ErrorGroupPage, ListStackModel, SortOrder, StackSort, StackStatus,
+    StatusFilter,
+};
 use crate::stack::route::locale::stack_locale::{
-    StackFetchLocale, StackLocale, stack_clear_confirm_message,
+    StackFetchLocale, StackGroupLocale, StackLocale, severity_text, stack_clear_action_text,
+    stack_clear_confirm_message, stack_clear_success_message, stack_export_action_text,
+    stack_group_samples_message, stack_page_message, stack_search_results_message,
+    stack_status_changed_message, status_filter_text, status_text,
 };
+use crate::stack::service::stack_export::{ExportFormat, to_pretty_json};
 use crate::stack::service::stack_service::StackService;
+use crate::user::pointer::user_pointer::UserPointer;
+use crate::user::role::Role;
+use crate::user::service::user_check_service::UserCheckService;
+use chrono::{NaiveDate, Utc};
+use futures_util::{Stream, StreamExt, stream};
 use maud::{Markup, html};
+use poem::http::Uri;
+use poem::http::header::CONTENT_DISPOSITION;
+use poem::i18n::Locale;
 use poem::session::Session;
+use poem::web::sse::{Event, SSE};
 use poem::web::{CsrfToken, Path, Redirect};
-use poem::{Response, Route, get, handler};
+use poem::{Body, Response, Route, delete, get, handler, post};
+use serde::{Deserialize, Serialize};
 use shared::utils::context::Dep;
-use shared::utils::csrf::{CsrfTokenHtml, csrf_header_check_strict};
-use shared::utils::error::FromErrorStack;
+use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml, csrf_header_check, csrf_header_check_strict};
+use shared::utils::error::{ExtraResultExt, FromErrorStack, Severity};
 use shared::utils::flash::{Flash, FlashMessageExt};
 use shared::utils::htmx::HtmxHeader;
+use shared::utils::log::broadcast::ErrorStackBroadcast;
+use shared::utils::log::model::ErrorStackEvent;
+use shared::utils::query_string::empty_as_none::empty_as_none;
+use shared::utils::query_string::query::QueryQs;
+use std::io;
+use std::time::Duration;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
 
 pub const STACK_ROUTE: &str = "/stack";
 
+/// SSE event type of a new occurrence, see `live`.
+pub const LIVE_EVENT: &str = "error-stack";
+
+/// Id of the newest occurrence the user has seen in the list.
+const SEEN_ID_SESSION_KEY: &str = "stack_seen_id";
+
+/// Comment sent on an idle live stream, so proxies keep it open.
+const LIVE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, Serialize, Default, Clone)]
+struct ListQuery {
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity: Option<Severity>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<StatusFilter>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sort: Option<StackSort>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<SortOrder>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<u32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+impl ListQuery {
+    fn filter(&self) -> ErrorGroupFilter {
+        ErrorGroupFilter {
+            search: self.search.clone(),
+            severity: self.severity,
+            status: self.status.unwrap_or_default(),
+            from: self.from,
+            to: self.to,
+            sort: self.sort.unwrap_or_default(),
+            order: self.order.unwrap_or_default(),
+        }
+    }
+
+    fn url(&self) -> String {
+        match serde_qs::to_string(self) {
+            Ok(query) if !query.is_empty() => format!("{}/?{}", STACK_ROUTE, query),
+            _ => STACK_ROUTE.to_owned() + "/",
+        }
+    }
+
+    fn page_url(&self, page: u32) -> String {
+        Self {
+            page: Some(page),
+            ..self.clone()
+        }
+        .url()
+    }
+
+    fn export_url(&self, format: ExportFormat) -> String {
+        let query = Self {
+            page: None,
+            request_id: None,
+            ..self.clone()
+        };
+        match serde_qs::to_string(&query) {
+            Ok(query) if !query.is_empty() => {
+                format!(
+                    "{}/export?{}&format={}",
+                    STACK_ROUTE,
+                    query,
+                    format.as_str()
+                )
+            }
+            _ => format!("{}/export?format={}", STACK_ROUTE, format.as_str()),
+        }
+    }
+
+    /// Sorts by `sort`, flipping the order when already sorted by it.
+    fn sort_url(&self, sort: StackSort) -> String {
+        let filter = self.filter();
+        let order = if filter.sort == sort {
+            filter.order.reverse()
+        } else if sort == StackSort::Name {
+            SortOrder::Asc
+        } else {
+            SortOrder::Desc
+        };
+        Self {
+            sort: Some(sort),
+            order: Some(order),
+            page: None,
+            ..self.clone()
+        }
+        .url()
+    }
+}
+
+#[derive(Deserialize)]
+struct StatusForm {
+    status: StackStatus,
+    #[serde(default)]
+    note: String,
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+#[derive(Deserialize)]
+struct ClearQuery {
+    days: u32,
+}
+
+fn search_html(lc: &StackLocale, l: &Locale, query: &ListQuery) -> Markup {
+    let url = STACK_ROUTE.to_owned() + "/";
+    let filter = query.filter();
+    html! {
+        form .form .mt-3 action=(url) method="get" hx-get=(url)
+            hx-push-url="true" hx-target="#main-content" {
+            div .flex .gap-3 {
+                input .form-item type="search" name="search" placeholder=(lc.search_placeholder)
+                    value=[query.search.as_deref()] {}
+                select .form-item name="severity" title=(lc.filter_severity) {
+                    option value="" { (lc.filter_all) }
+                    @for severity in Severity::ALL {
+                        option value=(severity.as_str()) selected[query.severity == Some(severity)] {
+                            (severity_text(l, severity))
+                        }
+                    }
+                }
+                select .form-item name="status" title=(lc.filter_status) {
+                    @for status in StatusFilter::ALL {
+                        option value=(status.as_str()) selected[filter.status == status] {
+                            (status_filter_text(l, status))
+                        }
+                    }
+                }
+                input .form-item type="date" name="from" title=(lc.filter_from)
+                    value=[query.from.map(|from| from.to_string())] {}
+                input .form-item type="date" name="to" title=(lc.filter_to)
+                    value=[query.to.map(|to| to.to_string())] {}
+                input .form-item type="text" name="request_id" placeholder=(lc.search_request_id)
+                    value=[query.request_id.as_deref()] {}
+            }
+            input type="hidden" name="sort" value=(filter.sort.as_str()) {}
+            input type="hidden" name="order" value=(filter.order.as_str()) {}
+            div .flex .justify-end .gap-3 {
+                a .btn href=(url) hx-boost="true" hx-push-url="true"
+                    hx-target="#main-content" { (lc.action_reset) }
+                input .btn .btn-sky-blue type="submit" value=(lc.action_search) {}
+            }
+        }
+    }
+}
+
+fn sort_head_html(query: &ListQuery, sort: StackSort, label: &str) -> Markup {
+    let filter = query.filter();
+    let url = query.sort_url(sort);
+    html! {
+        th {
+            a href=(url) hx-boost="true" hx-push-url="true" hx-target="#main-content" {
+                (label)
+                @if filter.sort == sort {
+                    @match filter.order {
+                        SortOrder::Asc => " ▲",
+                        SortOrder::Desc => " ▼",
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn pagination_html(
+    lc: &StackLocale,
+    l: &Locale,
+    query: &ListQuery,
+    page: &ErrorGroupPage,
+) -> Markup {
+    html! {
+        div .flex .justify-end .gap-3 .mt-3 {
+            @if page.page > 1 {
+                a href=(query.page_url(page.page - 1)) hx-boost="true" hx-push-url="true"
+                    hx-target="#main-content" { (lc.action_previous) }
+            }
+            span { (stack_page_message(l, page.page, page.page_count, page.total)) }
+            @if page.page < page.page_count {
+                a href=(query.page_url(page.page + 1)) hx-boost="true" hx-push-url="true"
+                    hx-target="#main-content" { (lc.action_next) }
+            }
+        }
+    }
+}
+
+fn occurrence_row_html(lc: &StackLocale, severity: &str, error_stack: &ListStackModel) -> Markup {
+    html! {
+        tr {
+            td { (error_stack.id) }
+            td { (severity) }
+            td { (error_stack.error_summary) }
+            td x-init="$store.util.formatToLocalTime($el)" { (error_stack.reported_at.to_rfc3339()) }
+            td .action {
+                a .icon href=(format!("{}/view/{}", STACK_ROUTE, error_stack.id))
+                    title=(lc.action_details) hx-boost="true"
+                    hx-push-url="true" hx-target="#main-content" { (document_magnifying_glass_icon()) }
+            }
+        }
+    }
+}
+
+fn occurrences_head_html(lc: &StackLocale) -> Markup {
+    html! {
+        thead {
+            th { (lc.head_id) }
+            th { (lc.head_severity) }
+            th { (lc.head_summary) }
+            th { (lc.head_reported) }
+            th .action { (lc.head_action) }
+        }
+    }
+}
+
+fn occurrences_table_html(lc: &StackLocale, l: &Locale, occurrences: &[ListStackModel]) -> Markup {
+    html! {
+        table .table-full {
+            (occurrences_head_html(lc))
+            tbody {
+                @for error_stack in occurrences {
+                    (occurrence_row_html(lc, &severity_text(l, error_stack.severity), error_stack))
+                }
+            }
+        }
+    }
+}
+
+/// Rows pushed by `live` are prepended here while the list is open.
+fn live_table_html(lc: &StackLocale) -> Markup {
+    html! {
+        div #stack-live .mt-3 {
+            h2 { (lc.head_live) }
+            p { (lc.live_hint) }
+            table .table-full {
+                (occurrences_head_html(lc))
+                tbody sse-swap=(LIVE_EVENT) hx-target="this" hx-swap="afterbegin" {}
+            }
+        }
+    }
+}
+
+/// Stores the newest occurrence id as seen, leaving the session untouched when it is.
+fn mark_seen(session: &Session, latest_id: i64) {
+    if session.get::<i64>(SEEN_ID_SESSION_KEY) != Some(latest_id) {
+        session.set(SEEN_ID_SESSION_KEY, latest_id);
+    }
+}
+
+fn unread_badge_text(count: i64) -> String {
+    match count {
+        ..=0 => String::new(),
+        1..=99 => count.to_string(),
+        _ => "99+".to_string(),
+    }
+}
+
 #[handler]
 fn list_error_stack(
     Dep(stack_service): Dep<StackService>,
     Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    QueryQs(query): QueryQs<ListQuery>,
     csrf_token: &CsrfToken,
+    session: &Session,
 ) -> Markup {
-    let error_stack_list = stack_service.list_error_stack();
+    mark_seen(session, stack_service.latest_error_stack_id());
+    let search_results = query
+        .request_id
+        .as_deref()
+        .map(|request_id| (request_id, stack_service.find_by_request_id(request_id)));
+    let error_groups = match search_results {
+        Some(_) => None,
+        None => Some(stack_service.list_error_groups(&query.filter(), query.page.unwrap_or(1))),
+    };
     let open_icon = document_magnifying_glass_icon();
     let clear_icon = no_symbol_icon();
+    let download_icon = arrow_down_tray_icon();
 
-    let lc = StackLocale::new(&context_html_builder.locale);
+    let l = &context_html_builder.locale;
+    let lc = StackLocale::new(l);
     let title = lc.title.as_str();
 
     context_html_builder
@@ -34,41 +348,193 @@ fn list_error_stack(
         .set_current_tag("id-tag-stack")
         .attach_content(html! {
             h1 { (title) }
-            table .table-full {
-                thead {
-                    th { (lc.head_id) }
-                    th { (lc.head_name) }
-                    th { (lc.head_summary) }
-                    th { (lc.head_reported) }
-                    th .action { (lc.head_action) }
-                }
-                tbody {
-                    @for error_stack in error_stack_list.iter() {
-                        tr {
-                            td { (error_stack.id) }
-                            td { (error_stack.error_name) }
-                            td { (error_stack.error_summary) }
-                            td x-init="$store.util.formatToLocalTime($el)" { (error_stack.reported_at.to_rfc3339()) }
-                            td .action {
-                                a .icon href=(format!("{}/view/{}", STACK_ROUTE, error_stack.id))
-                                    title=(lc.action_details) hx-boost="true"
-                                    hx-push-url="true" hx-target="#main-content" { (open_icon) }
+            (search_html(&lc, l, &query))
+            @if let Some((request_id, occurrences)) = &search_results {
+                p .mt-3 { (stack_search_results_message(l, request_id, occurrences.len())) }
+                (occurrences_table_html(&lc, l, occurrences))
+            }
+            @if let Some(error_groups) = &error_groups {
+                (live_table_html(&lc))
+                table .table-full .mt-3 {
+                    thead {
+                        th { (lc.head_id) }
+                        (sort_head_html(&query, StackSort::Severity, &lc.head_severity))
+                        (sort_head_html(&query, StackSort::Name, &lc.head_name))
+                        th { (lc.head_summary) }
+                        th { (lc.head_status) }
+                        (sort_head_html(&query, StackSort::Occurrences, &lc.head_occurrences))
+                        (sort_head_html(&query, StackSort::FirstSeen, &lc.head_first_seen))
+                        (sort_head_html(&query, StackSort::LastSeen, &lc.head_last_seen))
+                        th .action { (lc.head_action) }
+                    }
+                    tbody {
+                        @for error_group in error_groups.items.iter() {
+                            tr {
+                                td { (error_group.id) }
+                                td { (severity_text(l, error_group.severity)) }
+                                td { (error_group.error_name) }
+                                td { (error_group.error_summary) }
+                                td { (status_text(l, error_group.status)) }
+                                td { (error_group.occurrences) }
+                                td x-init="$store.util.formatToLocalTime($el)" { (error_group.first_seen.to_rfc3339()) }
+                                td x-init="$store.util.formatToLocalTime($el)" { (error_group.last_seen.to_rfc3339()) }
+                                td .action {
+                                    a .icon href=(format!("{}/group/{}", STACK_ROUTE, error_group.id))
+                                        title=(lc.action_occurrences) hx-boost="true"
+                                        hx-push-url="true" hx-target="#main-content" { (open_icon) }
+                                }
                             }
                         }
                     }
                 }
+                (pagination_html(&lc, l, &query, error_groups))
             }
-            div .text-right .mt-3 {
-                a .inline-block hx-confirm=(stack_clear_confirm_message(&context_html_builder.locale)) href=(format!("{}/clear", STACK_ROUTE))
-                title=(lc.action_clear) hx-delete=(format!("{}/clear", STACK_ROUTE)) { (clear_icon) }
+            div .flex .justify-end .gap-3 .mt-3 {
+                @for format in ExportFormat::ALL {
+                    a .btn .btn-sky-blue href=(query.export_url(format)) download {
+                        (stack_export_action_text(l, format.label())) (download_icon)
+                    }
+                }
+                @for days in stack_service.clear_choices_days() {
+                    button .btn .btn-sky-blue type="button" hx-confirm=(stack_clear_confirm_message(l, *days))
+                        hx-delete=(format!("{}/clear?days={}", STACK_ROUTE, days)) {
+                        (stack_clear_action_text(l, *days)) (clear_icon)
+                    }
+                }
             }
         })
         .attach_footer(html!{
             (csrf_token.as_html_command())
+            span hidden hx-swap-oob="innerHTML:#id-tag-stack-badge" {}
         })
         .build()
 }
 
+#[handler]
+fn fetch_error_group_detail(
+    Dep(stack_service): Dep<StackService>,
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Path(group_id): Path<i64>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    let group = stack_service
+        .fetch_error_group(group_id)
+        .map_err(poem::Error::from_error_stack)?;
+    let occurrences = stack_service.list_group_occurrences(group_id);
+    let status_changes = stack_service.list_status_changes(group_id);
+
+    let l = &context_html_builder.locale;
+    let lc = StackLocale::new(l);
+    let lcg = StackGroupLocale::new(l, group.error_name.as_str());
+    let title = lcg.title.as_str();
+
+    Ok(context_html_builder
+        .attach_title(title)
+        .set_current_tag("id-tag-stack")
+        .attach_content(html! {
+            h1 { (title) }
+            table .table-full {
+                thead {
+                    th { (lc.head_severity) }
+                    th { (lc.head_status) }
+                    th { (lc.head_occurrences) }
+                    th { (lc.head_first_seen) }
+                    th { (lc.head_last_seen) }
+                }
+                tbody {
+                    tr {
+                        td { (severity_text(l, group.severity)) }
+                        td { (status_text(l, group.status)) }
+                        td { (group.occurrences) }
+                        td x-init="$store.util.formatToLocalTime($el)" { (group.first_seen.to_rfc3339()) }
+                        td x-init="$store.util.formatToLocalTime($el)" { (group.last_seen.to_rfc3339()) }
+                    }
+                }
+            }
+            h2 .mt-3 { (lcg.head_change_status) }
+            form .form .mt-3 method="post" action=(format!("{}/group/{}/status", STACK_ROUTE, group.id))
+                hx-boost="true" hx-target="#main-content" {
+                (csrf_token.as_html_input())
+                div .form-group {
+                    select .form-item .w-full name="status" title=(lc.head_status) {
+                        @for status in StackStatus::ALL {
+                            option value=(status.as_str()) selected[group.status == status] {
+                                (status_text(l, status))
+                            }
+                        }
+                    }
+                }
+                div .form-group {
+                    textarea .form-item .w-full name="note" maxlength="1000"
+                        placeholder=(lcg.note_placeholder) {}
+                }
+                div .form-group {
+                    input .btn .btn-sky-blue type="submit" value=(lcg.action_change_status) {}
+                }
+            }
+            @if !status_changes.is_empty() {
+                h2 .mt-3 { (lcg.head_history) }
+                table .table-full {
+                    thead {
+                        th { (lcg.head_changed_at) }
+                        th { (lc.head_status) }
+                        th { (lcg.head_changed_by) }
+                        th { (lcg.head_note) }
+                    }
+                    tbody {
+                        @for change in status_changes.iter() {
+                            tr {
+                                td x-init="$store.util.formatToLocalTime($el)" { (change.changed_at.to_rfc3339()) }
+                                td { (status_text(l, change.status)) }
+                                td {
+                                    @match (&change.username, change.user_id) {
+                                        (Some(username), _) => (username),
+                                        (None, Some(user_id)) => { "#" (user_id) }
+                                        (None, None) => (lcg.changed_by_system),
+                                    }
+                                }
+                                td { (change.note.as_deref().unwrap_or_default()) }
+                            }
+                        }
+                    }
+                }
+            }
+            h2 .mt-3 { (lcg.head_occurrences) }
+            p { (stack_group_samples_message(l, occurrences.len(), group.occurrences)) }
+            (occurrences_table_html(&lc, l, &occurrences))
+        })
+        .build())
+}
+
+#[handler]
+fn change_group_status(
+    Dep(stack_service): Dep<StackService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    Path(group_id): Path<i64>,
+    CsrfFormQs(status_form): CsrfFormQs<StatusForm>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> poem::Result<Response> {
+    stack_service
+        .change_status(
+            group_id,
+            status_form.status,
+            user_pointer.id,
+            &user_pointer.username,
+            &status_form.note,
+        )
+        .map_err(poem::Error::from_error_stack)?;
+
+    session.flash(Flash::Success {
+        msg: stack_status_changed_message(&locale, status_form.status),
+    });
+    Ok(htmx_header.do_location(
+        Redirect::see_other(format!("{}/group/{}", STACK_ROUTE, group_id)),
+        "#main-content",
+    ))
+}
+
 #[handler]
 fn fetch_error_stack_detail(
     Dep(stack_service): Dep<StackService>,
@@ -86,28 +552,238 @@ fn fetch_error_stack_detail(
         .attach_title(title)
         .attach_content(html! {
             h1 { (title) }
+            @if let Some(group_id) = item.group_id {
+                p {
+                    a href=(format!("{}/group/{}", STACK_ROUTE, group_id)) hx-boost="true"
+                        hx-push-url="true" hx-target="#main-content" { (lc.action_group) }
+                }
+            }
+            div .flex .justify-end .gap-3 {
+                a .btn .btn-sky-blue href=(format!("{}/view/{}/download", STACK_ROUTE, item.id))
+                    download { (lc.action_download) (arrow_down_tray_icon()) }
+            }
             h2 { (lc.head_reported) }
             pre .pre x-init="$store.util.formatToLocalTime($el)" { (item.reported_at.to_rfc3339()) }
+            h2 { (lc.head_severity) }
+            pre .pre { (severity_text(&context_html_builder.locale, item.severity)) }
             h2 { (lc.head_summary) }
             pre .pre { (item.error_summary) }
+            @if let Some(request) = &item.request {
+                h2 { (lc.head_request) }
+                table .table-full {
+                    tbody {
+                        tr { th { (lc.head_server) } td { (request.server) } }
+                        tr { th { (lc.head_method) } td { (request.method) } }
+                        tr {
+                            th { (lc.head_path) }
+                            td {
+                                (request.path)
+                                @if let Some(query) = &request.query { "?" (query) }
+                            }
+                        }
+                        @if let Some(user_id) = request.user_id {
+                            tr { th { (lc.head_user_id) } td { (user_id) } }
+                        }
+                        @if let Some(request_id) = &request.request_id {
+                            tr {
+                                th { (lc.head_request_id) }
+                                td {
+                                    a href=(format!("{}/?request_id={}", STACK_ROUTE, request_id))
+                                        hx-boost="true" hx-push-url="true"
+                                        hx-target="#main-content" { (request_id) }
+                                }
+                            }
+                        }
+                    }
+                }
+                @if !request.htmx.is_empty() {
+                    h2 { (lc.head_htmx) }
+                    table .table-full {
+                        tbody {
+                            @for (name, value) in request.htmx.iter().filter(|(_, value)| !value.is_null()) {
+                                tr {
+                                    th { (name) }
+                                    td {
+                                        @match value.as_str() {
+                                            Some(value) => (value),
+                                            None => (value.to_string()),
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                @if !request.headers.is_empty() {
+                    h2 { (lc.head_headers) }
+                    table .table-full {
+                        tbody {
+                            @for (name, value) in &request.headers {
+                                tr { th { (name) } td { (value) } }
+                            }
+                        }
+                    }
+                }
+            }
             h2 { (lc.head_stack) }
             pre .pre { (item.error_stack) }
         })
         .build())
 }
 
+#[handler]
+fn download_error_stack(
+    Dep(stack_service): Dep<StackService>,
+    Path(view_id): Path<i64>,
+) -> poem::Result<Response> {
+    let item = stack_service
+        .fetch_error_stack(view_id)
+        .map_err(poem::Error::from_error_stack)?;
+    let body = to_pretty_json(&item).map_err(poem::Error::from_error_stack)?;
+
+    Ok(Response::builder()
+        .content_type(ExportFormat::Json.content_type())
+        .header(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"error-stack-{}.json\"", item.id),
+        )
+        .body(body))
+}
+
+/// Streams the occurrences of the groups the list shows, in the list's order, one batch
+/// per chunk.
+#[handler]
+fn export(
+    Dep(stack_service): Dep<StackService>,
+    QueryQs(query): QueryQs<ListQuery>,
+    QueryQs(export_query): QueryQs<ExportQuery>,
+) -> Response {
+    let format = export_query.format;
+    let filename = format!(
+        "error-stack-{}.{}",
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        format.as_str()
+    );
+    let filter = query.filter();
+
+    let chunks = stream::unfold(Some((stack_service, 0i64)), move |state| {
+        let filter = filter.clone();
+        async move {
+            let (stack_service, offset) = state?;
+            let mut buf = String::new();
+            if offset == 0 {
+                format.begin(&mut buf);
+            }
+            let batch = match stack_service.export_batch(&filter, offset) {
+                Ok(batch) => batch,
+                Err(_) => return Some((Err(io::Error::other("export failed")), None)),
+            };
+            for (i, entry) in batch.iter().enumerate() {
+                let written = format
+                    .write_entry(&mut buf, entry, offset == 0 && i == 0)
+                    .log_it();
+                if written.is_err() {
+                    return Some((Err(io::Error::other("export failed")), None));
+                }
+            }
+            let next = if batch.is_empty() {
+                format.end(&mut buf);
+                None
+            } else {
+                Some((stack_service, offset + batch.len() as i64))
+            };
+            Some((Ok(buf), next))
+        }
+    });
+
+    Response::builder()
+        .content_type(format.content_type())
+        .header(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(Body::from_bytes_stream(chunks))
+}
+
+/// New occurrences outside ignored groups. `allowed` is asked again before each one is
+/// sent, the stream ends once it says no.
+fn live_events(
+    receiver: Receiver<ErrorStackEvent>,
+    allowed: impl Fn() -> bool + Send + 'static,
+) -> impl Stream<Item = ErrorStackEvent> + Send {
+    stream::unfold((receiver, allowed), |(mut receiver, allowed)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if event.ignored => continue,
+                Ok(event) if allowed() => return Some((event, (receiver, allowed))),
+                Ok(_) | Err(RecvError::Closed) => return None,
+                Err(RecvError::Lagged(_)) => continue,
+            }
+        }
+    })
+}
+
+/// Pushes every new occurrence as a table row, for the live table and the unread badge.
+/// The stream outlives the request, so the login is checked again for each row.
+#[handler]
+fn live(
+    Dep(error_stack_broadcast): Dep<ErrorStackBroadcast>,
+    Dep(user_check_service): Dep<UserCheckService>,
+    locale: Locale,
+) -> SSE {
+    let lc = StackLocale::new(&locale);
+    let severities = Severity::ALL.map(|severity| (severity, severity_text(&locale, severity)));
+    // The role `stack_route` is nested under.
+    let events = live_events(error_stack_broadcast.subscribe(), move || {
+        user_check_service.current_role() == Role::Root
+    })
+    .map(move |event| {
+        let severity = severities
+            .iter()
+            .find(|(severity, _)| *severity == event.severity)
+            .map(|(_, text)| text.as_str())
+            .unwrap_or_default();
+        let row = occurrence_row_html(&lc, severity, &ListStackModel::from(event));
+        Event::message(row.into_string()).event_type(LIVE_EVENT)
+    });
+
+    SSE::new(events).keep_alive(LIVE_KEEP_ALIVE)
+}
+
+/// Occurrences since the list was last opened, empty while it is open.
+#[handler]
+fn unread(
+    Dep(stack_service): Dep<StackService>,
+    session: &Session,
+    htmx_header: HtmxHeader,
+) -> String {
+    let list_open = htmx_header
+        .current_url
+        .as_deref()
+        .and_then(|url| url.parse::<Uri>().ok())
+        .is_some_and(|url| url.path() == STACK_ROUTE.to_owned() + "/");
+    if list_open {
+        mark_seen(session, stack_service.latest_error_stack_id());
+        return String::new();
+    }
+    let seen_id = session.get::<i64>(SEEN_ID_SESSION_KEY).unwrap_or_default();
+    unread_badge_text(stack_service.count_unread(seen_id))
+}
+
 #[handler]
 fn clear(
     Dep(stack_service): Dep<StackService>,
+    QueryQs(query): QueryQs<ClearQuery>,
     session: &Session,
+    locale: Locale,
     htmx_header: HtmxHeader,
 ) -> poem::Result<Response> {
-    stack_service
-        .clear()
+    let deleted = stack_service
+        .clear(query.days)
         .map_err(poem::Error::from_error_stack)?;
 
     session.flash(Flash::Success {
-        msg: "Successfully clear records older than 30 days".to_string(),
+        msg: stack_clear_success_message(&locale, deleted, query.days),
     });
     Ok(htmx_header.do_location(
         Redirect::see_other(STACK_ROUTE.to_owned() + "/"),
@@ -118,6 +794,75 @@ fn clear(
 pub fn stack_route() -> Route {
     Route::new()
         .at("/", get(list_error_stack))
+        .at("/group/:group_id", get(fetch_error_group_detail))
+        .at(
+            "/group/:group_id/status",
+            post(csrf_header_check(change_group_status)),
+        )
         .at("/view/:view_id", get(fetch_error_stack_detail))
-        .at("/clear", get(clear).delete(csrf_header_check_strict(clear)))
+        .at("/view/:view_id/download", get(download_error_stack))
+        .at("/export", get(export))
+        .at("/live", get(live))
+        .at("/unread", get(unread))
+        .at("/clear", delete(csrf_header_check_strict(clear)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::sync::broadcast::channel;
+
+    fn event(id: i64, ignored: bool) -> ErrorStackEvent {
+        ErrorStackEvent {
+            id,
+            group_id: 1,
+            severity: Severity::Error,
+            error_summary: "Summary".to_string(),
+            reported_at: Default::default(),
+            ignored,
+        }
+    }
+
+    #[test]
+    fn test_unread_badge_text() {
+        assert_eq!(unread_badge_text(-1), "");
+        assert_eq!(unread_badge_text(0), "");
+        assert_eq!(unread_badge_text(1), "1");
+        assert_eq!(unread_badge_text(99), "99");
+        assert_eq!(unread_badge_text(100), "99+");
+    }
+
+    #[test]
+    fn test_live_events_skip_ignored_groups() {
+        let (sender, receiver) = channel(4);
+        sender.send(event(1, true)).unwrap();
+        sender.send(event(2, false)).unwrap();
+        drop(sender);
+
+        let ids: Vec<i64> = live_events(receiver, || true)
+            .map(|event| event.id)
+            .collect()
+            .now_or_never()
+            .unwrap();
+        assert_eq!(ids, vec![2]);
+    }
+
+    #[test]
+    fn test_live_events_end_once_not_allowed() {
+        let (sender, receiver) = channel(4);
+        sender.send(event(1, false)).unwrap();
+        sender.send(event(2, false)).unwrap();
+
+        // Allowed for the first event only.
+        let allowed = AtomicBool::new(true);
+        let ids: Vec<i64> = live_events(receiver, move || allowed.swap(false, Ordering::SeqCst))
+            .map(|event| event.id)
+            .collect()
+            .now_or_never()
+            .unwrap();
+        assert_eq!(ids, vec![1]);
+        drop(sender);
+    }
 }
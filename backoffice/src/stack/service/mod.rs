@@ -1 +1,2 @@
+pub mod stack_export;
 pub mod stack_service;
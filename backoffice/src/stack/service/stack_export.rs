@@ -0,0 +1,241 @@
+use crate::stack::model::stack_model::StackModel;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Serialize error")]
+    SerializeError,
+}
+
+const CSV_COLUMNS: [&str; 15] = [
+    "id",
+    "group_id",
+    "severity",
+    "error_name",
+    "error_summary",
+    "reported_at",
+    "server",
+    "method",
+    "path",
+    "query",
+    "user_id",
+    "request_id",
+    "htmx",
+    "headers",
+    "error_stack",
+];
+
+/// File format of a stack export, every format carries the full stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Ndjson,
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] =
+        [ExportFormat::Ndjson, ExportFormat::Json, ExportFormat::Csv];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "ndjson",
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "NDJSON",
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    /// Written once, before the first entry.
+    pub fn begin(&self, buf: &mut String) {
+        match self {
+            Self::Ndjson => {}
+            Self::Json => buf.push('['),
+            Self::Csv => {
+                buf.push_str(&CSV_COLUMNS.join(","));
+                buf.push_str("\r\n");
+            }
+        }
+    }
+
+    /// Appends one entry, `first` is set for the first entry of the export.
+    pub fn write_entry(
+        &self,
+        buf: &mut String,
+        entry: &StackModel,
+        first: bool,
+    ) -> Result<(), Report<ExportError>> {
+        match self {
+            Self::Ndjson => {
+                buf.push_str(&to_json(entry)?);
+                buf.push('\n');
+            }
+            Self::Json => {
+                if !first {
+                    buf.push(',');
+                }
+                buf.push_str(&to_json(entry)?);
+            }
+            Self::Csv => write_csv_entry(buf, entry)?,
+        }
+        Ok(())
+    }
+
+    /// Written once, after the last entry.
+    pub fn end(&self, buf: &mut String) {
+        if *self == Self::Json {
+            buf.push_str("]\n");
+        }
+    }
+}
+
+/// A single occurrence as indented JSON, for downloading one stack.
+pub fn to_pretty_json(entry: &StackModel) -> Result<String, Report<ExportError>> {
+    serde_json::to_string_pretty(entry)
+        .change_context(ExportError::SerializeError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, Report<ExportError>> {
+    serde_json::to_string(value)
+        .change_context(ExportError::SerializeError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn write_csv_entry(buf: &mut String, entry: &StackModel) -> Result<(), Report<ExportError>> {
+    let request = entry.request.as_ref();
+    let fields = [
+        entry.id.to_string(),
+        entry.group_id.map(|id| id.to_string()).unwrap_or_default(),
+        entry.severity.as_str().to_owned(),
+        entry.error_name.clone(),
+        entry.error_summary.clone(),
+        entry.reported_at.to_rfc3339(),
+        request.map(|r| r.server.clone()).unwrap_or_default(),
+        request.map(|r| r.method.clone()).unwrap_or_default(),
+        request.map(|r| r.path.clone()).unwrap_or_default(),
+        request.and_then(|r| r.query.clone()).unwrap_or_default(),
+        request
+            .and_then(|r| r.user_id)
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+        request
+            .and_then(|r| r.request_id.clone())
+            .unwrap_or_default(),
+        request
+            .map(|r| to_json(&r.htmx))
+            .transpose()?
+            .unwrap_or_default(),
+        request
+            .map(|r| to_json(&r.headers))
+            .transpose()?
+            .unwrap_or_default(),
+        entry.error_stack.clone(),
+    ];
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        write_csv_field(buf, field);
+    }
+    buf.push_str("\r\n");
+    Ok(())
+}
+
+/// Quotes a field when it holds a separator, quote or line break (RFC 4180). A field
+/// starting like a formula gets a `'` in front so spreadsheets show it as text.
+fn write_csv_field(buf: &mut String, field: &str) {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{field}")
+    } else {
+        field.to_owned()
+    };
+    if field.contains([',', '"', '\r', '\n']) {
+        let _ = write!(buf, "\"{}\"", field.replace('"', "\"\""));
+    } else {
+        buf.push_str(&field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::utils::error::Severity;
+
+    fn entry(id: i64) -> StackModel {
+        StackModel {
+            id,
+            group_id: Some(1),
+            severity: Severity::Error,
+            error_name: "Name".to_string(),
+            error_summary: "Says \"hi\", twice".to_string(),
+            error_stack: "line 1\nline 2".to_string(),
+            reported_at: Default::default(),
+            request: None,
+        }
+    }
+
+    #[test]
+    fn test_export_format_csv_quotes_fields() {
+        let mut buf = String::new();
+        ExportFormat::Csv
+            .write_entry(&mut buf, &entry(1), true)
+            .unwrap();
+        assert_eq!(
+            buf,
+            "1,1,error,Name,\"Says \"\"hi\"\", twice\",1970-01-01T00:00:00+00:00,,,,,,,,,\"line 1\nline 2\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_export_format_json_is_an_array() {
+        let mut buf = String::new();
+        let format = ExportFormat::Json;
+        format.begin(&mut buf);
+        format.write_entry(&mut buf, &entry(1), true).unwrap();
+        format.write_entry(&mut buf, &entry(2), false).unwrap();
+        format.end(&mut buf);
+
+        let value: serde_json::Value = serde_json::from_str(&buf).unwrap();
+        assert_eq!(value.as_array().map(Vec::len), Some(2));
+        assert_eq!(value[1]["id"], 2);
+    }
+
+    #[test]
+    fn test_export_format_csv_escapes_formulas() {
+        let mut entry = entry(1);
+        entry.error_name = "=HYPERLINK(\"x\")".to_string();
+        entry.error_summary = "-1+2".to_string();
+        entry.error_stack = "@SUM(A1)".to_string();
+
+        let mut buf = String::new();
+        ExportFormat::Csv
+            .write_entry(&mut buf, &entry, true)
+            .unwrap();
+        assert_eq!(
+            buf,
+            "1,1,error,\"'=HYPERLINK(\"\"x\"\")\",'-1+2,1970-01-01T00:00:00+00:00,,,,,,,,,'@SUM(A1)\r\n"
+        );
+    }
+}
@@ -1,7 +1,12 @@
-use crate::stack::model::stack_model::{ListStackModel, StackModel};
+use crate::stack::model::stack_model::{
+    ErrorGroupFilter, ErrorGroupModel, ErrorGroupPage, ListStackModel, StackModel, StackStatus,
+    StatusChangeModel,
+};
 use crate::stack::repository::stack_repository::StackRepository;
 use error_stack::{Report, ResultExt};
 use poem::http::StatusCode;
+use shared::utils::config::ConfigPointer;
+use shared::utils::config::error_stack_log::ErrorStackConfig;
 use shared::utils::context::{Context, ContextError, FromContext};
 use shared::utils::error::ExtraResultExt;
 use std::sync::Arc;
@@ -13,20 +18,60 @@ pub enum StackServiceError {
     DbError,
     #[error("Not found")]
     NotFound,
+    #[error("Clear choice not allowed")]
+    InvalidClearChoice,
+    #[error("Status note too long")]
+    NoteTooLong,
 }
 
+/// Longest note accepted with a status change, in characters.
+const MAX_STATUS_NOTE_LEN: usize = 1000;
+
+/// Occurrences read per query while exporting.
+const EXPORT_BATCH_SIZE: u32 = 500;
+
 pub struct StackService {
     stack_repository: StackRepository,
+    error_stack_config: Arc<ErrorStackConfig>,
 }
 
 impl StackService {
-    pub fn new(stack_repository: StackRepository) -> Self {
-        Self { stack_repository }
+    pub fn new(
+        stack_repository: StackRepository,
+        error_stack_config: Arc<ErrorStackConfig>,
+    ) -> Self {
+        Self {
+            stack_repository,
+            error_stack_config,
+        }
+    }
+
+    pub fn retention_days(&self) -> u32 {
+        self.error_stack_config.retention_days
+    }
+
+    pub fn clear_choices_days(&self) -> &[u32] {
+        &self.error_stack_config.clear_choices_days
     }
 
-    pub fn clear(&self) -> Result<(), Report<StackServiceError>> {
+    /// Deletes entries older than one of the configured clear choices, returns how many were deleted.
+    pub fn clear(&self, older_than_days: u32) -> Result<usize, Report<StackServiceError>> {
+        if !self.clear_choices_days().contains(&older_than_days) {
+            return Err(
+                Report::new(StackServiceError::InvalidClearChoice).attach(StatusCode::BAD_REQUEST)
+            );
+        }
+        self.clear_older_than(older_than_days)
+    }
+
+    /// Deletes entries outside the retention period, returns how many were deleted.
+    pub fn clear_expired(&self) -> Result<usize, Report<StackServiceError>> {
+        self.clear_older_than(self.retention_days())
+    }
+
+    fn clear_older_than(&self, older_than_days: u32) -> Result<usize, Report<StackServiceError>> {
         self.stack_repository
-            .clear()
+            .clear(older_than_days)
             .change_context(StackServiceError::DbError)
             .log_it()
     }
@@ -39,14 +84,126 @@ impl StackService {
             .ok_or_else(|| Report::new(StackServiceError::NotFound).attach(StatusCode::NOT_FOUND))
     }
 
-    pub fn list_error_stack(&self) -> Arc<[ListStackModel]> {
-        self.stack_repository.list_error_stack().unwrap_or_default()
+    pub fn fetch_error_group(&self, id: i64) -> Result<ErrorGroupModel, Report<StackServiceError>> {
+        self.stack_repository
+            .fetch_error_group(id)
+            .change_context(StackServiceError::DbError)
+            .log_it()?
+            .ok_or_else(|| Report::new(StackServiceError::NotFound).attach(StatusCode::NOT_FOUND))
+    }
+
+    /// Lists one page of the groups matching `filter`, `page` is clamped to the pages there are.
+    pub fn list_error_groups(&self, filter: &ErrorGroupFilter, page: u32) -> ErrorGroupPage {
+        let page_size = self.error_stack_config.page_size.max(1);
+        let total = self
+            .stack_repository
+            .count_error_groups(self.retention_days(), filter.clone())
+            .unwrap_or_default();
+        let page_count = u32::try_from((total.max(0) as u64).div_ceil(page_size.into()))
+            .unwrap_or(u32::MAX)
+            .max(1);
+        let page = page.clamp(1, page_count);
+        let items = self
+            .stack_repository
+            .list_error_groups(
+                self.retention_days(),
+                filter.clone(),
+                page_size,
+                (page as i64 - 1) * page_size as i64,
+            )
+            .unwrap_or_default();
+
+        ErrorGroupPage {
+            items,
+            page,
+            page_count,
+            total,
+        }
+    }
+
+    /// Sets the status of a group on behalf of `user_id`, a blank note is not recorded.
+    pub fn change_status(
+        &self,
+        group_id: i64,
+        status: StackStatus,
+        user_id: i64,
+        username: &str,
+        note: &str,
+    ) -> Result<(), Report<StackServiceError>> {
+        let note = note.trim();
+        if note.chars().count() > MAX_STATUS_NOTE_LEN {
+            return Err(Report::new(StackServiceError::NoteTooLong).attach(StatusCode::BAD_REQUEST));
+        }
+        let note = (!note.is_empty()).then(|| note.to_owned());
+
+        let updated = self
+            .stack_repository
+            .update_group_status(group_id, status, user_id, username.to_owned(), note)
+            .change_context(StackServiceError::DbError)
+            .log_it()?;
+        if !updated {
+            return Err(Report::new(StackServiceError::NotFound).attach(StatusCode::NOT_FOUND));
+        }
+        Ok(())
+    }
+
+    pub fn list_status_changes(&self, group_id: i64) -> Arc<[StatusChangeModel]> {
+        self.stack_repository
+            .list_status_changes(group_id)
+            .unwrap_or_default()
+    }
+
+    pub fn list_group_occurrences(&self, group_id: i64) -> Arc<[ListStackModel]> {
+        self.stack_repository
+            .list_group_occurrences(group_id)
+            .unwrap_or_default()
+    }
+
+    /// Next batch of occurrences for an export, after the first `offset`; empty once done.
+    pub fn export_batch(
+        &self,
+        filter: &ErrorGroupFilter,
+        offset: i64,
+    ) -> Result<Arc<[StackModel]>, Report<StackServiceError>> {
+        self.stack_repository
+            .export_error_stacks(
+                self.retention_days(),
+                filter.clone(),
+                EXPORT_BATCH_SIZE,
+                offset,
+            )
+            .change_context(StackServiceError::DbError)
+            .log_it()
+    }
+
+    /// Id of the newest occurrence, 0 when there are none.
+    pub fn latest_error_stack_id(&self) -> i64 {
+        self.stack_repository
+            .latest_error_stack_id()
+            .unwrap_or_default()
+    }
+
+    /// Occurrences stored after `seen_id` for the unread badge.
+    pub fn count_unread(&self, seen_id: i64) -> i64 {
+        self.stack_repository
+            .count_error_stacks_after(seen_id)
+            .unwrap_or_default()
+    }
+
+    pub fn find_by_request_id(&self, request_id: &str) -> Arc<[ListStackModel]> {
+        self.stack_repository
+            .find_by_request_id(request_id.to_owned())
+            .unwrap_or_default()
     }
 }
 
 impl FromContext for StackService {
     async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
-        Ok(Self::new(ctx.inject().await?))
+        let config: ConfigPointer = ctx.inject().await?;
+        Ok(Self::new(
+            ctx.inject().await?,
+            Arc::clone(&config.error_stack),
+        ))
     }
 }
 
@@ -54,29 +211,109 @@ impl FromContext for StackService {
 mod tests {
     use super::*;
     use crate::stack::repository::stack_repository::StackRepositoryError;
+    use shared::utils::error::Severity;
+
+    fn stack_service(stack_repository: StackRepository) -> StackService {
+        StackService::new(stack_repository, Arc::new(ErrorStackConfig::default()))
+    }
 
     #[test]
     fn test_stack_service_clear_success() {
         let mut stack_repository = StackRepository::new_mock();
-        stack_repository.mock_clear().returns_once(Ok(()));
+        stack_repository.mock_clear(7).returns_once(Ok(3));
 
-        let stack_service = StackService::new(stack_repository);
-        let result = stack_service.clear();
-        assert!(result.is_ok());
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.clear(7);
+        assert_eq!(result.ok(), Some(3));
     }
 
     #[test]
     fn test_stack_service_clear_failure() {
         let mut stack_repository = StackRepository::new_mock();
         stack_repository
-            .mock_clear()
+            .mock_clear(7)
             .returns_once(Err(Report::new(StackRepositoryError::QueryError)));
 
-        let stack_service = StackService::new(stack_repository);
-        let result = stack_service.clear();
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.clear(7);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_stack_service_clear_rejects_unlisted_choice() {
+        let stack_repository = StackRepository::new_mock();
+
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.clear(1);
+        let result = result.err().unwrap();
+        let error_code = result.downcast_ref::<StatusCode>().unwrap();
+        assert_eq!(error_code, &StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_stack_service_clear_expired_uses_retention() {
+        let mut stack_repository = StackRepository::new_mock();
+        stack_repository.mock_clear(30).returns_once(Ok(2));
+
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.clear_expired();
+        assert_eq!(result.ok(), Some(2));
+    }
+
+    #[test]
+    fn test_stack_service_list_error_groups_clamps_page() {
+        let filter = ErrorGroupFilter {
+            search: Some("boom".to_string()),
+            ..Default::default()
+        };
+        let mut stack_repository = StackRepository::new_mock();
+        stack_repository
+            .mock_count_error_groups(30, filter.clone())
+            .returns_once(Ok(60));
+        stack_repository
+            .mock_list_error_groups(30, filter.clone(), 25, 50)
+            .returns_once(Ok(Arc::default()));
+
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.list_error_groups(&filter, 9);
+        assert_eq!(result.page, 3);
+        assert_eq!(result.page_count, 3);
+        assert_eq!(result.total, 60);
+    }
+
+    #[test]
+    fn test_stack_service_change_status_trims_note() {
+        let mut stack_repository = StackRepository::new_mock();
+        stack_repository
+            .mock_update_group_status(
+                1,
+                StackStatus::Resolved,
+                2,
+                "admin".to_string(),
+                Some("fixed".to_string()),
+            )
+            .returns_once(Ok(true));
+
+        let stack_service = stack_service(stack_repository);
+        let result =
+            stack_service.change_status(1, StackStatus::Resolved, 2, "admin", "  fixed \n");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_stack_service_change_status_not_found() {
+        let mut stack_repository = StackRepository::new_mock();
+        stack_repository
+            .mock_update_group_status(1, StackStatus::Ignored, 2, "admin".to_string(), None)
+            .returns_once(Ok(false));
+
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.change_status(1, StackStatus::Ignored, 2, "admin", " ");
+        let result = result.err().unwrap();
+        let error_code = result.downcast_ref::<StatusCode>().unwrap();
+        assert_eq!(error_code, &StatusCode::NOT_FOUND);
+    }
+
     #[test]
     fn test_stack_service_fetch_error_stack_success() {
         let mut stack_repository = StackRepository::new_mock();
@@ -84,13 +321,16 @@ mod tests {
             .mock_fetch_error_stack(1)
             .returns_once(Ok(Some(StackModel {
                 id: 1,
+                group_id: Some(1),
+                severity: Severity::Error,
                 error_name: "1".to_string(),
                 error_summary: "1".to_string(),
                 error_stack: "1".to_string(),
                 reported_at: Default::default(),
+                request: None,
             })));
 
-        let stack_service = StackService::new(stack_repository);
+        let stack_service = stack_service(stack_repository);
         let result = stack_service.fetch_error_stack(1);
         assert!(result.is_ok());
     }
@@ -102,7 +342,7 @@ mod tests {
             .mock_fetch_error_stack(1)
             .returns_once(Err(Report::new(StackRepositoryError::QueryError)));
 
-        let stack_service = StackService::new(stack_repository);
+        let stack_service = stack_service(stack_repository);
         let result = stack_service.fetch_error_stack(1);
         assert!(result.is_err());
     }
@@ -114,11 +354,25 @@ mod tests {
             .mock_fetch_error_stack(1)
             .returns_once(Ok(None));
 
-        let stack_service = StackService::new(stack_repository);
+        let stack_service = stack_service(stack_repository);
         let result = stack_service.fetch_error_stack(1);
         assert!(result.is_err());
         let result = result.err().unwrap();
         let error_code = result.downcast_ref::<StatusCode>().unwrap();
         assert_eq!(error_code, &StatusCode::NOT_FOUND);
     }
+
+    #[test]
+    fn test_stack_service_fetch_error_group_not_found() {
+        let mut stack_repository = StackRepository::new_mock();
+        stack_repository
+            .mock_fetch_error_group(1)
+            .returns_once(Ok(None));
+
+        let stack_service = stack_service(stack_repository);
+        let result = stack_service.fetch_error_group(1);
+        let result = result.err().unwrap();
+        let error_code = result.downcast_ref::<StatusCode>().unwrap();
+        assert_eq!(error_code, &StatusCode::NOT_FOUND);
+    }
 }
@@ -144,6 +144,14 @@ impl AddUserError {
             password_confirm: self.password_confirm.as_translated_message(locale),
         }
     }
+
+    pub fn as_original_message(&self) -> AddUserMessage {
+        AddUserMessage {
+            username: self.username.as_original_message(),
+            password: self.password.as_original_message(),
+            password_confirm: self.password_confirm.as_original_message(),
+        }
+    }
 }
 
 pub struct AddUserResult(pub Result<AddUserValidated, AddUserError>);
@@ -114,6 +114,13 @@ impl EditPasswordManagerError {
             password_confirm: self.password_confirm.as_translated_message(locale),
         }
     }
+
+    pub fn as_original_message(&self) -> EditPasswordManagerMessage {
+        EditPasswordManagerMessage {
+            password: self.password.as_original_message(),
+            password_confirm: self.password_confirm.as_original_message(),
+        }
+    }
 }
 
 pub struct EditPasswordManagerResult(
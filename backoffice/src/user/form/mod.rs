@@ -3,3 +3,4 @@ pub mod edit_password_manager;
 pub mod edit_user;
 pub mod locale;
 pub mod login;
+pub mod two_factor;
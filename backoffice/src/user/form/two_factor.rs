@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+/// A TOTP code or a recovery code, checked by `TwoFactorService::verify`.
+#[derive(Deserialize, Clone, Default)]
+pub struct TwoFactorCodeForm {
+    pub code: String,
+}
+
+/// Enrolment carries no fields beyond the csrf token.
+#[derive(Deserialize, Clone, Default)]
+pub struct TwoFactorEnrolForm {}
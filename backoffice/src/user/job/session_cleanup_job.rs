@@ -0,0 +1,52 @@
+use crate::user::repository::login_attempt_repository::LoginAttemptRepository;
+use crate::user::repository::session_repository::SessionRepository;
+use chrono::{DateTime, TimeDelta, Utc};
+use error_stack::{Report, ResultExt};
+use shared::utils::config::ConfigPointer;
+use shared::utils::config::login_throttle::LoginThrottleConfig;
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::jobs::{Job, JobError};
+use std::sync::Arc;
+
+pub const SESSION_CLEANUP_JOB: &str = "session_cleanup";
+
+/// Deletes expired login tokens, which are otherwise never removed, and login attempts
+/// whose failures no longer count.
+pub struct SessionCleanupJob {
+    session_repository: SessionRepository,
+    login_attempt_repository: LoginAttemptRepository,
+    login_throttle_config: Arc<LoginThrottleConfig>,
+}
+
+impl Job for SessionCleanupJob {
+    async fn run(&self) -> Result<String, Report<JobError>> {
+        let deleted_tokens = self
+            .session_repository
+            .delete_expired()
+            .change_context(JobError::Failed)?;
+        let reset_before = i64::try_from(self.login_throttle_config.reset_after_secs)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|reset_after| Utc::now().checked_sub_signed(reset_after))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let deleted_attempts = self
+            .login_attempt_repository
+            .delete_expired(reset_before)
+            .change_context(JobError::Failed)?;
+        Ok(format!(
+            "Deleted {} expired login tokens and {} expired login attempts",
+            deleted_tokens, deleted_attempts
+        ))
+    }
+}
+
+impl FromContext for SessionCleanupJob {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let config: ConfigPointer = ctx.inject().await?;
+        Ok(Self {
+            session_repository: ctx.inject().await?,
+            login_attempt_repository: ctx.inject().await?,
+            login_throttle_config: Arc::clone(&config.login_throttle),
+        })
+    }
+}
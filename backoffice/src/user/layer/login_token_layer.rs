@@ -0,0 +1,13 @@
+use sha2::{Digest, Sha256};
+use shared::utils::cipher::{encode_hex, random_bytes};
+
+/// A new 256 bit login token, only ever handed to the client.
+pub fn generate_login_token() -> String {
+    encode_hex(&random_bytes::<32>())
+}
+
+/// The SHA-256 digest stored in `user_login_tokens`, so a copy of the database cannot be
+/// used to sign in.
+pub fn hash_login_token(token: &str) -> String {
+    encode_hex(&Sha256::digest(token.as_bytes()))
+}
@@ -1 +1,3 @@
+pub mod login_token_layer;
 pub mod password_layer;
+pub mod two_factor_layer;
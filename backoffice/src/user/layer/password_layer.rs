@@ -1,13 +1,19 @@
 use error_stack::Report;
+use shared::utils::config::ConfigPointer;
+use shared::utils::config::password::PasswordConfig;
 use shared::utils::context::{Context, ContextError, FromContext};
 use shared::utils::password::{Password, PasswordError, PasswordState};
+use std::sync::Arc;
 
 #[mry::mry]
-pub struct PasswordLayer {}
+pub struct PasswordLayer {
+    password_config: Arc<PasswordConfig>,
+}
 
 impl PasswordLayer {
-    pub fn new() -> Self {
+    pub fn new(password_config: Arc<PasswordConfig>) -> Self {
         Self {
+            password_config,
             mry: Default::default(),
         }
     }
@@ -20,23 +26,26 @@ impl PasswordLayer {
         password_hash: Box<[u8]>,
         password: &str,
     ) -> Result<PasswordState, Report<PasswordError>> {
-        Password::verify_password(password_hash, password.to_string())
+        Password::verify_password(password_hash, password.to_string(), &self.password_config)
     }
 
     pub fn hash_password(&self, password: &str) -> Result<Password, Report<PasswordError>> {
-        Password::hash_password(password.to_string())
+        Password::hash_password(password.to_string(), &self.password_config)
     }
 }
 
 #[cfg(test)]
 impl PasswordLayer {
     pub fn new_mock() -> Self {
-        mry::new!(Self {})
+        mry::new!(Self {
+            password_config: Default::default()
+        })
     }
 }
 
 impl FromContext for PasswordLayer {
-    async fn from_context(_ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
-        Ok(Self::new())
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let config: ConfigPointer = ctx.inject().await?;
+        Ok(Self::new(Arc::clone(&config.password)))
     }
 }
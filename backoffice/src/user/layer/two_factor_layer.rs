@@ -0,0 +1,158 @@
+use error_stack::{Report, ResultExt};
+use sha2::{Digest, Sha256};
+use shared::utils::cipher::{SecretCipher, random_bytes};
+use shared::utils::config::ConfigPointer;
+use shared::utils::config::two_factor::TwoFactorConfig;
+use shared::utils::context::{Context, ContextError, FromContext};
+use std::sync::Arc;
+use thiserror::Error;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECS: u64 = 30;
+/// 32 symbols without look-alikes, so every random byte maps without bias.
+const RECOVERY_CODE_ALPHABET: &[u8; 32] = b"abcdefghjkmnpqrstuvwxyz023456789";
+
+#[derive(Debug, Error)]
+pub enum TwoFactorLayerError {
+    #[error("Secret error")]
+    SecretError,
+    #[error("Cipher error")]
+    CipherError,
+}
+
+#[mry::mry]
+pub struct TwoFactorLayer {
+    secret_cipher: Option<SecretCipher>,
+    two_factor_config: Arc<TwoFactorConfig>,
+}
+
+impl TwoFactorLayer {
+    pub fn new(secret_cipher: SecretCipher, two_factor_config: Arc<TwoFactorConfig>) -> Self {
+        Self {
+            secret_cipher: Some(secret_cipher),
+            two_factor_config,
+            mry: Default::default(),
+        }
+    }
+
+    fn totp(&self, secret: &str, username: &str) -> Result<TOTP, Report<TwoFactorLayerError>> {
+        let secret = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|_| Report::new(TwoFactorLayerError::SecretError))?;
+        TOTP::new(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            0,
+            TOTP_STEP_SECS,
+            secret,
+            Some(self.two_factor_config.issuer.clone()),
+            username.to_string(),
+        )
+        .change_context(TwoFactorLayerError::SecretError)
+    }
+
+    fn cipher(&self) -> Result<&SecretCipher, Report<TwoFactorLayerError>> {
+        self.secret_cipher
+            .as_ref()
+            .ok_or_else(|| Report::new(TwoFactorLayerError::CipherError))
+    }
+
+    pub fn pending_login_secs(&self) -> u64 {
+        self.two_factor_config.pending_login_secs
+    }
+
+    /// Recovery codes are compared case-insensitively and without separators.
+    pub fn hash_recovery_code(code: &str) -> String {
+        let normalized: String = code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        Sha256::digest(normalized.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[mry::mry]
+impl TwoFactorLayer {
+    /// A new base32 encoded 160 bit secret.
+    pub fn generate_secret(&self) -> String {
+        Secret::Raw(random_bytes::<20>().to_vec())
+            .to_encoded()
+            .to_string()
+    }
+
+    pub fn otpauth_url(
+        &self,
+        secret: &str,
+        username: &str,
+    ) -> Result<String, Report<TwoFactorLayerError>> {
+        Ok(self.totp(secret, username)?.get_url())
+    }
+
+    /// Checks `code` against the steps around `unix_time`, allowing one step of clock drift.
+    ///
+    /// Returns the matching time step.
+    pub fn verify_code(&self, secret: &str, code: &str, unix_time: u64) -> Option<i64> {
+        let totp = self.totp(secret, "").ok()?;
+        [
+            unix_time,
+            unix_time.saturating_sub(TOTP_STEP_SECS),
+            unix_time + TOTP_STEP_SECS,
+        ]
+        .into_iter()
+        .find(|time| totp.check(code, *time))
+        .map(|time| (time / TOTP_STEP_SECS) as i64)
+    }
+
+    /// Encrypts TOTP secrets and other values that must not be readable or forgeable
+    /// outside the server.
+    pub fn encrypt(&self, secret: &str) -> Result<Box<[u8]>, Report<TwoFactorLayerError>> {
+        self.cipher()?
+            .encrypt(secret.as_bytes())
+            .change_context(TwoFactorLayerError::CipherError)
+    }
+
+    pub fn decrypt(&self, data: Box<[u8]>) -> Result<String, Report<TwoFactorLayerError>> {
+        let secret = self
+            .cipher()?
+            .decrypt(&data)
+            .change_context(TwoFactorLayerError::CipherError)?;
+        String::from_utf8(secret).change_context(TwoFactorLayerError::CipherError)
+    }
+
+    pub fn generate_recovery_codes(&self) -> Vec<String> {
+        (0..self.two_factor_config.recovery_code_count)
+            .map(|_| {
+                let code: String = random_bytes::<10>()
+                    .iter()
+                    .map(|byte| RECOVERY_CODE_ALPHABET[(*byte & 31) as usize] as char)
+                    .collect();
+                format!("{}-{}", &code[..5], &code[5..])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl TwoFactorLayer {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            secret_cipher: None,
+            two_factor_config: Default::default()
+        })
+    }
+}
+
+impl FromContext for TwoFactorLayer {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let config: ConfigPointer = ctx.inject().await?;
+        Ok(Self::new(
+            ctx.inject().await?,
+            Arc::clone(&config.two_factor),
+        ))
+    }
+}
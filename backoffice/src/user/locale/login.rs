@@ -1,4 +1,4 @@
-use poem::i18n::Locale;
+use poem::i18n::{I18NArgs, Locale};
 use shared::utils::locale::LocaleExt;
 
 pub struct LoginLocale {
@@ -19,9 +19,33 @@ impl LoginLocale {
     }
 }
 
+pub struct LoginTwoFactorLocale {
+    pub title: String,
+    pub intro: String,
+    pub code: String,
+    pub confirm_button: String,
+}
+
+impl LoginTwoFactorLocale {
+    pub fn new(locale: &Locale) -> Self {
+        Self {
+            title: locale.text_with_default("login-two-factor-title", "Two-Factor Authentication"),
+            intro: locale.text_with_default(
+                "login-two-factor-intro",
+                "Enter the code from your authenticator app, or one of your recovery codes.",
+            ),
+            code: locale.text_with_default("login-two-factor-code", "Authentication code"),
+            confirm_button: locale.text_with_default("login-two-factor-confirm-button", "Verify"),
+        }
+    }
+}
+
 pub struct LoginPostLocale {
     pub flash_success: String,
     pub flash_failed: String,
+    pub flash_locked: String,
+    pub flash_two_factor_invalid: String,
+    pub flash_two_factor_expired: String,
 }
 
 impl LoginPostLocale {
@@ -29,10 +53,30 @@ impl LoginPostLocale {
         Self {
             flash_success: locale.text_with_default("login-post-flash-success", "Login success"),
             flash_failed: locale.text_with_default("login-post-flash-failed", "Login failed"),
+            flash_locked: locale.text_with_default(
+                "login-post-flash-locked",
+                "This account is locked after too many failed logins, ask an administrator to unlock it",
+            ),
+            flash_two_factor_invalid: locale.text_with_default(
+                "login-post-flash-two-factor-invalid",
+                "Invalid authentication code",
+            ),
+            flash_two_factor_expired: locale.text_with_default(
+                "login-post-flash-two-factor-expired",
+                "The login took too long, please enter your password again",
+            ),
         }
     }
 }
 
+pub fn login_throttled_message(locale: &Locale, retry_after_secs: u64) -> String {
+    locale.text_with_default_args(
+        "login-post-flash-throttled",
+        format!("Too many failed logins, try again in {retry_after_secs} seconds").as_str(),
+        I18NArgs::from((("seconds", retry_after_secs),)),
+    )
+}
+
 pub struct LogoutLocale {
     pub flash_success: String,
 }
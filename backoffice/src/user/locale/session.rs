@@ -0,0 +1,58 @@
+use poem::i18n::{I18NArgs, Locale};
+use shared::utils::locale::LocaleExt;
+
+pub struct SessionLocale {
+    pub title: String,
+    pub head_created_at: String,
+    pub head_last_seen_at: String,
+    pub head_user_agent: String,
+    pub head_client_ip: String,
+    pub head_action: String,
+    pub current: String,
+    pub unknown: String,
+    pub empty: String,
+    pub action_revoke: String,
+    pub revoke_confirm: String,
+}
+
+impl SessionLocale {
+    pub fn new(l: &Locale) -> Self {
+        Self {
+            title: l.text_with_default("session-title", "My Sessions"),
+            head_created_at: l.text_with_default("session-head-created-at", "Signed In"),
+            head_last_seen_at: l.text_with_default("session-head-last-seen-at", "Last Seen"),
+            head_user_agent: l.text_with_default("session-head-user-agent", "Device"),
+            head_client_ip: l.text_with_default("session-head-client-ip", "IP Address"),
+            head_action: l.text_with_default("session-head-action", "Action"),
+            current: l.text_with_default("session-current", "This session"),
+            unknown: l.text_with_default("session-unknown", "Unknown"),
+            empty: l.text_with_default("session-empty", "There are no active sessions."),
+            action_revoke: l.text_with_default("session-action-revoke", "Revoke Session"),
+            revoke_confirm: l.text_with_default(
+                "session-revoke-confirm",
+                "Are you sure you want to revoke this session?",
+            ),
+        }
+    }
+}
+
+pub struct SessionFlashLocale {
+    pub success_revoked: String,
+}
+
+impl SessionFlashLocale {
+    pub fn new(l: &Locale) -> Self {
+        Self {
+            success_revoked: l
+                .text_with_default("session-flash-success-revoked", "Session revoked"),
+        }
+    }
+}
+
+pub fn session_title_for_user(l: &Locale, username: &str) -> String {
+    l.text_with_default_args(
+        "session-title-for-user",
+        format!("Sessions of '{username}'").as_str(),
+        I18NArgs::from((("username", username),)),
+    )
+}
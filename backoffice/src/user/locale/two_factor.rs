@@ -0,0 +1,82 @@
+use poem::i18n::{I18NArgs, Locale};
+use shared::utils::locale::LocaleExt;
+
+pub struct TwoFactorLocale {
+    pub title: String,
+    pub status_disabled: String,
+    pub enrol_button: String,
+    pub enrol_intro: String,
+    pub secret: String,
+    pub code: String,
+    pub confirm_button: String,
+    pub recovery_title: String,
+    pub recovery_intro: String,
+    pub recovery_continue: String,
+    pub disable_intro: String,
+    pub disable_button: String,
+}
+
+impl TwoFactorLocale {
+    pub fn new(l: &Locale) -> Self {
+        Self {
+            title: l.text_with_default("two-factor-title", "Two-Factor Authentication"),
+            status_disabled: l.text_with_default(
+                "two-factor-status-disabled",
+                "Two-factor authentication is not enabled for your account.",
+            ),
+            enrol_button: l.text_with_default("two-factor-enrol-button", "Set Up Two-Factor"),
+            enrol_intro: l.text_with_default(
+                "two-factor-enrol-intro",
+                "Scan the QR code with your authenticator app, then enter the code it shows to finish the setup.",
+            ),
+            secret: l.text_with_default("two-factor-secret", "Or enter this key manually:"),
+            code: l.text_with_default("two-factor-code", "Authentication code"),
+            confirm_button: l.text_with_default("two-factor-confirm-button", "Enable"),
+            recovery_title: l.text_with_default("two-factor-recovery-title", "Recovery Codes"),
+            recovery_intro: l.text_with_default(
+                "two-factor-recovery-intro",
+                "Store these codes somewhere safe. Each one can be used once instead of an authentication code, and they will not be shown again.",
+            ),
+            recovery_continue: l.text_with_default("two-factor-recovery-continue", "Done"),
+            disable_intro: l.text_with_default(
+                "two-factor-disable-intro",
+                "Enter an authentication code or a recovery code to turn two-factor authentication off.",
+            ),
+            disable_button: l.text_with_default("two-factor-disable-button", "Disable"),
+        }
+    }
+}
+
+pub struct TwoFactorFlashLocale {
+    pub success_enabled: String,
+    pub success_disabled: String,
+    pub error_invalid_code: String,
+}
+
+impl TwoFactorFlashLocale {
+    pub fn new(l: &Locale) -> Self {
+        Self {
+            success_enabled: l.text_with_default(
+                "two-factor-flash-success-enabled",
+                "Two-factor authentication enabled",
+            ),
+            success_disabled: l.text_with_default(
+                "two-factor-flash-success-disabled",
+                "Two-factor authentication disabled",
+            ),
+            error_invalid_code: l.text_with_default(
+                "two-factor-flash-error-invalid-code",
+                "Invalid authentication code",
+            ),
+        }
+    }
+}
+
+pub fn two_factor_status_enabled_message(l: &Locale, recovery_codes_left: u32) -> String {
+    l.text_with_default_args(
+        "two-factor-status-enabled",
+        format!("Two-factor authentication is enabled, {recovery_codes_left} recovery codes left.")
+            .as_str(),
+        I18NArgs::from((("count", recovery_codes_left),)),
+    )
+}
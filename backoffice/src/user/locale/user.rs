@@ -6,9 +6,16 @@ pub struct UserLocale {
     pub user_list_head_id: String,
     pub user_list_head_username: String,
     pub user_list_head_role: String,
+    pub user_list_head_status: String,
+    pub user_list_status_locked: String,
+    pub user_list_status_two_factor: String,
     pub user_list_action_edit: String,
     pub user_list_action_password: String,
     pub user_list_action_sign_out: String,
+    pub user_list_action_unlock: String,
+    pub user_list_action_two_factor: String,
+    pub user_list_action_sessions: String,
+    pub user_list_action_reset_two_factor: String,
     pub user_list_action_add_user: String,
 }
 
@@ -20,11 +27,27 @@ impl UserLocale {
             user_list_head_username: l
                 .text_with_default("user-route-list-head-username", "Username"),
             user_list_head_role: l.text_with_default("user-route-list-head-role", "Role"),
+            user_list_head_status: l.text_with_default("user-route-list-head-status", "Status"),
+            user_list_status_locked: l.text_with_default("user-route-list-status-locked", "Locked"),
+            user_list_status_two_factor: l
+                .text_with_default("user-route-list-status-two-factor", "2FA"),
             user_list_action_edit: l.text_with_default("user-route-list-action-edit", "Edit User"),
             user_list_action_password: l
                 .text_with_default("user-route-list-action-password", "Edit Password"),
             user_list_action_sign_out: l
                 .text_with_default("user-route-list-action-sign-out", "Sign Out User"),
+            user_list_action_unlock: l
+                .text_with_default("user-route-list-action-unlock", "Unlock User"),
+            user_list_action_two_factor: l.text_with_default(
+                "user-route-list-action-two-factor",
+                "Two-Factor Authentication",
+            ),
+            user_list_action_sessions: l
+                .text_with_default("user-route-list-action-sessions", "Sessions"),
+            user_list_action_reset_two_factor: l.text_with_default(
+                "user-route-list-action-reset-two-factor",
+                "Reset Two-Factor",
+            ),
             user_list_action_add_user: l
                 .text_with_default("user-route-list-action-add-user", "Add Users"),
         }
@@ -38,3 +61,12 @@ pub fn user_logout_confirm_message(l: &Locale, username: &str) -> String {
         I18NArgs::from((("username", username),)),
     )
 }
+
+pub fn user_reset_two_factor_confirm_message(l: &Locale, username: &str) -> String {
+    l.text_with_default_args(
+        "user-route-reset-two-factor-confirm-message",
+        format!("Are you sure you want to reset two-factor authentication for '{username}'?")
+            .as_str(),
+        I18NArgs::from((("username", username),)),
+    )
+}
@@ -1,4 +1,5 @@
 pub mod form;
+pub mod job;
 pub mod layer;
 pub mod locale;
 pub mod model;
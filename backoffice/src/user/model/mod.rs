@@ -1,2 +1,4 @@
+pub mod session_model;
+pub mod two_factor_model;
 pub mod user_manager_model;
 pub mod user_model;
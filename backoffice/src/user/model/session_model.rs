@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+
+/// An active login token, as shown on the sessions page.
+pub struct LoginSession {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub client_ip: Option<String>,
+    /// The token belongs to the request viewing the list.
+    pub current: bool,
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub struct TwoFactorSecret {
+    pub secret: Box<[u8]>,
+    pub enabled: bool,
+}
+
+pub enum TwoFactorStatus {
+    Disabled,
+    Pending,
+    Enabled { recovery_codes_left: u32 },
+}
+
+pub struct TwoFactorEnrolment {
+    pub secret: String,
+    pub otpauth_url: String,
+}
+
+/// State between a correct password and the second login step, sealed into the session.
+#[derive(Serialize, Deserialize)]
+pub struct TwoFactorPending {
+    pub user_id: i64,
+    pub username: String,
+    pub expires_at: DateTime<Utc>,
+}
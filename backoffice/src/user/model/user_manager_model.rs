@@ -6,6 +6,8 @@ pub struct ListUser {
     pub id: i64,
     pub username: String,
     pub role: Role,
+    pub locked: bool,
+    pub two_factor: bool,
 }
 
 pub struct FetchUser {
@@ -1,8 +1,8 @@
 use crate::user::role::Role;
+use chrono::{DateTime, Utc};
 
 #[derive(Debug)]
 pub struct UserIdContext {
-    #[allow(dead_code)]
     pub id: i64,
     pub username: String,
     pub role: Role,
@@ -11,4 +11,10 @@ pub struct UserIdContext {
 pub struct IdPassword {
     pub id: i64,
     pub password: Box<[u8]>,
+    pub locked: bool,
+}
+
+pub struct LoginAttempt {
+    pub failed_count: u32,
+    pub last_failed_at: DateTime<Utc>,
 }
@@ -1,7 +1,9 @@
 use crate::user::model::user_model::UserIdContext;
+use crate::user::role::Role;
 use crate::user::service::user_check_service::UserCheckService;
 use error_stack::Report;
 use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::log::scope::LogScope;
 use shared::utils::request_cache::RequestCacheExt;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -27,6 +29,9 @@ impl FromContext for UserPointer {
         req.get_or_init_cache(|| async {
             let user_service: UserCheckService = ctx.inject().await?;
             let user_id_context = user_service.get_user_context();
+            if user_id_context.role != Role::Visitor {
+                LogScope::set_user_id(user_id_context.id);
+            }
             Ok(UserPointer(Arc::new(user_id_context)))
         })
         .await
@@ -0,0 +1,4 @@
+delete
+from login_attempts
+where scope = :scope
+  and key = :key
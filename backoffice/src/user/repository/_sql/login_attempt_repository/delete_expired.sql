@@ -0,0 +1,3 @@
+delete
+from login_attempts
+where last_failed_at < :reset_before
@@ -0,0 +1,4 @@
+select failed_count, last_failed_at
+from login_attempts
+where scope = :scope
+  and key = :key
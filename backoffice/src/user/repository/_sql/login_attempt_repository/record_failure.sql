@@ -0,0 +1,8 @@
+insert into login_attempts (scope, key, failed_count, last_failed_at)
+values (:scope, :key, 1, :now)
+on conflict (scope, key) do update set failed_count   = case
+                                                             when last_failed_at < :reset_before then 1
+                                                             else failed_count + 1
+                                                         end,
+                                       last_failed_at = :now
+returning failed_count
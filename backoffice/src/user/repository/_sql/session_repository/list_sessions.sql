@@ -0,0 +1,5 @@
+select id, created_at, last_seen_at, user_agent, client_ip, coalesce(token_hash = :current_token_hash, 0) as current
+from user_login_tokens
+where user_id = :user_id
+  and expire_after > datetime('now')
+order by last_seen_at desc
@@ -0,0 +1,4 @@
+select count(*) as remaining
+from user_recovery_codes
+where user_id = :user_id
+  and used_at is null
@@ -0,0 +1,3 @@
+delete
+from user_two_factor
+where user_id = :user_id
@@ -0,0 +1,4 @@
+update user_two_factor
+set enabled_at = :enabled_at
+where user_id = :user_id
+  and enabled_at is null
@@ -0,0 +1,3 @@
+select secret, enabled_at is not null as enabled
+from user_two_factor
+where user_id = :user_id
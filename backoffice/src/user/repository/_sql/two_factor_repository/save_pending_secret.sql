@@ -0,0 +1,5 @@
+insert into user_two_factor (user_id, secret, enabled_at, last_used_step)
+values (:user_id, :secret, null, null)
+on conflict (user_id) do update set secret         = excluded.secret,
+                                    enabled_at     = null,
+                                    last_used_step = null
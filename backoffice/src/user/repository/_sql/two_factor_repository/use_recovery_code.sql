@@ -0,0 +1,5 @@
+update user_recovery_codes
+set used_at = :used_at
+where user_id = :user_id
+  and code_hash = :code_hash
+  and used_at is null
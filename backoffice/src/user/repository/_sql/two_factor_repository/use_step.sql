@@ -0,0 +1,4 @@
+update user_two_factor
+set last_used_step = :step
+where user_id = :user_id
+  and (last_used_step is null or last_used_step < :step)
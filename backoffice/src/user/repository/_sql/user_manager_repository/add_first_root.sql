@@ -0,0 +1,3 @@
+insert into backoffice_users (username, password, role)
+select :username, :password, 'root'
+where not exists(select 1 from backoffice_users where role = 'root')
@@ -0,0 +1,4 @@
+delete
+from login_attempts
+where scope = 'username'
+  and key = (select username from backoffice_users where id = :id)
@@ -0,0 +1,4 @@
+select id
+from backoffice_users
+where username = :username
+limit 1;
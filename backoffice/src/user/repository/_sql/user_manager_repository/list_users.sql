@@ -1,3 +1,10 @@
-select id, username, role
+select id,
+       username,
+       role,
+       locked_at is not null as locked,
+       exists(select 1
+              from user_two_factor t
+              where t.user_id = backoffice_users.id
+                and t.enabled_at is not null) as two_factor
 from backoffice_users
 order by id
\ No newline at end of file
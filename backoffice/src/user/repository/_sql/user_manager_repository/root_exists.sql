@@ -0,0 +1 @@
+select exists(select 1 from backoffice_users where role = 'root') as root_exists
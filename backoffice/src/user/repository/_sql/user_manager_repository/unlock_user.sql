@@ -0,0 +1,3 @@
+update backoffice_users
+set locked_at = null
+where id = :id
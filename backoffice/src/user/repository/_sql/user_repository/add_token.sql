@@ -1,2 +1,2 @@
-insert into user_login_tokens(user_id, token, expire_after)
-values (:user_id, :token, datetime('now', '+30 day'))
\ No newline at end of file
+insert into user_login_tokens(user_id, token_hash, expire_after, created_at, last_seen_at, user_agent, client_ip)
+values (:user_id, :token_hash, datetime('now', '+30 day'), :now, :now, :user_agent, :client_ip)
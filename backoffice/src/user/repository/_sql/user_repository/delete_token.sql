@@ -1,3 +1,3 @@
 delete
 from user_login_tokens
-where token = :token;
\ No newline at end of file
+where token_hash = :token_hash;
\ No newline at end of file
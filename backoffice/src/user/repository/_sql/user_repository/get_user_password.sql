@@ -1,4 +1,4 @@
-select id, password
+select id, password, locked_at is not null as locked
 from backoffice_users
 where username = :username
 limit 1;
\ No newline at end of file
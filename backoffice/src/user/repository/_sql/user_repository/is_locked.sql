@@ -0,0 +1,4 @@
+select locked_at is not null as locked
+from backoffice_users
+where id = :id
+limit 1;
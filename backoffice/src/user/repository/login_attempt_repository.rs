@@ -0,0 +1,226 @@
+use crate::user::model::user_model::LoginAttempt;
+use chrono::{DateTime, Utc};
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use rusqlite::{Connection, OptionalExtension, named_params};
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::db::{BorrowConnectionExt, SqliteClient};
+use std::sync::MutexGuard;
+use thiserror::Error;
+
+pub const LOGIN_ATTEMPT_SCOPE_USERNAME: &str = "username";
+pub const LOGIN_ATTEMPT_SCOPE_IP: &str = "ip";
+
+#[derive(Debug, Error)]
+pub enum LoginAttemptRepositoryError {
+    #[error("Query error")]
+    QueryError,
+    #[error("Row Value error")]
+    RowValueError,
+    #[error("Borrow Conn error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct LoginAttemptRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl LoginAttemptRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(
+        &'_ self,
+    ) -> Result<MutexGuard<'_, Connection>, Report<LoginAttemptRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(LoginAttemptRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl LoginAttemptRepository {
+    pub fn fetch_attempt(
+        &self,
+        scope: &str,
+        key: String,
+    ) -> Result<Option<LoginAttempt>, Report<LoginAttemptRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!(
+                "_sql/login_attempt_repository/fetch_attempt.sql"
+            ))
+            .change_context(LoginAttemptRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row: Option<LoginAttempt> = stmt
+            .query_one(
+                named_params! {
+                    ":scope": scope,
+                    ":key": key,
+                },
+                |row| {
+                    Ok(LoginAttempt {
+                        failed_count: row.get("failed_count")?,
+                        last_failed_at: row.get("last_failed_at")?,
+                    })
+                },
+            )
+            .optional()
+            .change_context(LoginAttemptRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row)
+    }
+
+    /// Counts one more failure and returns the new total.
+    ///
+    /// The count starts over when the previous failure is older than `reset_before`.
+    pub fn record_failure(
+        &self,
+        scope: &str,
+        key: String,
+        now: DateTime<Utc>,
+        reset_before: DateTime<Utc>,
+    ) -> Result<u32, Report<LoginAttemptRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!(
+                "_sql/login_attempt_repository/record_failure.sql"
+            ))
+            .change_context(LoginAttemptRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        stmt.query_one(
+            named_params! {
+                ":scope": scope,
+                ":key": key,
+                ":now": now,
+                ":reset_before": reset_before,
+            },
+            |row| row.get("failed_count"),
+        )
+        .change_context(LoginAttemptRepositoryError::RowValueError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn clear_attempts(
+        &self,
+        scope: &str,
+        key: String,
+    ) -> Result<(), Report<LoginAttemptRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/login_attempt_repository/clear_attempts.sql"),
+            named_params! {
+                ":scope": scope,
+                ":key": key,
+            },
+        )
+        .change_context(LoginAttemptRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
+    /// Removes every attempt whose last failure is older than `reset_before`, which no
+    /// longer counts, returns how many were deleted.
+    pub fn delete_expired(
+        &self,
+        reset_before: DateTime<Utc>,
+    ) -> Result<usize, Report<LoginAttemptRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/login_attempt_repository/delete_expired.sql"),
+            named_params! {
+                ":reset_before": reset_before,
+            },
+        )
+        .change_context(LoginAttemptRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[cfg(test)]
+impl LoginAttemptRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for LoginAttemptRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::migration::BACKOFFICE_MIGRATIONS;
+    use chrono::TimeDelta;
+    use shared::utils::config::sqlite::SqliteConfig;
+    use shared::utils::db::migration::SHARED_MIGRATIONS;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_delete_expired_keeps_recent_attempts() {
+        let dir = std::env::temp_dir().join(format!("login-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let sqlite_client = SqliteClient::new(&SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+        sqlite_client.migrate(&SHARED_MIGRATIONS).unwrap();
+        sqlite_client.migrate(&BACKOFFICE_MIGRATIONS).unwrap();
+        let repository = LoginAttemptRepository::new(sqlite_client);
+
+        let now = Utc::now();
+        let reset_before = now - TimeDelta::hours(1);
+        let old = now - TimeDelta::hours(2);
+        repository
+            .record_failure(LOGIN_ATTEMPT_SCOPE_USERNAME, "gone".to_string(), old, old)
+            .unwrap();
+        repository
+            .record_failure(LOGIN_ATTEMPT_SCOPE_IP, "10.0.0.1".to_string(), old, old)
+            .unwrap();
+        repository
+            .record_failure(
+                LOGIN_ATTEMPT_SCOPE_USERNAME,
+                "kept".to_string(),
+                now,
+                reset_before,
+            )
+            .unwrap();
+
+        assert_eq!(repository.delete_expired(reset_before).unwrap(), 2);
+        assert!(
+            repository
+                .fetch_attempt(LOGIN_ATTEMPT_SCOPE_USERNAME, "gone".to_string())
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            repository
+                .fetch_attempt(LOGIN_ATTEMPT_SCOPE_USERNAME, "kept".to_string())
+                .unwrap()
+                .unwrap()
+                .failed_count,
+            1
+        );
+        drop(repository);
+        remove_dir_all(&dir).unwrap();
+    }
+}
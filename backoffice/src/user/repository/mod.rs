@@ -1,2 +1,5 @@
+pub mod login_attempt_repository;
+pub mod session_repository;
+pub mod two_factor_repository;
 pub mod user_manager_repository;
 pub mod user_repository;
@@ -0,0 +1,127 @@
+use crate::user::model::session_model::LoginSession;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use rusqlite::{Connection, named_params};
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::db::{BorrowConnectionExt, SqliteClient};
+use std::sync::{Arc, MutexGuard};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SessionRepositoryError {
+    #[error("Query error")]
+    QueryError,
+    #[error("Row Value error")]
+    RowValueError,
+    #[error("Borrow Conn error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct SessionRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl SessionRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(&'_ self) -> Result<MutexGuard<'_, Connection>, Report<SessionRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(SessionRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl SessionRepository {
+    /// Lists the unexpired tokens of a user, most recently active first.
+    pub fn list_sessions(
+        &self,
+        user_id: i64,
+        current_token_hash: Option<String>,
+    ) -> Result<Arc<[LoginSession]>, Report<SessionRepositoryError>> {
+        let conn = self.borrow_conn()?;
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/session_repository/list_sessions.sql"))
+            .change_context(SessionRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        let rows = stmt
+            .query_map(
+                named_params! {
+                    ":user_id": user_id,
+                    ":current_token_hash": current_token_hash,
+                },
+                |row| {
+                    Ok(LoginSession {
+                        id: row.get("id")?,
+                        created_at: row.get("created_at")?,
+                        last_seen_at: row.get("last_seen_at")?,
+                        user_agent: row.get("user_agent")?,
+                        client_ip: row.get("client_ip")?,
+                        current: row.get("current")?,
+                    })
+                },
+            )
+            .change_context(SessionRepositoryError::RowValueError)?;
+
+        let sessions = rows
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(SessionRepositoryError::RowValueError)?;
+
+        Ok(sessions.into())
+    }
+
+    /// Deletes one token of a user, returns false when it did not exist.
+    pub fn revoke_session(
+        &self,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<bool, Report<SessionRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let deleted = conn
+            .execute(
+                include_str!("_sql/session_repository/revoke_session.sql"),
+                named_params! {
+                    ":id": session_id,
+                    ":user_id": user_id,
+                },
+            )
+            .change_context(SessionRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Removes every expired token, returns how many were deleted.
+    pub fn delete_expired(&self) -> Result<usize, Report<SessionRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/session_repository/delete_expired.sql"),
+            named_params! {},
+        )
+        .change_context(SessionRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[cfg(test)]
+impl SessionRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for SessionRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
@@ -0,0 +1,267 @@
+use crate::user::model::two_factor_model::TwoFactorSecret;
+use chrono::Utc;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use rusqlite::{Connection, OptionalExtension, named_params};
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::db::{BorrowConnectionExt, SqliteClient};
+use std::sync::MutexGuard;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TwoFactorRepositoryError {
+    #[error("Query error")]
+    QueryError,
+    #[error("Row Value error")]
+    RowValueError,
+    #[error("Borrow Conn error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct TwoFactorRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl TwoFactorRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(
+        &'_ self,
+    ) -> Result<MutexGuard<'_, Connection>, Report<TwoFactorRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(TwoFactorRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl TwoFactorRepository {
+    pub fn fetch_two_factor(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<TwoFactorSecret>, Report<TwoFactorRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!(
+                "_sql/two_factor_repository/fetch_two_factor.sql"
+            ))
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row: Option<TwoFactorSecret> = stmt
+            .query_one(
+                named_params! {
+                    ":user_id": user_id,
+                },
+                |row| {
+                    Ok(TwoFactorSecret {
+                        secret: row.get("secret")?,
+                        enabled: row.get("enabled")?,
+                    })
+                },
+            )
+            .optional()
+            .change_context(TwoFactorRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row)
+    }
+
+    /// Stores a new secret awaiting confirmation, replacing any previous enrolment.
+    pub fn save_pending_secret(
+        &self,
+        user_id: i64,
+        secret: Box<[u8]>,
+    ) -> Result<(), Report<TwoFactorRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/two_factor_repository/save_pending_secret.sql"),
+            named_params! {
+                ":user_id": user_id,
+                ":secret": secret,
+            },
+        )
+        .change_context(TwoFactorRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
+    /// Activates a pending enrolment and replaces the recovery codes.
+    ///
+    /// Returns `false` when there was no pending enrolment to activate.
+    pub fn enable_two_factor(
+        &self,
+        user_id: i64,
+        step: i64,
+        code_hashes: Vec<String>,
+    ) -> Result<bool, Report<TwoFactorRepositoryError>> {
+        let mut conn = self.borrow_conn()?;
+        let tx = conn
+            .transaction()
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let enabled = tx
+            .execute(
+                include_str!("_sql/two_factor_repository/enable_two_factor.sql"),
+                named_params! {
+                    ":user_id": user_id,
+                    ":enabled_at": Utc::now(),
+                },
+            )
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        if enabled == 0 {
+            return Ok(false);
+        }
+
+        tx.execute(
+            include_str!("_sql/two_factor_repository/use_step.sql"),
+            named_params! {
+                ":user_id": user_id,
+                ":step": step,
+            },
+        )
+        .change_context(TwoFactorRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            include_str!("_sql/two_factor_repository/delete_recovery_codes.sql"),
+            named_params! {
+                ":user_id": user_id,
+            },
+        )
+        .change_context(TwoFactorRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        for code_hash in code_hashes {
+            tx.execute(
+                include_str!("_sql/two_factor_repository/add_recovery_code.sql"),
+                named_params! {
+                    ":user_id": user_id,
+                    ":code_hash": code_hash,
+                },
+            )
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        }
+
+        tx.commit()
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(true)
+    }
+
+    /// Records `step` as the latest accepted time step.
+    ///
+    /// Returns `false` when that step or a later one was already used, so a code can
+    /// not be replayed.
+    pub fn use_step(
+        &self,
+        user_id: i64,
+        step: i64,
+    ) -> Result<bool, Report<TwoFactorRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let updated = conn
+            .execute(
+                include_str!("_sql/two_factor_repository/use_step.sql"),
+                named_params! {
+                    ":user_id": user_id,
+                    ":step": step,
+                },
+            )
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(updated > 0)
+    }
+
+    pub fn use_recovery_code(
+        &self,
+        user_id: i64,
+        code_hash: String,
+    ) -> Result<bool, Report<TwoFactorRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let updated = conn
+            .execute(
+                include_str!("_sql/two_factor_repository/use_recovery_code.sql"),
+                named_params! {
+                    ":user_id": user_id,
+                    ":code_hash": code_hash,
+                    ":used_at": Utc::now(),
+                },
+            )
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(updated > 0)
+    }
+
+    pub fn count_recovery_codes(
+        &self,
+        user_id: i64,
+    ) -> Result<u32, Report<TwoFactorRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.query_one(
+            include_str!("_sql/two_factor_repository/count_recovery_codes.sql"),
+            named_params! {
+                ":user_id": user_id,
+            },
+            |row| row.get("remaining"),
+        )
+        .change_context(TwoFactorRepositoryError::RowValueError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn delete_two_factor(&self, user_id: i64) -> Result<(), Report<TwoFactorRepositoryError>> {
+        let mut conn = self.borrow_conn()?;
+        let tx = conn
+            .transaction()
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.execute(
+            include_str!("_sql/two_factor_repository/delete_recovery_codes.sql"),
+            named_params! {
+                ":user_id": user_id,
+            },
+        )
+        .change_context(TwoFactorRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            include_str!("_sql/two_factor_repository/delete_two_factor.sql"),
+            named_params! {
+                ":user_id": user_id,
+            },
+        )
+        .change_context(TwoFactorRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.commit()
+            .change_context(TwoFactorRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl TwoFactorRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for TwoFactorRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
@@ -63,6 +63,29 @@ impl UserManagerRepository {
         Ok(())
     }
 
+    /// Adds the first root user, in the same statement that checks there is none yet.
+    ///
+    /// Returns false, adding nothing, when a root user already exists.
+    pub fn add_first_root(
+        &self,
+        username: String,
+        password: Box<[u8]>,
+    ) -> Result<bool, Report<UserManagerRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let added = conn
+            .execute(
+                include_str!("_sql/user_manager_repository/add_first_root.sql"),
+                named_params! {
+                    ":username": username,
+                    ":password": password,
+                },
+            )
+            .change_context(UserManagerRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(added > 0)
+    }
+
     pub fn edit_password(
         &self,
         id: i64,
@@ -143,6 +166,8 @@ impl UserManagerRepository {
                     username: row.get("username")?,
                     role: Role::try_from(row.get::<_, String>("role")?.as_str())
                         .unwrap_or_default(),
+                    locked: row.get("locked")?,
+                    two_factor: row.get("two_factor")?,
                 })
             })
             .change_context(UserManagerRepositoryError::RowValueError)?;
@@ -171,6 +196,37 @@ impl UserManagerRepository {
         Ok(())
     }
 
+    /// Lifts a lockout and forgets the failed attempts that caused it.
+    pub fn unlock_user(&self, user_id: i64) -> Result<(), Report<UserManagerRepositoryError>> {
+        let mut conn = self.borrow_conn()?;
+        let tx = conn
+            .transaction()
+            .change_context(UserManagerRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.execute(
+            include_str!("_sql/user_manager_repository/unlock_user.sql"),
+            named_params! {
+                ":id": user_id,
+            },
+        )
+        .change_context(UserManagerRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            include_str!("_sql/user_manager_repository/clear_user_attempts.sql"),
+            named_params! {
+                ":id": user_id,
+            },
+        )
+        .change_context(UserManagerRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.commit()
+            .change_context(UserManagerRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
     pub fn username_taken(
         &self,
         username: String,
@@ -198,6 +254,49 @@ impl UserManagerRepository {
         Ok(row.unwrap_or_default())
     }
 
+    pub fn root_exists(&self) -> Result<bool, Report<UserManagerRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/user_manager_repository/root_exists.sql"))
+            .change_context(UserManagerRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let root_exists: bool = stmt
+            .query_one(named_params! {}, |row| row.get("root_exists"))
+            .change_context(UserManagerRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(root_exists)
+    }
+
+    pub fn find_user_id(
+        &self,
+        username: String,
+    ) -> Result<Option<i64>, Report<UserManagerRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!(
+                "_sql/user_manager_repository/find_user_id.sql"
+            ))
+            .change_context(UserManagerRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row: Option<i64> = stmt
+            .query_one(
+                named_params! {
+                    ":username": username
+                },
+                |row| row.get("id"),
+            )
+            .optional()
+            .change_context(UserManagerRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row)
+    }
+
     #[allow(dead_code)]
     pub fn fetch_password(
         &self,
@@ -244,3 +343,43 @@ impl FromContext for UserManagerRepository {
         Ok(Self::new(ctx.inject().await?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::migration::BACKOFFICE_MIGRATIONS;
+    use shared::utils::config::sqlite::SqliteConfig;
+    use shared::utils::db::migration::SHARED_MIGRATIONS;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_add_first_root_only_once() {
+        let dir = std::env::temp_dir().join(format!("setup-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let sqlite_client = SqliteClient::new(&SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+        sqlite_client.migrate(&SHARED_MIGRATIONS).unwrap();
+        sqlite_client.migrate(&BACKOFFICE_MIGRATIONS).unwrap();
+        let repository = UserManagerRepository::new(sqlite_client);
+
+        assert!(!repository.root_exists().unwrap());
+        assert!(
+            repository
+                .add_first_root("first".to_string(), Box::new([1]))
+                .unwrap()
+        );
+        assert!(
+            !repository
+                .add_first_root("second".to_string(), Box::new([2]))
+                .unwrap()
+        );
+        assert!(repository.root_exists().unwrap());
+        assert!(!repository.username_taken("second".to_string()).unwrap());
+        drop(repository);
+        remove_dir_all(&dir).unwrap();
+    }
+}
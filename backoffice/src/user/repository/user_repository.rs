@@ -1,5 +1,6 @@
 use crate::user::model::user_model::{IdPassword, UserIdContext};
 use crate::user::role::Role;
+use chrono::{TimeDelta, Utc};
 use error_stack::{Report, ResultExt};
 use poem::http::StatusCode;
 use rusqlite::{Connection, OptionalExtension, named_params};
@@ -8,6 +9,8 @@ use shared::utils::db::{BorrowConnectionExt, SqliteClient};
 use std::sync::MutexGuard;
 use thiserror::Error;
 
+const TOKEN_TOUCH_INTERVAL: TimeDelta = TimeDelta::minutes(1);
+
 #[derive(Debug, Error)]
 pub enum UserRepositoryError {
     #[error("Query error")]
@@ -44,16 +47,21 @@ impl UserRepository {
 impl UserRepository {
     pub fn add_token(
         &self,
-        token: String,
+        token_hash: String,
         user_id: i64,
+        user_agent: Option<String>,
+        client_ip: Option<String>,
     ) -> Result<(), Report<UserRepositoryError>> {
         let conn = self.borrow_conn()?;
 
         conn.execute(
             include_str!("_sql/user_repository/add_token.sql"),
             named_params! {
-                ":token": token,
+                ":token_hash": token_hash,
                 ":user_id": user_id,
+                ":now": Utc::now(),
+                ":user_agent": user_agent,
+                ":client_ip": client_ip,
             },
         )
         .change_context(UserRepositoryError::QueryError)
@@ -62,13 +70,32 @@ impl UserRepository {
         Ok(())
     }
 
-    pub fn delete_token(&self, token: String) -> Result<(), Report<UserRepositoryError>> {
+    pub fn delete_token(&self, token_hash: String) -> Result<(), Report<UserRepositoryError>> {
         let conn = self.borrow_conn()?;
 
         conn.execute(
             include_str!("_sql/user_repository/delete_token.sql"),
             named_params! {
-                ":token": token,
+                ":token_hash": token_hash,
+            },
+        )
+        .change_context(UserRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(())
+    }
+
+    /// Records activity on a token, skipping the write when it was seen within `TOKEN_TOUCH_INTERVAL`.
+    pub fn touch_token(&self, token_hash: String) -> Result<(), Report<UserRepositoryError>> {
+        let conn = self.borrow_conn()?;
+        let now = Utc::now();
+
+        conn.execute(
+            include_str!("_sql/user_repository/touch_token.sql"),
+            named_params! {
+                ":token_hash": token_hash,
+                ":now": now,
+                ":stale_before": now - TOKEN_TOUCH_INTERVAL,
             },
         )
         .change_context(UserRepositoryError::QueryError)
@@ -79,7 +106,7 @@ impl UserRepository {
 
     pub fn find_by_token(
         &self,
-        token: String,
+        token_hash: String,
     ) -> Result<UserIdContext, Report<UserRepositoryError>> {
         let conn = self.borrow_conn()?;
 
@@ -91,7 +118,7 @@ impl UserRepository {
         let row: Option<UserIdContext> = stmt
             .query_one(
                 named_params! {
-                    ":token": token,
+                    ":token_hash": token_hash,
                 },
                 |row| {
                     Ok(UserIdContext {
@@ -134,6 +161,7 @@ impl UserRepository {
                     Ok(IdPassword {
                         id: row.get("id")?,
                         password: row.get("password")?,
+                        locked: row.get("locked")?,
                     })
                 },
             )
@@ -148,6 +176,59 @@ impl UserRepository {
             }
         }
     }
+
+    pub fn is_locked(&self, user_id: i64) -> Result<bool, Report<UserRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/user_repository/is_locked.sql"))
+            .change_context(UserRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let locked: Option<bool> = stmt
+            .query_one(named_params! { ":id": user_id }, |row| row.get("locked"))
+            .optional()
+            .change_context(UserRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        locked.ok_or_else(|| {
+            Report::new(UserRepositoryError::NotFoundError).attach(StatusCode::NOT_FOUND)
+        })
+    }
+
+    pub fn update_password(
+        &self,
+        user_id: i64,
+        password: Box<[u8]>,
+    ) -> Result<(), Report<UserRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/user_repository/update_password.sql"),
+            named_params! {
+                ":id": user_id,
+                ":password": password,
+            },
+        )
+        .change_context(UserRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
+
+    pub fn lock_user(&self, user_id: i64) -> Result<(), Report<UserRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/user_repository/lock_user.sql"),
+            named_params! {
+                ":id": user_id,
+                ":locked_at": Utc::now(),
+            },
+        )
+        .change_context(UserRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
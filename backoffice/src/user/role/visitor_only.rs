@@ -1,9 +1,11 @@
+use crate::user::LOGIN_TOKEN_COOKIE_NAME;
 use crate::user::pointer::user_pointer::UserPointer;
 use crate::user::role::Role;
 use crate::user::route::login::LOGIN_ROUTE;
 use poem::http::StatusCode;
+use poem::session::Session;
 use poem::web::Redirect;
-use poem::{Endpoint, Error, FromRequest, IntoEndpoint, IntoResponse, Request};
+use poem::{Endpoint, Error, FromRequest, IntoEndpoint, IntoResponse, Request, Response};
 use shared::utils::context::Dep;
 
 struct VisitorOnly<E: Endpoint>(E);
@@ -33,16 +35,23 @@ where
 struct VisitorRedirect<E: Endpoint>(E);
 
 impl<E: Endpoint> Endpoint for VisitorRedirect<E> {
-    type Output = E::Output;
+    type Output = Response;
 
     async fn call(&self, req: Request) -> poem::Result<Self::Output> {
         let Dep(user_context) = Dep::<UserPointer>::from_request_without_body(&req).await?;
         if user_context.role == Role::Visitor {
-            return Err(Error::from_response(
-                Redirect::see_other(LOGIN_ROUTE).into_response(),
-            ));
+            // The login cookie no longer signs in, the token was revoked or has expired.
+            // Whatever the session still holds for that login goes with it. The redirect
+            // is a response, not an error, so the session and cookie layers still apply.
+            if req.cookie().get(LOGIN_TOKEN_COOKIE_NAME).is_some() {
+                if let Some(session) = req.extensions().get::<Session>() {
+                    session.purge();
+                }
+                req.cookie().remove(LOGIN_TOKEN_COOKIE_NAME);
+            }
+            return Ok(Redirect::see_other(LOGIN_ROUTE).into_response());
         }
-        self.0.call(req).await
+        self.0.call(req).await.map(IntoResponse::into_response)
     }
 }
 
@@ -1,10 +1,13 @@
 use crate::common::html::context_html::ContextHtmlBuilder;
 use crate::user::LOGIN_TOKEN_COOKIE_NAME;
 use crate::user::form::login::{UserLoginForm, UserLoginFormResult};
-use crate::user::locale::login::{LoginLocale, LoginPostLocale, LogoutLocale};
+use crate::user::form::two_factor::TwoFactorCodeForm;
+use crate::user::locale::login::{
+    LoginLocale, LoginPostLocale, LoginTwoFactorLocale, LogoutLocale, login_throttled_message,
+};
 use crate::user::role::user_role_check::must_be_user;
 use crate::user::role::visitor_only::visitor_only;
-use crate::user::service::user_login_service::UserLoginService;
+use crate::user::service::user_login_service::{LoginFailure, LoginSuccess, UserLoginService};
 use chrono::TimeDelta;
 use maud::{Markup, html};
 use poem::i18n::Locale;
@@ -19,6 +22,19 @@ use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml};
 use shared::utils::flash::{Flash, FlashMessageExt};
 
 pub const LOGIN_ROUTE: &str = "/user-login";
+const TWO_FACTOR_PENDING_SESSION_KEY: &str = "two_factor_pending";
+
+fn add_login_cookie(cookie_jar: &CookieJar, token: String) {
+    let new_cookie = Cookie::new_with_str(LOGIN_TOKEN_COOKIE_NAME, token)
+        .into_builder()
+        .path("/")
+        .expires_by_delta(TimeDelta::days(30))
+        .secure()
+        .http_only()
+        .build();
+
+    cookie_jar.add(new_cookie);
+}
 
 #[handler]
 async fn login(
@@ -62,31 +78,44 @@ async fn login_post(
 ) -> LoginPostResponse {
     unified(async {
         let login_post_locale = LoginPostLocale::new(&locale);
+        let mut flash_failed = login_post_locale.flash_failed;
         if let UserLoginFormResult(Ok(user_login_form_validated)) = user_login_form.as_validated() {
             let token = user_login_service.validate_login(
                 user_login_form_validated.username.as_str().to_string(),
                 user_login_form_validated.password.as_str().to_string(),
             );
-            if let Some(token) = token {
-                let new_cookie = Cookie::new_with_str(LOGIN_TOKEN_COOKIE_NAME, token)
-                    .into_builder()
-                    .path("/")
-                    .expires_by_delta(TimeDelta::days(30))
-                    .secure()
-                    .http_only()
-                    .build();
-
-                cookie_jar.add(new_cookie);
-                session.flash(Flash::Success {
-                    msg: login_post_locale.flash_success,
-                });
-                return Ok(LoginPostResponse::Redirect(Redirect::see_other("/")));
+            match token {
+                Ok(LoginSuccess::Token(token)) => {
+                    // A new session id once signed in, so one planted before can't follow.
+                    session.renew();
+                    add_login_cookie(cookie_jar, token);
+                    session.flash(Flash::Success {
+                        msg: login_post_locale.flash_success,
+                    });
+                    return Ok(LoginPostResponse::Redirect(Redirect::see_other("/")));
+                }
+                Ok(LoginSuccess::TwoFactorRequired(pending)) => {
+                    session.renew();
+                    session.set(TWO_FACTOR_PENDING_SESSION_KEY, pending);
+                    return Ok(LoginPostResponse::Redirect(Redirect::see_other(
+                        LOGIN_ROUTE.to_owned() + "/two-factor",
+                    )));
+                }
+                Err(LoginFailure::Throttled { retry_after_secs }) => {
+                    flash_failed = login_throttled_message(&locale, retry_after_secs);
+                }
+                Err(LoginFailure::Locked) => {
+                    flash_failed = login_post_locale.flash_locked;
+                }
+                Err(
+                    LoginFailure::InvalidCredentials
+                    | LoginFailure::Expired
+                    | LoginFailure::Unavailable,
+                ) => {}
             }
         }
 
-        session.flash(Flash::Error {
-            msg: login_post_locale.flash_failed,
-        });
+        session.flash(Flash::Error { msg: flash_failed });
         Err(LoginPostResponse::Redirect(Redirect::see_other(
             LOGIN_ROUTE.to_owned() + "/",
         )))
@@ -94,6 +123,78 @@ async fn login_post(
     .await
 }
 
+#[handler]
+async fn login_two_factor(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    session: &Session,
+    csrf_token: &CsrfToken,
+) -> Response {
+    if session
+        .get::<Box<[u8]>>(TWO_FACTOR_PENDING_SESSION_KEY)
+        .is_none()
+    {
+        return Redirect::see_other(LOGIN_ROUTE.to_owned() + "/").into_response();
+    }
+
+    let lc = LoginTwoFactorLocale::new(&context_html_builder.locale);
+    context_html_builder
+        .attach_title(&lc.title)
+        .attach_content(html! {
+            h1 .mt-3 { (lc.title) }
+            p { (lc.intro) }
+            form method="post" .form {
+                (csrf_token.as_html_input())
+                input .form-item type="text" name="code" placeholder=(lc.code)
+                    autocomplete="one-time-code" autofocus {}
+                button .btn .btn-sky-blue .mt-3 type="submit" { (lc.confirm_button) }
+            }
+        })
+        .build()
+        .into_response()
+}
+
+#[handler]
+async fn login_two_factor_post(
+    Dep(user_login_service): Dep<UserLoginService>,
+    CsrfFormQs(two_factor_form): CsrfFormQs<TwoFactorCodeForm>,
+    session: &Session,
+    cookie_jar: &CookieJar,
+    locale: Locale,
+) -> Redirect {
+    let Some(pending) = session.get::<Box<[u8]>>(TWO_FACTOR_PENDING_SESSION_KEY) else {
+        return Redirect::see_other(LOGIN_ROUTE.to_owned() + "/");
+    };
+
+    let login_post_locale = LoginPostLocale::new(&locale);
+    let (msg, restart_login) =
+        match user_login_service.validate_two_factor(pending, two_factor_form.code) {
+            Ok(token) => {
+                session.remove(TWO_FACTOR_PENDING_SESSION_KEY);
+                session.renew();
+                add_login_cookie(cookie_jar, token);
+                session.flash(Flash::Success {
+                    msg: login_post_locale.flash_success,
+                });
+                return Redirect::see_other("/");
+            }
+            Err(LoginFailure::Throttled { retry_after_secs }) => {
+                (login_throttled_message(&locale, retry_after_secs), false)
+            }
+            Err(LoginFailure::Locked) => (login_post_locale.flash_locked, true),
+            Err(LoginFailure::Expired) => (login_post_locale.flash_two_factor_expired, true),
+            Err(LoginFailure::InvalidCredentials | LoginFailure::Unavailable) => {
+                (login_post_locale.flash_two_factor_invalid, false)
+            }
+        };
+
+    session.flash(Flash::Error { msg });
+    if restart_login {
+        session.remove(TWO_FACTOR_PENDING_SESSION_KEY);
+        return Redirect::see_other(LOGIN_ROUTE.to_owned() + "/");
+    }
+    Redirect::see_other(LOGIN_ROUTE.to_owned() + "/two-factor")
+}
+
 #[handler]
 async fn logout(
     Dep(user_login_service): Dep<UserLoginService>,
@@ -103,6 +204,10 @@ async fn logout(
 ) -> Redirect {
     user_login_service.logout();
     cookie_jar.remove(LOGIN_TOKEN_COOKIE_NAME);
+    // Drops the stored session and its id like `purge` does, but leaves a fresh session
+    // for the flash below.
+    session.clear();
+    session.renew();
     let logout_locale = LogoutLocale::new(&locale);
     session.flash(Flash::Success {
         msg: logout_locale.flash_success,
@@ -113,5 +218,9 @@ async fn logout(
 pub fn login_route() -> Route {
     Route::new()
         .at("/", visitor_only(get(login).post(login_post)))
+        .at(
+            "/two-factor",
+            visitor_only(get(login_two_factor).post(login_two_factor_post)),
+        )
         .at("/logout", must_be_user(get(logout)))
 }
@@ -1,2 +1,4 @@
 pub mod login;
+pub mod session;
+pub mod two_factor;
 pub mod user;
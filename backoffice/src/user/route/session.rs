@@ -0,0 +1,196 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::common::icon::trash_icon;
+use crate::user::locale::session::{SessionFlashLocale, SessionLocale, session_title_for_user};
+use crate::user::model::session_model::LoginSession;
+use crate::user::pointer::user_pointer::UserPointer;
+use crate::user::role::user_role_check::must_be_root;
+use crate::user::route::user::USER_ROUTE;
+use crate::user::service::session_service::SessionService;
+use maud::{Markup, html};
+use poem::i18n::Locale;
+use poem::session::Session;
+use poem::web::{CsrfToken, Path, Redirect};
+use poem::{Error, Response, Route, delete, get, handler};
+use shared::utils::context::Dep;
+use shared::utils::csrf::{CsrfTokenHtml, csrf_header_check_strict};
+use shared::utils::error::{ExtraResultExt, FromErrorStack};
+use shared::utils::flash::{Flash, FlashMessageExt};
+use shared::utils::htmx::HtmxHeader;
+
+fn sessions_url(path: &str) -> String {
+    format!("{}/sessions{}", USER_ROUTE, path)
+}
+
+fn user_sessions_url(user_id: i64, path: &str) -> String {
+    sessions_url(&format!("/user/{}{}", user_id, path))
+}
+
+fn sessions_html(
+    context_html_builder: &ContextHtmlBuilder,
+    title: &str,
+    sessions: &[LoginSession],
+    revoke_url: impl Fn(i64) -> String,
+    csrf_token: &CsrfToken,
+) -> Markup {
+    let lc = SessionLocale::new(&context_html_builder.locale);
+    let revoke_icon = trash_icon();
+
+    context_html_builder
+        .attach_title(title)
+        .set_current_tag("id-tag-user")
+        .attach_content(html! {
+            h1 { (title) }
+            @if sessions.is_empty() {
+                p { (lc.empty) }
+            } @else {
+                table .table-full {
+                    thead {
+                        tr {
+                            th { (lc.head_created_at) }
+                            th { (lc.head_last_seen_at) }
+                            th { (lc.head_user_agent) }
+                            th { (lc.head_client_ip) }
+                            th .action { (lc.head_action) }
+                        }
+                    }
+                    tbody {
+                        @for session in sessions {
+                            tr {
+                                td x-init="$store.util.formatToLocalTime($el)" { (session.created_at.to_rfc3339()) }
+                                td x-init="$store.util.formatToLocalTime($el)" { (session.last_seen_at.to_rfc3339()) }
+                                td { (session.user_agent.as_deref().unwrap_or(&lc.unknown)) }
+                                td { (session.client_ip.as_deref().unwrap_or(&lc.unknown)) }
+                                td .action {
+                                    @if session.current {
+                                        (lc.current)
+                                    } @else {
+                                        a .icon hx-confirm=(lc.revoke_confirm)
+                                            href=(revoke_url(session.id)) title=(lc.action_revoke)
+                                            hx-delete=(revoke_url(session.id)) { (revoke_icon) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .attach_footer(html! {
+            (csrf_token.as_html_command())
+        })
+        .build()
+}
+
+#[handler]
+fn own_sessions(
+    Dep(session_service): Dep<SessionService>,
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(user_pointer): Dep<UserPointer>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    let sessions = session_service
+        .list_sessions(user_pointer.id)
+        .log_it()
+        .map_err(Error::from_error_stack)?;
+    let title = SessionLocale::new(&context_html_builder.locale).title;
+
+    Ok(sessions_html(
+        &context_html_builder,
+        &title,
+        &sessions,
+        |session_id| sessions_url(&format!("/revoke/{}", session_id)),
+        csrf_token,
+    ))
+}
+
+#[handler]
+fn user_sessions(
+    Dep(session_service): Dep<SessionService>,
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Path(user_id): Path<i64>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    let username = session_service
+        .fetch_username(user_id)
+        .map_err(Error::from_error_stack)?;
+    let sessions = session_service
+        .list_sessions(user_id)
+        .log_it()
+        .map_err(Error::from_error_stack)?;
+    let title = session_title_for_user(&context_html_builder.locale, &username);
+
+    Ok(sessions_html(
+        &context_html_builder,
+        &title,
+        &sessions,
+        |session_id| user_sessions_url(user_id, &format!("/revoke/{}", session_id)),
+        csrf_token,
+    ))
+}
+
+fn revoked_response(
+    session: &Session,
+    locale: &Locale,
+    htmx_header: HtmxHeader,
+    back_url: String,
+) -> Response {
+    session.flash(Flash::Success {
+        msg: SessionFlashLocale::new(locale).success_revoked,
+    });
+    htmx_header.do_location(Redirect::see_other(back_url), "#main-content")
+}
+
+#[handler]
+fn revoke_own_session(
+    Dep(session_service): Dep<SessionService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    Path(session_id): Path<i64>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> poem::Result<Response> {
+    session_service
+        .revoke_session(user_pointer.id, session_id)
+        .map_err(Error::from_error_stack)?;
+
+    Ok(revoked_response(
+        session,
+        &locale,
+        htmx_header,
+        sessions_url("/"),
+    ))
+}
+
+#[handler]
+fn revoke_user_session(
+    Dep(session_service): Dep<SessionService>,
+    Path((user_id, session_id)): Path<(i64, i64)>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> poem::Result<Response> {
+    session_service
+        .revoke_session(user_id, session_id)
+        .map_err(Error::from_error_stack)?;
+
+    Ok(revoked_response(
+        session,
+        &locale,
+        htmx_header,
+        user_sessions_url(user_id, ""),
+    ))
+}
+
+pub fn session_route() -> Route {
+    Route::new()
+        .at("/", get(own_sessions))
+        .at(
+            "/revoke/:session_id",
+            delete(csrf_header_check_strict(revoke_own_session)),
+        )
+        .at("/user/:user_id", must_be_root(get(user_sessions)))
+        .at(
+            "/user/:user_id/revoke/:session_id",
+            must_be_root(delete(csrf_header_check_strict(revoke_user_session))),
+        )
+}
@@ -0,0 +1,231 @@
+use crate::common::html::context_html::ContextHtmlBuilder;
+use crate::common::html::qr_code::qr_code_svg;
+use crate::user::form::two_factor::{TwoFactorCodeForm, TwoFactorEnrolForm};
+use crate::user::locale::two_factor::{
+    TwoFactorFlashLocale, TwoFactorLocale, two_factor_status_enabled_message,
+};
+use crate::user::model::two_factor_model::{TwoFactorEnrolment, TwoFactorStatus};
+use crate::user::pointer::user_pointer::UserPointer;
+use crate::user::route::user::USER_ROUTE;
+use crate::user::service::two_factor_service::TwoFactorService;
+use maud::{Markup, html};
+use poem::http::StatusCode;
+use poem::i18n::Locale;
+use poem::session::Session;
+use poem::web::{CsrfToken, Redirect};
+use poem::{Error, IntoResponse, Response, Route, get, handler, post};
+use shared::utils::context::Dep;
+use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml, csrf_header_check};
+use shared::utils::error::{ExtraResultExt, FromErrorStack};
+use shared::utils::flash::{Flash, FlashMessageExt};
+use shared::utils::htmx::HtmxHeader;
+
+fn two_factor_url(path: &str) -> String {
+    format!("{}/two-factor{}", USER_ROUTE, path)
+}
+
+fn enrolment_html(
+    context_html_builder: &ContextHtmlBuilder,
+    enrolment: &TwoFactorEnrolment,
+    csrf_token: &CsrfToken,
+) -> Markup {
+    let lc = TwoFactorLocale::new(&context_html_builder.locale);
+    context_html_builder
+        .attach_title(&lc.title)
+        .set_current_tag("id-tag-user")
+        .attach_content(html! {
+            h1 .mt-3 { (lc.title) }
+            p { (lc.enrol_intro) }
+            div .mt-3 {
+                @if let Some(qr_code) = qr_code_svg(&enrolment.otpauth_url) {
+                    (qr_code)
+                }
+            }
+            p .mt-3 { (lc.secret) " " code { (enrolment.secret) } }
+            form hx-boost="true" hx-target="#main-content" .form method="post"
+                action=(two_factor_url("/confirm")) {
+                (csrf_token.as_html_input())
+                div .form-group {
+                    label .label for="code" { (lc.code) }
+                    input .form-item .w-full type="text" name="code" #code
+                        autocomplete="one-time-code" inputmode="numeric" {}
+                }
+                div .form-group {
+                    input .btn .btn-sky-blue type="submit" value=(lc.confirm_button) {}
+                }
+            }
+        })
+        .build()
+}
+
+#[handler]
+async fn two_factor_get(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(two_factor_service): Dep<TwoFactorService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    let status = two_factor_service
+        .status(user_pointer.id)
+        .log_it()
+        .map_err(Error::from_error_stack)?;
+    if let TwoFactorStatus::Pending = status {
+        let enrolment = two_factor_service
+            .pending_enrolment(user_pointer.id, &user_pointer.username)
+            .log_it()
+            .map_err(Error::from_error_stack)?;
+        return Ok(enrolment_html(
+            &context_html_builder,
+            &enrolment,
+            csrf_token,
+        ));
+    }
+
+    let lc = TwoFactorLocale::new(&context_html_builder.locale);
+    Ok(context_html_builder
+        .attach_title(&lc.title)
+        .set_current_tag("id-tag-user")
+        .attach_content(html! {
+            h1 .mt-3 { (lc.title) }
+            @if let TwoFactorStatus::Enabled { recovery_codes_left } = status {
+                p { (two_factor_status_enabled_message(&context_html_builder.locale, recovery_codes_left)) }
+                p .mt-3 { (lc.disable_intro) }
+                form hx-boost="true" hx-target="#main-content" .form method="post"
+                    action=(two_factor_url("/disable")) {
+                    (csrf_token.as_html_input())
+                    div .form-group {
+                        label .label for="code" { (lc.code) }
+                        input .form-item .w-full type="text" name="code" #code
+                            autocomplete="one-time-code" {}
+                    }
+                    div .form-group {
+                        input .btn .btn-sky-blue type="submit" value=(lc.disable_button) {}
+                    }
+                }
+            } @else {
+                p { (lc.status_disabled) }
+                form hx-boost="true" hx-target="#main-content" .form method="post"
+                    action=(two_factor_url("/enrol")) {
+                    (csrf_token.as_html_input())
+                    div .form-group {
+                        input .btn .btn-sky-blue type="submit" value=(lc.enrol_button) {}
+                    }
+                }
+            }
+        })
+        .build())
+}
+
+#[handler]
+async fn two_factor_enrol_post(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(two_factor_service): Dep<TwoFactorService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    CsrfFormQs(_): CsrfFormQs<TwoFactorEnrolForm>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Markup> {
+    if two_factor_service
+        .is_enabled(user_pointer.id)
+        .log_it()
+        .map_err(Error::from_error_stack)?
+    {
+        return Err(Error::from_status(StatusCode::CONFLICT));
+    }
+    let enrolment = two_factor_service
+        .begin_enrolment(user_pointer.id, &user_pointer.username)
+        .log_it()
+        .map_err(Error::from_error_stack)?;
+    Ok(enrolment_html(
+        &context_html_builder,
+        &enrolment,
+        csrf_token,
+    ))
+}
+
+#[handler]
+async fn two_factor_confirm_post(
+    Dep(context_html_builder): Dep<ContextHtmlBuilder>,
+    Dep(two_factor_service): Dep<TwoFactorService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    CsrfFormQs(two_factor_form): CsrfFormQs<TwoFactorCodeForm>,
+    csrf_token: &CsrfToken,
+) -> poem::Result<Response> {
+    let recovery_codes = two_factor_service
+        .confirm_enrolment(user_pointer.id, &two_factor_form.code)
+        .log_it()
+        .map_err(Error::from_error_stack)?;
+    let flash_locale = TwoFactorFlashLocale::new(&context_html_builder.locale);
+
+    let Some(recovery_codes) = recovery_codes else {
+        let enrolment = two_factor_service
+            .pending_enrolment(user_pointer.id, &user_pointer.username)
+            .log_it()
+            .map_err(Error::from_error_stack)?;
+        context_html_builder.attach_flash(Flash::Error {
+            msg: flash_locale.error_invalid_code,
+        });
+        return Ok(
+            enrolment_html(&context_html_builder, &enrolment, csrf_token)
+                .with_status(StatusCode::UNPROCESSABLE_ENTITY)
+                .into_response(),
+        );
+    };
+
+    let lc = TwoFactorLocale::new(&context_html_builder.locale);
+    context_html_builder.attach_flash(Flash::Success {
+        msg: flash_locale.success_enabled,
+    });
+    Ok(context_html_builder
+        .attach_title(&lc.recovery_title)
+        .set_current_tag("id-tag-user")
+        .attach_content(html! {
+            h1 .mt-3 { (lc.recovery_title) }
+            p { (lc.recovery_intro) }
+            ul .mt-3 {
+                @for code in recovery_codes.iter() {
+                    li { code { (code) } }
+                }
+            }
+            div .mt-3 {
+                a .btn .btn-sky-blue href=(two_factor_url("/")) hx-boost="true"
+                    hx-push-url="true" hx-target="#main-content" { (lc.recovery_continue) }
+            }
+        })
+        .build()
+        .into_response())
+}
+
+#[handler]
+async fn two_factor_disable_post(
+    Dep(two_factor_service): Dep<TwoFactorService>,
+    Dep(user_pointer): Dep<UserPointer>,
+    CsrfFormQs(two_factor_form): CsrfFormQs<TwoFactorCodeForm>,
+    session: &Session,
+    htmx_header: HtmxHeader,
+    locale: Locale,
+) -> poem::Result<Response> {
+    let disabled = two_factor_service
+        .disable(user_pointer.id, &two_factor_form.code)
+        .log_it()
+        .map_err(Error::from_error_stack)?;
+
+    let flash_locale = TwoFactorFlashLocale::new(&locale);
+    session.flash(if disabled {
+        Flash::Success {
+            msg: flash_locale.success_disabled,
+        }
+    } else {
+        Flash::Error {
+            msg: flash_locale.error_invalid_code,
+        }
+    });
+    Ok(htmx_header.do_location(Redirect::see_other(two_factor_url("/")), "#main-content"))
+}
+
+pub fn two_factor_route() -> Route {
+    Route::new()
+        .at("/", get(two_factor_get))
+        .at("/enrol", post(csrf_header_check(two_factor_enrol_post)))
+        .at("/confirm", post(csrf_header_check(two_factor_confirm_post)))
+        .at("/disable", post(csrf_header_check(two_factor_disable_post)))
+}
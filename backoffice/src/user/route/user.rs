@@ -1,13 +1,21 @@
 use crate::common::html::context_html::ContextHtmlBuilder;
-use crate::common::icon::{flag_icon, key_icon, pencil_square_icon, plus_icon};
+use crate::common::icon::{
+    computer_desktop_icon, flag_icon, key_icon, lock_open_icon, pencil_square_icon, plus_icon,
+    shield_check_icon, shield_exclamation_icon,
+};
 use crate::user::form::add_user::AddUserForm;
 use crate::user::form::edit_password_manager::EditPasswordManagerForm;
 use crate::user::form::edit_user::EditUserForm;
-use crate::user::locale::user::{UserLocale, user_logout_confirm_message};
+use crate::user::locale::user::{
+    UserLocale, user_logout_confirm_message, user_reset_two_factor_confirm_message,
+};
 use crate::user::pointer::user_pointer::UserPointer;
 use crate::user::repository::user_manager_repository::UserManagerRepository;
 use crate::user::role::Role;
 use crate::user::role::user_role_check::{must_be_root, must_be_user};
+use crate::user::route::session::session_route;
+use crate::user::route::two_factor::two_factor_route;
+use crate::user::service::two_factor_service::TwoFactorService;
 use crate::user::service::user_manager_service::add_user_service::AddUserService;
 use crate::user::service::user_manager_service::edit_password_service::EditPasswordService;
 use crate::user::service::user_manager_service::edit_service::EditUserService;
@@ -17,9 +25,9 @@ use poem::http::StatusCode;
 use poem::i18n::{I18NArgs, Locale};
 use poem::session::Session;
 use poem::web::{CsrfToken, Path, Redirect};
-use poem::{Error, IntoResponse, Response, Route, get, handler};
+use poem::{Error, IntoResponse, Response, Route, get, handler, post};
 use shared::utils::context::Dep;
-use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml, csrf_header_check};
+use shared::utils::csrf::{CsrfFormQs, CsrfTokenHtml, csrf_header_check, csrf_header_check_strict};
 use shared::utils::error::{ExtraResultExt, FromErrorStack};
 use shared::utils::flash::{Flash, FlashMessageExt};
 use shared::utils::htmx::HtmxHeader;
@@ -32,11 +40,16 @@ async fn list_users(
     Dep(list_user_service): Dep<ListUserService>,
     Dep(context_html_builder): Dep<ContextHtmlBuilder>,
     Dep(user_id_context): Dep<UserPointer>,
+    csrf_token: &CsrfToken,
 ) -> Markup {
     let list_user = list_user_service.list_users();
     let edit_icon = pencil_square_icon();
     let password_icon = key_icon();
     let flag_icon = flag_icon();
+    let unlock_icon = lock_open_icon();
+    let two_factor_icon = shield_check_icon();
+    let reset_two_factor_icon = shield_exclamation_icon();
+    let sessions_icon = computer_desktop_icon();
 
     let user_locale = UserLocale::new(&context_html_builder.locale);
 
@@ -51,9 +64,8 @@ async fn list_users(
                         th { (&user_locale.user_list_head_id) }
                         th { (&user_locale.user_list_head_username) }
                         th { (&user_locale.user_list_head_role) }
-                        @if user_id_context.role == Role::Root {
-                            th .action { "Action" }
-                        }
+                        th { (&user_locale.user_list_head_status) }
+                        th .action { "Action" }
                     }
                 }
                 tbody {
@@ -62,8 +74,28 @@ async fn list_users(
                             td { (user.id) }
                             td { (&user.username) }
                             td { (user.role.as_stringed()) }
-                            @if user_id_context.role == Role::Root {
-                                td .action {
+                            td {
+                                @if user.locked {
+                                    (&user_locale.user_list_status_locked) " "
+                                }
+                                @if user.two_factor {
+                                    (&user_locale.user_list_status_two_factor)
+                                }
+                            }
+                            td .action {
+                                @if user.id == user_id_context.id {
+                                    a .icon href=(format!("{}/two-factor/", USER_ROUTE)) title=(&user_locale.user_list_action_two_factor)
+                                        hx-boost="true" hx-push-url="true" hx-target="#main-content" { (two_factor_icon) }
+                                    " "
+                                    a .icon href=(format!("{}/sessions/", USER_ROUTE)) title=(&user_locale.user_list_action_sessions)
+                                        hx-boost="true" hx-push-url="true" hx-target="#main-content" { (sessions_icon) }
+                                    " "
+                                } @else if user_id_context.role == Role::Root {
+                                    a .icon href=(format!("{}/sessions/user/{}", USER_ROUTE, user.id)) title=(&user_locale.user_list_action_sessions)
+                                        hx-boost="true" hx-push-url="true" hx-target="#main-content" { (sessions_icon) }
+                                    " "
+                                }
+                                @if user_id_context.role == Role::Root {
                                     a .icon href=(format!("{}/edit/{}", USER_ROUTE, user.id)) title=(&user_locale.user_list_action_edit)
                                         hx-boost="true" hx-push-url="true" hx-target="#main-content" { (edit_icon) }
                                     " "
@@ -73,6 +105,17 @@ async fn list_users(
                                     a .icon hx-confirm=(user_logout_confirm_message(&context_html_builder.locale, &user.username))
                                         href=(format!("{}/sign-out/{}", USER_ROUTE, user.id)) title=(&user_locale.user_list_action_sign_out)
                                         hx-boost="true" hx-push-url="true" hx-target="#main-content" { (flag_icon) }
+                                    @if user.locked {
+                                        " "
+                                        a .icon href=(format!("{}/unlock/{}", USER_ROUTE, user.id)) title=(&user_locale.user_list_action_unlock)
+                                            hx-post=(format!("{}/unlock/{}", USER_ROUTE, user.id)) { (unlock_icon) }
+                                    }
+                                    @if user.two_factor {
+                                        " "
+                                        a .icon hx-confirm=(user_reset_two_factor_confirm_message(&context_html_builder.locale, &user.username))
+                                            href=(format!("{}/reset-two-factor/{}", USER_ROUTE, user.id)) title=(&user_locale.user_list_action_reset_two_factor)
+                                            hx-post=(format!("{}/reset-two-factor/{}", USER_ROUTE, user.id)) { (reset_two_factor_icon) }
+                                    }
                                 }
                             }
                         }
@@ -86,6 +129,9 @@ async fn list_users(
                 }
             }
         })
+        .attach_footer(html! {
+            (csrf_token.as_html_command())
+        })
         .build()
 }
 
@@ -355,6 +401,86 @@ fn sign_out_user(
     )
 }
 
+#[handler]
+fn unlock_user(
+    Dep(user_manager_repository): Dep<UserManagerRepository>,
+    Path(user_id): Path<i64>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> Response {
+    let result = user_manager_repository.unlock_user(user_id);
+    let l = &locale;
+    if result.is_err() {
+        session.flash(Flash::Error {
+            msg: l.text_with_default_args(
+                "user-route-flash-unlock-error",
+                format!("Failed to unlock user id: {}", user_id).as_str(),
+                I18NArgs::from((("user_id", user_id),)),
+            ),
+        });
+        return htmx_header.do_location(
+            Redirect::see_other(USER_ROUTE.to_owned() + "/"),
+            "#main-content",
+        );
+    }
+    session.flash(Flash::Success {
+        msg: l.text_with_default_args(
+            "user-route-flash-unlock-success",
+            format!("Successfully unlocked user id: {}", user_id).as_str(),
+            I18NArgs::from((("user_id", user_id),)),
+        ),
+    });
+    htmx_header.do_location(
+        Redirect::see_other(USER_ROUTE.to_owned() + "/"),
+        "#main-content",
+    )
+}
+
+#[handler]
+fn reset_two_factor(
+    Dep(two_factor_service): Dep<TwoFactorService>,
+    Path(user_id): Path<i64>,
+    session: &Session,
+    locale: Locale,
+    htmx_header: HtmxHeader,
+) -> Response {
+    let result = two_factor_service.reset(user_id);
+    let l = &locale;
+    if result.is_err() {
+        session.flash(Flash::Error {
+            msg: l.text_with_default_args(
+                "user-route-flash-reset-two-factor-error",
+                format!(
+                    "Failed to reset two-factor authentication for user id: {}",
+                    user_id
+                )
+                .as_str(),
+                I18NArgs::from((("user_id", user_id),)),
+            ),
+        });
+        return htmx_header.do_location(
+            Redirect::see_other(USER_ROUTE.to_owned() + "/"),
+            "#main-content",
+        );
+    }
+    session.flash(Flash::Success {
+        msg: l.text_with_default_args(
+            "user-route-flash-reset-two-factor-success",
+            format!(
+                "Successfully reset two-factor authentication for user id: {}",
+                user_id
+            )
+            .as_str(),
+            I18NArgs::from((("user_id", user_id),)),
+        ),
+    });
+    htmx_header.do_location(
+        Redirect::see_other(USER_ROUTE.to_owned() + "/"),
+        "#main-content",
+    )
+}
+
 pub fn user_route() -> Route {
     Route::new()
         .at("/", get(must_be_user(list_users)))
@@ -375,4 +501,14 @@ pub fn user_route() -> Route {
             ),
         )
         .at("/sign-out/:user_id", must_be_root(get(sign_out_user)))
+        .at(
+            "/unlock/:user_id",
+            must_be_root(post(csrf_header_check_strict(unlock_user))),
+        )
+        .at(
+            "/reset-two-factor/:user_id",
+            must_be_root(post(csrf_header_check_strict(reset_two_factor))),
+        )
+        .nest("/two-factor", must_be_user(two_factor_route()))
+        .nest("/sessions", must_be_user(session_route()))
 }
@@ -1,3 +1,5 @@
+pub mod session_service;
+pub mod two_factor_service;
 pub mod user_check_service;
 pub mod user_login_service;
 pub mod user_manager_service;
@@ -0,0 +1,146 @@
+use crate::user::LOGIN_TOKEN_COOKIE_NAME;
+use crate::user::layer::login_token_layer::hash_login_token;
+use crate::user::model::session_model::LoginSession;
+use crate::user::repository::session_repository::SessionRepository;
+use crate::user::repository::user_manager_repository::UserManagerRepository;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use shared::utils::context::{Context, ContextError, FromContext};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SessionServiceError {
+    #[error("DB error")]
+    DbError,
+    #[error("Not found")]
+    NotFound,
+}
+
+pub struct SessionService {
+    session_repository: SessionRepository,
+    user_manager_repository: UserManagerRepository,
+    token_cookie: Option<String>,
+}
+
+impl SessionService {
+    pub fn new(
+        session_repository: SessionRepository,
+        user_manager_repository: UserManagerRepository,
+        token_cookie: Option<String>,
+    ) -> Self {
+        Self {
+            session_repository,
+            user_manager_repository,
+            token_cookie,
+        }
+    }
+
+    pub fn fetch_username(&self, user_id: i64) -> Result<String, Report<SessionServiceError>> {
+        self.user_manager_repository
+            .fetch_user(user_id)
+            .change_context(SessionServiceError::DbError)?
+            .map(|user| user.username)
+            .ok_or_else(|| Report::new(SessionServiceError::NotFound).attach(StatusCode::NOT_FOUND))
+    }
+
+    pub fn list_sessions(
+        &self,
+        user_id: i64,
+    ) -> Result<Arc<[LoginSession]>, Report<SessionServiceError>> {
+        self.session_repository
+            .list_sessions(user_id, self.token_cookie.as_deref().map(hash_login_token))
+            .change_context(SessionServiceError::DbError)
+    }
+
+    pub fn revoke_session(
+        &self,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<(), Report<SessionServiceError>> {
+        let revoked = self
+            .session_repository
+            .revoke_session(user_id, session_id)
+            .change_context(SessionServiceError::DbError)?;
+        if !revoked {
+            return Err(Report::new(SessionServiceError::NotFound).attach(StatusCode::NOT_FOUND));
+        }
+        Ok(())
+    }
+}
+
+impl FromContext for SessionService {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let req = ctx.req_result()?;
+        let cookie = req.cookie();
+        Ok(Self::new(
+            ctx.inject().await?,
+            ctx.inject().await?,
+            cookie
+                .get(LOGIN_TOKEN_COOKIE_NAME)
+                .map(|v| v.value_str().to_string()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::model::user_manager_model::FetchUser;
+    use chrono::Utc;
+
+    #[test]
+    fn test_list_sessions_passes_current_token() {
+        let mut session_repository = SessionRepository::new_mock();
+        session_repository
+            .mock_list_sessions(1, Some(hash_login_token("hello")))
+            .returns_once(Ok(Arc::new([LoginSession {
+                id: 3,
+                created_at: Utc::now(),
+                last_seen_at: Utc::now(),
+                user_agent: None,
+                client_ip: None,
+                current: true,
+            }])));
+
+        let service = SessionService::new(
+            session_repository,
+            UserManagerRepository::new_mock(),
+            Some("hello".to_string()),
+        );
+        let result = service.list_sessions(1).unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].current);
+    }
+
+    #[test]
+    fn test_revoke_session_not_found() {
+        let mut session_repository = SessionRepository::new_mock();
+        session_repository
+            .mock_revoke_session(1, 3)
+            .returns_once(Ok(false));
+
+        let service =
+            SessionService::new(session_repository, UserManagerRepository::new_mock(), None);
+        let result = service.revoke_session(1, 3).err().unwrap();
+        assert_eq!(
+            result.downcast_ref::<StatusCode>(),
+            Some(&StatusCode::NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_fetch_username_success() {
+        let mut user_manager_repository = UserManagerRepository::new_mock();
+        user_manager_repository
+            .mock_fetch_user(2)
+            .returns_once(Ok(Some(FetchUser {
+                username: "hello".to_string(),
+                role: Default::default(),
+            })));
+
+        let service =
+            SessionService::new(SessionRepository::new_mock(), user_manager_repository, None);
+        assert_eq!(service.fetch_username(2).unwrap(), "hello");
+    }
+}
@@ -0,0 +1,378 @@
+use crate::user::layer::two_factor_layer::TwoFactorLayer;
+use crate::user::model::two_factor_model::{TwoFactorEnrolment, TwoFactorPending, TwoFactorStatus};
+use crate::user::repository::two_factor_repository::TwoFactorRepository;
+use chrono::{TimeDelta, Utc};
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use shared::utils::context::{Context, ContextError, FromContext};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TwoFactorServiceError {
+    #[error("DB error")]
+    DbError,
+    #[error("Secret error")]
+    SecretError,
+    #[error("No enrolment is pending")]
+    NotPending,
+}
+
+pub struct TwoFactorService {
+    two_factor_repository: TwoFactorRepository,
+    two_factor_layer: TwoFactorLayer,
+}
+
+impl TwoFactorService {
+    pub fn new(
+        two_factor_repository: TwoFactorRepository,
+        two_factor_layer: TwoFactorLayer,
+    ) -> Self {
+        Self {
+            two_factor_repository,
+            two_factor_layer,
+        }
+    }
+
+    pub fn status(&self, user_id: i64) -> Result<TwoFactorStatus, Report<TwoFactorServiceError>> {
+        let two_factor = self
+            .two_factor_repository
+            .fetch_two_factor(user_id)
+            .change_context(TwoFactorServiceError::DbError)?;
+        Ok(match two_factor {
+            None => TwoFactorStatus::Disabled,
+            Some(two_factor) if !two_factor.enabled => TwoFactorStatus::Pending,
+            Some(_) => TwoFactorStatus::Enabled {
+                recovery_codes_left: self
+                    .two_factor_repository
+                    .count_recovery_codes(user_id)
+                    .change_context(TwoFactorServiceError::DbError)?,
+            },
+        })
+    }
+
+    pub fn is_enabled(&self, user_id: i64) -> Result<bool, Report<TwoFactorServiceError>> {
+        Ok(matches!(
+            self.status(user_id)?,
+            TwoFactorStatus::Enabled { .. }
+        ))
+    }
+
+    /// Seals the state of a half finished login, so it can travel in the session cookie.
+    pub fn seal_pending_login(
+        &self,
+        user_id: i64,
+        username: String,
+    ) -> Result<Box<[u8]>, Report<TwoFactorServiceError>> {
+        let pending = TwoFactorPending {
+            user_id,
+            username,
+            expires_at: Utc::now()
+                + TimeDelta::seconds(self.two_factor_layer.pending_login_secs() as i64),
+        };
+        let pending =
+            serde_json::to_string(&pending).change_context(TwoFactorServiceError::SecretError)?;
+        self.two_factor_layer
+            .encrypt(pending.as_str())
+            .change_context(TwoFactorServiceError::SecretError)
+    }
+
+    /// Returns `None` for anything not produced by `seal_pending_login`.
+    pub fn open_pending_login(&self, sealed: Box<[u8]>) -> Option<TwoFactorPending> {
+        let pending = self.two_factor_layer.decrypt(sealed).ok()?;
+        serde_json::from_str(pending.as_str()).ok()
+    }
+
+    /// Starts a new enrolment, replacing a previous one that was never confirmed.
+    pub fn begin_enrolment(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<TwoFactorEnrolment, Report<TwoFactorServiceError>> {
+        let secret = self.two_factor_layer.generate_secret();
+        let encrypted = self
+            .two_factor_layer
+            .encrypt(secret.as_str())
+            .change_context(TwoFactorServiceError::SecretError)?;
+        self.two_factor_repository
+            .save_pending_secret(user_id, encrypted)
+            .change_context(TwoFactorServiceError::DbError)?;
+        self.enrolment(secret, username)
+    }
+
+    pub fn pending_enrolment(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<TwoFactorEnrolment, Report<TwoFactorServiceError>> {
+        let two_factor = self
+            .two_factor_repository
+            .fetch_two_factor(user_id)
+            .change_context(TwoFactorServiceError::DbError)?
+            .filter(|two_factor| !two_factor.enabled)
+            .ok_or_else(|| {
+                Report::new(TwoFactorServiceError::NotPending).attach(StatusCode::NOT_FOUND)
+            })?;
+        let secret = self
+            .two_factor_layer
+            .decrypt(two_factor.secret)
+            .change_context(TwoFactorServiceError::SecretError)?;
+        self.enrolment(secret, username)
+    }
+
+    fn enrolment(
+        &self,
+        secret: String,
+        username: &str,
+    ) -> Result<TwoFactorEnrolment, Report<TwoFactorServiceError>> {
+        let otpauth_url = self
+            .two_factor_layer
+            .otpauth_url(secret.as_str(), username)
+            .change_context(TwoFactorServiceError::SecretError)?;
+        Ok(TwoFactorEnrolment {
+            secret,
+            otpauth_url,
+        })
+    }
+
+    /// Activates the pending enrolment once `code` proves the authenticator is set up.
+    ///
+    /// Returns the plain recovery codes, which are only ever shown this once, or `None`
+    /// when the code is wrong.
+    pub fn confirm_enrolment(
+        &self,
+        user_id: i64,
+        code: &str,
+    ) -> Result<Option<Arc<[String]>>, Report<TwoFactorServiceError>> {
+        let two_factor = self
+            .two_factor_repository
+            .fetch_two_factor(user_id)
+            .change_context(TwoFactorServiceError::DbError)?
+            .filter(|two_factor| !two_factor.enabled)
+            .ok_or_else(|| {
+                Report::new(TwoFactorServiceError::NotPending).attach(StatusCode::NOT_FOUND)
+            })?;
+        let secret = self
+            .two_factor_layer
+            .decrypt(two_factor.secret)
+            .change_context(TwoFactorServiceError::SecretError)?;
+
+        let Some(step) = self.two_factor_layer.verify_code(
+            secret.as_str(),
+            code.trim(),
+            Utc::now().timestamp() as u64,
+        ) else {
+            return Ok(None);
+        };
+
+        let recovery_codes = self.two_factor_layer.generate_recovery_codes();
+        let enabled = self
+            .two_factor_repository
+            .enable_two_factor(
+                user_id,
+                step,
+                recovery_codes
+                    .iter()
+                    .map(|code| TwoFactorLayer::hash_recovery_code(code))
+                    .collect(),
+            )
+            .change_context(TwoFactorServiceError::DbError)?;
+        if !enabled {
+            return Err(
+                Report::new(TwoFactorServiceError::NotPending).attach(StatusCode::NOT_FOUND)
+            );
+        }
+        Ok(Some(recovery_codes.into()))
+    }
+
+    /// Accepts either a current TOTP code or an unused recovery code, each at most once.
+    pub fn verify(&self, user_id: i64, code: &str) -> Result<bool, Report<TwoFactorServiceError>> {
+        let Some(two_factor) = self
+            .two_factor_repository
+            .fetch_two_factor(user_id)
+            .change_context(TwoFactorServiceError::DbError)?
+            .filter(|two_factor| two_factor.enabled)
+        else {
+            return Ok(false);
+        };
+
+        let code = code.trim();
+        if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+            let secret = self
+                .two_factor_layer
+                .decrypt(two_factor.secret)
+                .change_context(TwoFactorServiceError::SecretError)?;
+            return match self.two_factor_layer.verify_code(
+                secret.as_str(),
+                code,
+                Utc::now().timestamp() as u64,
+            ) {
+                Some(step) => self
+                    .two_factor_repository
+                    .use_step(user_id, step)
+                    .change_context(TwoFactorServiceError::DbError),
+                None => Ok(false),
+            };
+        }
+
+        self.two_factor_repository
+            .use_recovery_code(user_id, TwoFactorLayer::hash_recovery_code(code))
+            .change_context(TwoFactorServiceError::DbError)
+    }
+
+    /// Turns 2FA off after the user proved they still hold the authenticator.
+    pub fn disable(&self, user_id: i64, code: &str) -> Result<bool, Report<TwoFactorServiceError>> {
+        if !self.verify(user_id, code)? {
+            return Ok(false);
+        }
+        self.reset(user_id)?;
+        Ok(true)
+    }
+
+    pub fn reset(&self, user_id: i64) -> Result<(), Report<TwoFactorServiceError>> {
+        self.two_factor_repository
+            .delete_two_factor(user_id)
+            .change_context(TwoFactorServiceError::DbError)
+    }
+}
+
+impl FromContext for TwoFactorService {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?, ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::model::two_factor_model::TwoFactorSecret;
+    use mry::Any;
+
+    fn enabled_secret() -> Option<TwoFactorSecret> {
+        Some(TwoFactorSecret {
+            secret: Default::default(),
+            enabled: true,
+        })
+    }
+
+    #[test]
+    fn test_confirm_enrolment_success() {
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(Some(TwoFactorSecret {
+                secret: Default::default(),
+                enabled: false,
+            })));
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok("SECRET".to_string()));
+        two_factor_layer
+            .mock_verify_code("SECRET", "123456", Any)
+            .returns_once(Some(42));
+        two_factor_layer
+            .mock_generate_recovery_codes()
+            .returns_once(vec!["aaaaa-bbbbb".to_string()]);
+        two_factor_repository
+            .mock_enable_two_factor(1, 42, Any)
+            .returns_once(Ok(true));
+
+        let service = TwoFactorService::new(two_factor_repository, two_factor_layer);
+        let result = service.confirm_enrolment(1, "123456");
+        assert_eq!(result.unwrap().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_confirm_enrolment_wrong_code() {
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(Some(TwoFactorSecret {
+                secret: Default::default(),
+                enabled: false,
+            })));
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok("SECRET".to_string()));
+        two_factor_layer
+            .mock_verify_code("SECRET", "123456", Any)
+            .returns_once(None);
+
+        let service = TwoFactorService::new(two_factor_repository, two_factor_layer);
+        let result = service.confirm_enrolment(1, "123456");
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_confirm_enrolment_not_pending() {
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(enabled_secret()));
+
+        let service = TwoFactorService::new(two_factor_repository, two_factor_layer);
+        let result = service.confirm_enrolment(1, "123456");
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.downcast_ref::<StatusCode>(),
+            Some(&StatusCode::NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_verify_totp_code_replayed() {
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(enabled_secret()));
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok("SECRET".to_string()));
+        two_factor_layer
+            .mock_verify_code("SECRET", "123456", Any)
+            .returns_once(Some(42));
+        two_factor_repository
+            .mock_use_step(1, 42)
+            .returns_once(Ok(false));
+
+        let service = TwoFactorService::new(two_factor_repository, two_factor_layer);
+        assert!(!service.verify(1, "123456").unwrap());
+    }
+
+    #[test]
+    fn test_verify_recovery_code() {
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(enabled_secret()));
+        two_factor_repository
+            .mock_use_recovery_code(1, TwoFactorLayer::hash_recovery_code("aaaaabbbbb"))
+            .returns_once(Ok(true));
+
+        let service = TwoFactorService::new(two_factor_repository, two_factor_layer);
+        assert!(service.verify(1, " AAAAA-BBBBB ").unwrap());
+    }
+
+    #[test]
+    fn test_verify_without_two_factor() {
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(None));
+
+        let service = TwoFactorService::new(two_factor_repository, two_factor_layer);
+        assert!(!service.verify(1, "123456").unwrap());
+    }
+}
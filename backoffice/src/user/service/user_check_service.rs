@@ -1,8 +1,10 @@
 use crate::user::LOGIN_TOKEN_COOKIE_NAME;
+use crate::user::layer::login_token_layer::hash_login_token;
 use crate::user::model::user_model::UserIdContext;
 use crate::user::repository::user_repository::UserRepository;
 use crate::user::role::Role;
 use error_stack::Report;
+use log::warn;
 use shared::utils::context::{Context, ContextError, FromContext};
 
 pub struct UserCheckService {
@@ -30,12 +32,29 @@ impl UserCheckService {
         }
     }
 
+    /// Role of the token holder right now, `Visitor` once the token is revoked or has
+    /// expired. Unlike `get_user_context` it does not count as activity on the token.
+    pub fn current_role(&self) -> Role {
+        let Some(token) = self.token_cookie.as_ref() else {
+            return Role::Visitor;
+        };
+        self.user_repository
+            .find_by_token(hash_login_token(token))
+            .map(|user_context| user_context.role)
+            .unwrap_or(Role::Visitor)
+    }
+
     fn is_logged_in(&self) -> Option<UserIdContext> {
-        if let Some(token) = self.token_cookie.as_ref() {
-            self.user_repository.find_by_token(token.to_string()).ok()
-        } else {
-            None
-        }
+        let token_hash = hash_login_token(self.token_cookie.as_ref()?);
+        let user_context = self
+            .user_repository
+            .find_by_token(token_hash.clone())
+            .ok()?;
+        let _ = self
+            .user_repository
+            .touch_token(token_hash)
+            .inspect_err(|report| warn!("{:?}", report));
+        Some(user_context)
     }
 }
 
@@ -62,12 +81,15 @@ mod tests {
         let mut user_repository = UserRepository::new_mock();
 
         user_repository
-            .mock_find_by_token("hello".to_string())
+            .mock_find_by_token(hash_login_token("hello"))
             .returns_once(Ok(UserIdContext {
                 id: 5,
                 username: "".to_string(),
                 role: Default::default(),
             }));
+        user_repository
+            .mock_touch_token(hash_login_token("hello"))
+            .returns_once(Ok(()));
 
         let service = UserCheckService::new(user_repository, Some("hello".to_string()));
         let result = service.get_user_context();
@@ -79,11 +101,23 @@ mod tests {
         let mut user_repository = UserRepository::new_mock();
 
         user_repository
-            .mock_find_by_token("hello".to_string())
+            .mock_find_by_token(hash_login_token("hello"))
             .returns_once(Err(Report::new(UserRepositoryError::QueryError)));
 
         let service = UserCheckService::new(user_repository, Some("hello".to_string()));
         let result = service.get_user_context();
         assert_eq!(result.id, 0);
     }
+
+    #[test]
+    fn test_current_role_visitor_once_revoked() {
+        let mut user_repository = UserRepository::new_mock();
+
+        user_repository
+            .mock_find_by_token(hash_login_token("hello"))
+            .returns_once(Err(Report::new(UserRepositoryError::NotFoundError)));
+
+        let service = UserCheckService::new(user_repository, Some("hello".to_string()));
+        assert_eq!(service.current_role(), Role::Visitor);
+    }
 }
@@ -1,56 +1,350 @@
 use crate::user::LOGIN_TOKEN_COOKIE_NAME;
+use crate::user::layer::login_token_layer::{generate_login_token, hash_login_token};
 use crate::user::layer::password_layer::PasswordLayer;
-use crate::user::repository::user_repository::UserRepository;
-use error_stack::Report;
+use crate::user::repository::login_attempt_repository::{
+    LOGIN_ATTEMPT_SCOPE_IP, LOGIN_ATTEMPT_SCOPE_USERNAME, LoginAttemptRepository,
+};
+use crate::user::repository::user_repository::{UserRepository, UserRepositoryError};
+use crate::user::service::two_factor_service::TwoFactorService;
+use chrono::{DateTime, TimeDelta, Utc};
+use error_stack::{Report, ResultExt};
+use log::{error, warn};
+use poem::http::header;
+use shared::utils::client_ip::ClientIp;
+use shared::utils::config::ConfigPointer;
+use shared::utils::config::login_throttle::LoginThrottleConfig;
 use shared::utils::context::{Context, ContextError, FromContext};
-use uuid::Uuid;
+use shared::utils::password::{Password, PasswordState};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum UserLoginServiceError {
+    #[error("Database error")]
+    DbError,
+    #[error("Password Serialize Error")]
+    PasswordSerializeError,
+    #[error("Password Verify Error")]
+    PasswordVerifyError,
+}
+
+/// Why `validate_login` refused to issue a token.
+#[derive(Debug, PartialEq)]
+pub enum LoginFailure {
+    InvalidCredentials,
+    Throttled {
+        retry_after_secs: u64,
+    },
+    Locked,
+    /// The second login step was not completed in time.
+    Expired,
+    Unavailable,
+}
+
+/// What a correct password unlocks.
+#[derive(Debug, PartialEq)]
+pub enum LoginSuccess {
+    Token(String),
+    /// The account has 2FA enabled, a code must pass `validate_two_factor` first.
+    /// Carries the sealed pending login for `validate_two_factor`.
+    TwoFactorRequired(Box<[u8]>),
+}
 
 pub struct UserLoginService {
     user_repository: UserRepository,
+    login_attempt_repository: LoginAttemptRepository,
     password_layer: PasswordLayer,
+    two_factor_service: TwoFactorService,
+    login_throttle_config: Arc<LoginThrottleConfig>,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
     token_cookie: Option<String>,
 }
 
 impl UserLoginService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_repository: UserRepository,
+        login_attempt_repository: LoginAttemptRepository,
         password_layer: PasswordLayer,
+        two_factor_service: TwoFactorService,
+        login_throttle_config: Arc<LoginThrottleConfig>,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
         token_cookie: Option<String>,
     ) -> Self {
         Self {
             user_repository,
+            login_attempt_repository,
             password_layer,
+            two_factor_service,
+            login_throttle_config,
+            client_ip,
+            user_agent,
             token_cookie,
         }
     }
 
-    pub fn validate_login(&self, username: String, password: String) -> Option<String> {
-        if let Ok(id_password) = self.user_repository.get_user_password(username) {
-            let password_status = self
-                .password_layer
-                .verify_password(id_password.password, password.as_str());
-            if let Ok(password_state) = password_status {
-                if password_state.is_valid() {
-                    let uuid = Uuid::new_v4().to_string();
+    pub fn validate_login(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<LoginSuccess, LoginFailure> {
+        let now = Utc::now();
+        self.check_throttle(LOGIN_ATTEMPT_SCOPE_USERNAME, &username, now)?;
+        if let Some(client_ip) = self.client_ip.as_deref() {
+            self.check_throttle(LOGIN_ATTEMPT_SCOPE_IP, client_ip, now)?;
+        }
+
+        let id_password = match self.user_repository.get_user_password(username.clone()) {
+            Ok(id_password) => id_password,
+            Err(report)
+                if matches!(report.current_context(), UserRepositoryError::NotFoundError) =>
+            {
+                return Err(self.record_failure(&username, None, now));
+            }
+            Err(report) => {
+                error!(
+                    "{:?}",
+                    report.change_context(UserLoginServiceError::DbError)
+                );
+                return Err(LoginFailure::Unavailable);
+            }
+        };
+        let password_state = self
+            .password_layer
+            .verify_password(id_password.password, password.as_str())
+            .change_context(UserLoginServiceError::PasswordVerifyError)
+            .map_err(|report| {
+                error!("{:?}", report);
+                LoginFailure::Unavailable
+            })?;
+        if !password_state.is_valid() {
+            return Err(self.record_failure(&username, Some(id_password.id), now));
+        }
+        // Only reported after the password checks out, so it does not tell
+        // a guesser which usernames exist and are locked.
+        if id_password.locked {
+            return Err(LoginFailure::Locked);
+        }
+        if let PasswordState::ValidRehashed(password) = password_state {
+            self.persist_rehashed_password(id_password.id, password);
+        }
+
+        match self.two_factor_service.is_enabled(id_password.id) {
+            Ok(true) => {
+                return self
+                    .two_factor_service
+                    .seal_pending_login(id_password.id, username)
+                    .map(LoginSuccess::TwoFactorRequired)
+                    .map_err(|report| {
+                        error!("{:?}", report);
+                        LoginFailure::Unavailable
+                    });
+            }
+            Ok(false) => {}
+            Err(report) => {
+                error!("{:?}", report);
+                return Err(LoginFailure::Unavailable);
+            }
+        }
+
+        self.clear_attempts(&username);
+        self.issue_token(id_password.id).map(LoginSuccess::Token)
+    }
+
+    /// Second login step, after `validate_login` returned `TwoFactorRequired`.
+    ///
+    /// Wrong codes count towards the same throttle and lockout as wrong passwords.
+    pub fn validate_two_factor(
+        &self,
+        sealed_pending: Box<[u8]>,
+        code: String,
+    ) -> Result<String, LoginFailure> {
+        let now = Utc::now();
+        let pending = self
+            .two_factor_service
+            .open_pending_login(sealed_pending)
+            .filter(|pending| pending.expires_at >= now)
+            .ok_or(LoginFailure::Expired)?;
+        let (user_id, username) = (pending.user_id, &pending.username);
+        self.check_throttle(LOGIN_ATTEMPT_SCOPE_USERNAME, username, now)?;
+        if let Some(client_ip) = self.client_ip.as_deref() {
+            self.check_throttle(LOGIN_ATTEMPT_SCOPE_IP, client_ip, now)?;
+        }
+        // The account may have been locked since the password step.
+        match self.user_repository.is_locked(user_id) {
+            Ok(false) => {}
+            Ok(true) => return Err(LoginFailure::Locked),
+            Err(report)
+                if matches!(report.current_context(), UserRepositoryError::NotFoundError) =>
+            {
+                return Err(LoginFailure::Expired);
+            }
+            Err(report) => {
+                error!(
+                    "{:?}",
+                    report.change_context(UserLoginServiceError::DbError)
+                );
+                return Err(LoginFailure::Unavailable);
+            }
+        }
+
+        match self.two_factor_service.verify(user_id, code.as_str()) {
+            Ok(true) => {}
+            Ok(false) => return Err(self.record_failure(username, Some(user_id), now)),
+            Err(report) => {
+                error!("{:?}", report);
+                return Err(LoginFailure::Unavailable);
+            }
+        }
+
+        self.clear_attempts(username);
+        self.issue_token(user_id)
+    }
+
+    fn issue_token(&self, user_id: i64) -> Result<String, LoginFailure> {
+        let token = generate_login_token();
+        self.user_repository
+            .add_token(
+                hash_login_token(&token),
+                user_id,
+                self.user_agent.clone(),
+                self.client_ip.clone(),
+            )
+            .map_err(|_| LoginFailure::Unavailable)?;
+
+        Ok(token)
+    }
+
+    fn backoff_secs(&self, failed_count: u32) -> u64 {
+        let config = &self.login_throttle_config;
+        2u64.saturating_pow(failed_count.saturating_sub(1))
+            .saturating_mul(config.backoff_base_secs)
+            .min(config.backoff_max_secs)
+    }
+
+    fn check_throttle(
+        &self,
+        scope: &str,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), LoginFailure> {
+        let attempt = self
+            .login_attempt_repository
+            .fetch_attempt(scope, key.to_string())
+            .change_context(UserLoginServiceError::DbError)
+            .map_err(|report| {
+                error!("{:?}", report);
+                LoginFailure::Unavailable
+            })?;
+        let Some(attempt) = attempt else {
+            return Ok(());
+        };
 
-                    if self
-                        .user_repository
-                        .add_token(uuid.clone(), id_password.id)
-                        .is_err()
-                    {
-                        return None;
-                    }
+        let elapsed_secs = (now - attempt.last_failed_at).num_seconds().max(0) as u64;
+        if elapsed_secs >= self.login_throttle_config.reset_after_secs {
+            return Ok(());
+        }
+        if scope == LOGIN_ATTEMPT_SCOPE_IP
+            && attempt.failed_count >= self.login_throttle_config.ip_lockout_threshold
+        {
+            return Err(LoginFailure::Throttled {
+                retry_after_secs: self.login_throttle_config.reset_after_secs - elapsed_secs,
+            });
+        }
+        let backoff_secs = self.backoff_secs(attempt.failed_count);
+        if elapsed_secs < backoff_secs {
+            return Err(LoginFailure::Throttled {
+                retry_after_secs: backoff_secs - elapsed_secs,
+            });
+        }
+        Ok(())
+    }
 
-                    return Some(uuid);
-                }
+    fn record_failure(
+        &self,
+        username: &str,
+        user_id: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> LoginFailure {
+        let reset_before = now
+            - TimeDelta::seconds(
+                self.login_throttle_config
+                    .reset_after_secs
+                    .min(i64::MAX as u64) as i64,
+            );
+        if let Some(client_ip) = self.client_ip.as_deref() {
+            _ = self
+                .login_attempt_repository
+                .record_failure(
+                    LOGIN_ATTEMPT_SCOPE_IP,
+                    client_ip.to_string(),
+                    now,
+                    reset_before,
+                )
+                .change_context(UserLoginServiceError::DbError)
+                .inspect_err(|report| warn!("{:?}", report));
+        }
+        let failed_count = self
+            .login_attempt_repository
+            .record_failure(
+                LOGIN_ATTEMPT_SCOPE_USERNAME,
+                username.to_string(),
+                now,
+                reset_before,
+            )
+            .change_context(UserLoginServiceError::DbError)
+            .inspect_err(|report| warn!("{:?}", report))
+            .unwrap_or_default();
+
+        match user_id {
+            Some(user_id) if failed_count >= self.login_throttle_config.lockout_threshold => {
+                // Same answer as any other wrong password, unknown usernames never lock.
+                _ = self
+                    .user_repository
+                    .lock_user(user_id)
+                    .change_context(UserLoginServiceError::DbError)
+                    .inspect_err(|report| error!("{:?}", report));
+                LoginFailure::InvalidCredentials
             }
+            _ => LoginFailure::InvalidCredentials,
         }
-        None
+    }
+
+    fn clear_attempts(&self, username: &str) {
+        _ = self
+            .login_attempt_repository
+            .clear_attempts(LOGIN_ATTEMPT_SCOPE_USERNAME, username.to_string())
+            .change_context(UserLoginServiceError::DbError)
+            .inspect_err(|report| warn!("{:?}", report));
+        if let Some(client_ip) = self.client_ip.as_deref() {
+            _ = self
+                .login_attempt_repository
+                .clear_attempts(LOGIN_ATTEMPT_SCOPE_IP, client_ip.to_string())
+                .change_context(UserLoginServiceError::DbError)
+                .inspect_err(|report| warn!("{:?}", report));
+        }
+    }
+
+    fn persist_rehashed_password(&self, user_id: i64, password: Password) {
+        // A failed upgrade must not block the login, the old hash still verifies.
+        _ = password
+            .encode_to_msg_pack()
+            .change_context(UserLoginServiceError::PasswordSerializeError)
+            .and_then(|password| {
+                self.user_repository
+                    .update_password(user_id, password)
+                    .change_context(UserLoginServiceError::DbError)
+            })
+            .inspect_err(|report| warn!("{:?}", report));
     }
 
     pub fn logout(&self) -> bool {
         if let Some(token) = self.token_cookie.as_ref() {
-            self.user_repository.delete_token(token.to_string()).is_ok()
+            self.user_repository
+                .delete_token(hash_login_token(token))
+                .is_ok()
         } else {
             false
         }
@@ -61,9 +355,16 @@ impl FromContext for UserLoginService {
     async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
         let req = ctx.req_result()?;
         let cookie = req.cookie();
+        let config: ConfigPointer = ctx.inject().await?;
+        let client_ip: ClientIp = ctx.inject().await?;
         Ok(Self::new(
             ctx.inject().await?,
             ctx.inject().await?,
+            ctx.inject().await?,
+            ctx.inject().await?,
+            Arc::clone(&config.login_throttle),
+            client_ip.ip().map(|ip| ip.to_string()),
+            req.header(header::USER_AGENT).map(|v| v.to_string()),
             cookie
                 .get(LOGIN_TOKEN_COOKIE_NAME)
                 .map(|v| v.value_str().to_string()),
@@ -74,32 +375,201 @@ impl FromContext for UserLoginService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::user::model::user_model::IdPassword;
-    use crate::user::repository::user_repository::UserRepositoryError;
+    use crate::user::layer::two_factor_layer::TwoFactorLayer;
+    use crate::user::model::two_factor_model::{TwoFactorPending, TwoFactorSecret};
+    use crate::user::model::user_model::{IdPassword, LoginAttempt};
+    use crate::user::repository::two_factor_repository::TwoFactorRepository;
     use mry::Any;
-    use shared::utils::password::PasswordState;
+
+    fn login_attempt_repository_without_failures() -> LoginAttemptRepository {
+        let mut login_attempt_repository = LoginAttemptRepository::new_mock();
+        login_attempt_repository
+            .mock_fetch_attempt(Any, Any)
+            .returns_with(|_, _| Ok(None));
+        login_attempt_repository
+            .mock_clear_attempts(Any, Any)
+            .returns_with(|_, _| Ok(()));
+        login_attempt_repository
+    }
 
     #[test]
     fn test_validate_login_success() {
         let mut user_repository = UserRepository::new_mock();
         let mut password_layer = PasswordLayer::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: false,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::Valid));
+
+        user_repository
+            .mock_add_token(Any, 1, Any, Any)
+            .returns_once(Ok(()));
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(None));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(two_factor_repository, TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_login_stores_only_token_hash() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
 
         user_repository
             .mock_get_user_password("hello".to_string())
             .returns_once(Ok(IdPassword {
                 id: 1,
                 password: Default::default(),
+                locked: false,
             }));
 
         password_layer
             .mock_verify_password(Any, "password")
             .returns_once(Ok(PasswordState::Valid));
 
-        user_repository.mock_add_token(Any, 1).returns_once(Ok(()));
+        let stored = Arc::new(std::sync::Mutex::new(String::new()));
+        let stored_in_mock = Arc::clone(&stored);
+        user_repository
+            .mock_add_token(Any, 1, Any, Any)
+            .returns_with(move |token_hash, _, _, _| {
+                *stored_in_mock.lock().unwrap() = token_hash;
+                Ok(())
+            });
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(None));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(two_factor_repository, TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        let Ok(LoginSuccess::Token(token)) = result else {
+            panic!("expected a token");
+        };
+        assert_eq!(token.len(), 64);
+        assert_ne!(*stored.lock().unwrap(), token);
+        assert_eq!(*stored.lock().unwrap(), hash_login_token(&token));
+    }
+
+    #[test]
+    fn test_validate_login_rehashed_password_persisted() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: false,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::ValidRehashed(Password::Version1 {
+                argon2: "argon2".to_string(),
+            })));
+
+        user_repository
+            .mock_update_password(1, Any)
+            .returns_once(Ok(()));
+        user_repository
+            .mock_add_token(Any, 1, Any, Any)
+            .returns_once(Ok(()));
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(None));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(two_factor_repository, TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_login_rehash_failure_still_logs_in() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: false,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::ValidRehashed(Password::Version1 {
+                argon2: "argon2".to_string(),
+            })));
+
+        user_repository
+            .mock_update_password(1, Any)
+            .returns_once(Err(Report::new(UserRepositoryError::QueryError)));
+        user_repository
+            .mock_add_token(Any, 1, Any, Any)
+            .returns_once(Ok(()));
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(None));
 
-        let service = UserLoginService::new(user_repository, password_layer, None);
-        let str = service.validate_login("hello".to_string(), "password".to_string());
-        assert!(str.is_some());
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(two_factor_repository, TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -111,14 +581,165 @@ mod tests {
             .mock_get_user_password("hello".to_string())
             .returns_once(Err(Report::new(UserRepositoryError::QueryError)));
 
-        let service = UserLoginService::new(user_repository, password_layer, None);
-        let str = service.validate_login("hello".to_string(), "password".to_string());
-        assert!(str.is_none());
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::Unavailable));
+    }
+
+    #[test]
+    fn test_validate_login_unknown_username_records_failure() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = login_attempt_repository_without_failures();
+        let password_layer = PasswordLayer::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Err(Report::new(UserRepositoryError::NotFoundError)));
+
+        login_attempt_repository
+            .mock_record_failure(LOGIN_ATTEMPT_SCOPE_USERNAME, "hello".to_string(), Any, Any)
+            .returns_once(Ok(1));
+        login_attempt_repository
+            .mock_record_failure(LOGIN_ATTEMPT_SCOPE_IP, "127.0.0.1".to_string(), Any, Any)
+            .returns_once(Ok(1));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::InvalidCredentials));
     }
 
     #[test]
     fn test_validate_login_password_verify_fail() {
         let mut user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = login_attempt_repository_without_failures();
+        let mut password_layer = PasswordLayer::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: false,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::Invalid));
+
+        login_attempt_repository
+            .mock_record_failure(Any, Any, Any, Any)
+            .returns_with(|_, _, _, _| Ok(1));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::InvalidCredentials));
+    }
+
+    #[test]
+    fn test_validate_login_locks_account_at_threshold() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = login_attempt_repository_without_failures();
+        let mut password_layer = PasswordLayer::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: false,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::Invalid));
+
+        login_attempt_repository
+            .mock_record_failure(Any, Any, Any, Any)
+            .returns_with(|_, _, _, _| Ok(LoginThrottleConfig::default().lockout_threshold));
+        let locked = Arc::new(std::sync::Mutex::new(false));
+        let locked_in_mock = Arc::clone(&locked);
+        user_repository.mock_lock_user(1).returns_with(move |_| {
+            *locked_in_mock.lock().unwrap() = true;
+            Ok(())
+        });
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::InvalidCredentials));
+        assert!(*locked.lock().unwrap());
+    }
+
+    #[test]
+    fn test_validate_login_locked_account() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: true,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::Valid));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::Locked));
+    }
+
+    #[test]
+    fn test_validate_login_locked_account_wrong_password() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = login_attempt_repository_without_failures();
         let mut password_layer = PasswordLayer::new_mock();
 
         user_repository
@@ -126,27 +747,290 @@ mod tests {
             .returns_once(Ok(IdPassword {
                 id: 1,
                 password: Default::default(),
+                locked: true,
             }));
 
         password_layer
             .mock_verify_password(Any, "password")
             .returns_once(Ok(PasswordState::Invalid));
 
-        let service = UserLoginService::new(user_repository, password_layer, None);
-        let str = service.validate_login("hello".to_string(), "password".to_string());
-        assert!(str.is_none());
+        login_attempt_repository
+            .mock_record_failure(Any, Any, Any, Any)
+            .returns_with(|_, _, _, _| Ok(1));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::InvalidCredentials));
+    }
+
+    #[test]
+    fn test_validate_login_throttled() {
+        let user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = LoginAttemptRepository::new_mock();
+        let password_layer = PasswordLayer::new_mock();
+
+        login_attempt_repository
+            .mock_fetch_attempt(LOGIN_ATTEMPT_SCOPE_USERNAME, "hello".to_string())
+            .returns_once(Ok(Some(LoginAttempt {
+                failed_count: 4,
+                last_failed_at: Utc::now(),
+            })));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert!(matches!(result, Err(LoginFailure::Throttled { .. })));
+    }
+
+    #[test]
+    fn test_validate_login_ip_locked_out() {
+        let user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = LoginAttemptRepository::new_mock();
+        let password_layer = PasswordLayer::new_mock();
+
+        login_attempt_repository
+            .mock_fetch_attempt(LOGIN_ATTEMPT_SCOPE_USERNAME, "hello".to_string())
+            .returns_once(Ok(None));
+        login_attempt_repository
+            .mock_fetch_attempt(LOGIN_ATTEMPT_SCOPE_IP, "127.0.0.1".to_string())
+            .returns_once(Ok(Some(LoginAttempt {
+                failed_count: LoginThrottleConfig::default().ip_lockout_threshold,
+                last_failed_at: Utc::now() - TimeDelta::hours(1),
+            })));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        let Err(LoginFailure::Throttled { retry_after_secs }) = result else {
+            panic!("expected the client ip to be locked out");
+        };
+        assert!(retry_after_secs > LoginThrottleConfig::default().backoff_max_secs);
+    }
+
+    #[test]
+    fn test_validate_login_two_factor_required() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut password_layer = PasswordLayer::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        user_repository
+            .mock_get_user_password("hello".to_string())
+            .returns_once(Ok(IdPassword {
+                id: 1,
+                password: Default::default(),
+                locked: false,
+            }));
+
+        password_layer
+            .mock_verify_password(Any, "password")
+            .returns_once(Ok(PasswordState::Valid));
+
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(Some(TwoFactorSecret {
+                secret: Default::default(),
+                enabled: true,
+            })));
+        two_factor_repository
+            .mock_count_recovery_codes(1)
+            .returns_once(Ok(10));
+        two_factor_layer
+            .mock_encrypt(Any)
+            .returns_once(Ok(Default::default()));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(two_factor_repository, two_factor_layer),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert!(matches!(result, Ok(LoginSuccess::TwoFactorRequired(_))));
+    }
+
+    #[test]
+    fn test_validate_two_factor_success() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok(serde_json::to_string(&TwoFactorPending {
+                user_id: 1,
+                username: "hello".to_string(),
+                expires_at: Utc::now() + TimeDelta::minutes(5),
+            })
+            .unwrap()));
+        user_repository.mock_is_locked(1).returns_once(Ok(false));
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(Some(TwoFactorSecret {
+                secret: Default::default(),
+                enabled: true,
+            })));
+        two_factor_repository
+            .mock_use_recovery_code(1, Any)
+            .returns_once(Ok(true));
+        user_repository
+            .mock_add_token(Any, 1, Any, Any)
+            .returns_once(Ok(()));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            PasswordLayer::new_mock(),
+            TwoFactorService::new(two_factor_repository, two_factor_layer),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_two_factor(Default::default(), "aaaaa-bbbbb".to_string());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_two_factor_wrong_code_records_failure() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut login_attempt_repository = login_attempt_repository_without_failures();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok(serde_json::to_string(&TwoFactorPending {
+                user_id: 1,
+                username: "hello".to_string(),
+                expires_at: Utc::now() + TimeDelta::minutes(5),
+            })
+            .unwrap()));
+        user_repository.mock_is_locked(1).returns_once(Ok(false));
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(Some(TwoFactorSecret {
+                secret: Default::default(),
+                enabled: true,
+            })));
+        two_factor_repository
+            .mock_use_recovery_code(1, Any)
+            .returns_once(Ok(false));
+        login_attempt_repository
+            .mock_record_failure(Any, Any, Any, Any)
+            .returns_with(|_, _, _, _| Ok(1));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository,
+            PasswordLayer::new_mock(),
+            TwoFactorService::new(two_factor_repository, two_factor_layer),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_two_factor(Default::default(), "aaaaa-bbbbb".to_string());
+        assert_eq!(result, Err(LoginFailure::InvalidCredentials));
+    }
+
+    #[test]
+    fn test_validate_two_factor_locked_since_password_step() {
+        let mut user_repository = UserRepository::new_mock();
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok(serde_json::to_string(&TwoFactorPending {
+                user_id: 1,
+                username: "hello".to_string(),
+                expires_at: Utc::now() + TimeDelta::minutes(5),
+            })
+            .unwrap()));
+        user_repository.mock_is_locked(1).returns_once(Ok(true));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            PasswordLayer::new_mock(),
+            TwoFactorService::new(TwoFactorRepository::new_mock(), two_factor_layer),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_two_factor(Default::default(), "aaaaa-bbbbb".to_string());
+        assert_eq!(result, Err(LoginFailure::Locked));
+    }
+
+    #[test]
+    fn test_validate_two_factor_expired() {
+        let mut two_factor_layer = TwoFactorLayer::new_mock();
+
+        two_factor_layer
+            .mock_decrypt(Any)
+            .returns_once(Ok(serde_json::to_string(&TwoFactorPending {
+                user_id: 1,
+                username: "hello".to_string(),
+                expires_at: Utc::now() - TimeDelta::seconds(1),
+            })
+            .unwrap()));
+
+        let service = UserLoginService::new(
+            UserRepository::new_mock(),
+            LoginAttemptRepository::new_mock(),
+            PasswordLayer::new_mock(),
+            TwoFactorService::new(TwoFactorRepository::new_mock(), two_factor_layer),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_two_factor(Default::default(), "123456".to_string());
+        assert_eq!(result, Err(LoginFailure::Expired));
     }
 
     #[test]
     fn test_validate_login_add_token_fail() {
         let mut user_repository = UserRepository::new_mock();
         let mut password_layer = PasswordLayer::new_mock();
+        let mut two_factor_repository = TwoFactorRepository::new_mock();
 
         user_repository
             .mock_get_user_password("hello".to_string())
             .returns_once(Ok(IdPassword {
                 id: 1,
                 password: Default::default(),
+                locked: false,
             }));
 
         password_layer
@@ -154,12 +1038,25 @@ mod tests {
             .returns_once(Ok(PasswordState::Valid));
 
         user_repository
-            .mock_add_token(Any, 1)
+            .mock_add_token(Any, 1, Any, Any)
             .returns_once(Err(Report::new(UserRepositoryError::QueryError)));
 
-        let service = UserLoginService::new(user_repository, password_layer, None);
-        let str = service.validate_login("hello".to_string(), "password".to_string());
-        assert!(str.is_none());
+        two_factor_repository
+            .mock_fetch_two_factor(1)
+            .returns_once(Ok(None));
+
+        let service = UserLoginService::new(
+            user_repository,
+            login_attempt_repository_without_failures(),
+            password_layer,
+            TwoFactorService::new(two_factor_repository, TwoFactorLayer::new_mock()),
+            Default::default(),
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+        );
+        let result = service.validate_login("hello".to_string(), "password".to_string());
+        assert_eq!(result, Err(LoginFailure::Unavailable));
     }
 
     #[test]
@@ -168,11 +1065,19 @@ mod tests {
         let password_layer = PasswordLayer::new_mock();
 
         user_repository
-            .mock_delete_token("hello".to_string())
+            .mock_delete_token(hash_login_token("hello"))
             .returns_once(Ok(()));
 
-        let service =
-            UserLoginService::new(user_repository, password_layer, Some("hello".to_string()));
+        let service = UserLoginService::new(
+            user_repository,
+            LoginAttemptRepository::new_mock(),
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            None,
+            None,
+            Some("hello".to_string()),
+        );
         let result = service.logout();
         assert_eq!(result, true);
     }
@@ -183,11 +1088,19 @@ mod tests {
         let password_layer = PasswordLayer::new_mock();
 
         user_repository
-            .mock_delete_token("hello".to_string())
+            .mock_delete_token(hash_login_token("hello"))
             .returns_once(Err(Report::new(UserRepositoryError::QueryError)));
 
-        let service =
-            UserLoginService::new(user_repository, password_layer, Some("hello".to_string()));
+        let service = UserLoginService::new(
+            user_repository,
+            LoginAttemptRepository::new_mock(),
+            password_layer,
+            TwoFactorService::new(TwoFactorRepository::new_mock(), TwoFactorLayer::new_mock()),
+            Default::default(),
+            None,
+            None,
+            Some("hello".to_string()),
+        );
         let result = service.logout();
         assert_eq!(result, false);
     }
@@ -0,0 +1,6 @@
+use shared::utils::db::migration::Migrations;
+
+pub static PUBLIC_MIGRATIONS: Migrations = Migrations {
+    scope: "public",
+    migrations: &[],
+};
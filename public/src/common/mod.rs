@@ -2,3 +2,4 @@ pub mod embed;
 pub mod html;
 pub mod js;
 pub mod locale;
+pub mod migration;
@@ -1,34 +1,58 @@
 pub(crate) mod common;
 pub(crate) mod home;
+pub(crate) mod shorty;
 
 use crate::common::embed::{AssetFilesEndPoint, EMBED_PATH};
+use crate::common::html::HtmlBuilder;
 use crate::common::locale::build_locale_resources;
 use crate::home::route::home_route;
+use crate::shorty::route::{SHORTY_PATH, shorty_route};
 use error_stack::{Report, ResultExt};
+use maud::html;
+use poem::i18n::Locale;
 use poem::middleware::CatchPanic;
-use poem::{EndpointExt, IntoResponse, Server};
-use shared::utils::config::Config;
+use poem::{Endpoint, EndpointExt, FromRequest, IntoResponse, Request, Response, Server};
+use shared::utils::config::{Config, ConfigPointer};
+use shared::utils::context::fetch_context;
 use shared::utils::embed::enforce_min_js_on_prod;
 use shared::utils::error::boot_error::MainError;
-use shared::utils::log::log_poem_error;
+use shared::utils::error::error_page::internal_error_page;
+use shared::utils::log::access_log::access_log;
+use shared::utils::log::model::RequestContext;
+use shared::utils::log::panic::catch_panic;
+use shared::utils::log::scope::log_scope;
+use shared::utils::log::{log_panic, log_poem_error};
 use shared::utils::request_cache::init_request_cache;
+use shared::utils::request_id::init_request_id;
+use std::sync::Arc;
+
+pub mod export {
+    pub use crate::common::migration::PUBLIC_MIGRATIONS;
+}
 
 pub async fn boot() -> Result<(), Report<MainError>> {
     let config = Config::fetch()
         .await
         .change_context(MainError::ConfigError)?;
+    let config_pointer: ConfigPointer = fetch_context()
+        .await
+        .change_context(MainError::ConfigError)?;
 
     let route = home_route();
 
-    let route = route.nest(
+    let route = route.at(SHORTY_PATH, shorty_route()).nest(
         EMBED_PATH,
         enforce_min_js_on_prod(AssetFilesEndPoint::new()),
     );
 
-    let route = route
+    // The request id, cache and locale layers that `catch_all_error` reads stay outside it.
+    let route = route.around(catch_all_error);
+
+    let route = log_scope(SERVER_NAME, access_log(route))
+        .around(init_request_id)
         .around(init_request_cache)
         .data(build_locale_resources().change_context(MainError::LocaleError)?)
-        .catch_all_error(catch_all_error)
+        .data(Arc::clone(&config_pointer.poem_public))
         .with(CatchPanic::new());
 
     match config.upgrade() {
@@ -48,7 +72,26 @@ pub async fn boot() -> Result<(), Report<MainError>> {
     }
 }
 
-async fn catch_all_error(err: poem::Error) -> impl IntoResponse {
-    log_poem_error(&err).await;
-    err.into_response()
+const SERVER_NAME: &str = "public";
+
+async fn catch_all_error<EP: Endpoint>(next: EP, req: Request) -> poem::Result<Response> {
+    let request_context = RequestContext::new(SERVER_NAME, &req);
+    let locale = Locale::from_request_without_body(&req).await.ok();
+    match catch_panic(next.call(req)).await {
+        Ok(Ok(resp)) => Ok(resp.into_response()),
+        Ok(Err(err)) => {
+            log_poem_error(&err, &request_context).await;
+            Ok(err.into_response())
+        }
+        Err(log_data) => {
+            log_panic(&log_data, &request_context).await;
+            Ok(internal_error_page(
+                locale.as_ref(),
+                request_context.request_id.as_deref(),
+                |title, content| {
+                    HtmlBuilder::new(title, html! { div .home-content { (content) } }).build()
+                },
+            ))
+        }
+    }
 }
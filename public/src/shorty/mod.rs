@@ -0,0 +1,3 @@
+pub mod repository;
+pub mod route;
+pub mod service;
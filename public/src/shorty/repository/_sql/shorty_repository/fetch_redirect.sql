@@ -0,0 +1,4 @@
+select url_redirect
+from shorty_urls
+where url_path = :url_path
+limit 1;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod shorty_repository;
@@ -0,0 +1,80 @@
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use rusqlite::{Connection, OptionalExtension, named_params};
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::db::{BorrowConnectionExt, SqliteClient};
+use std::sync::MutexGuard;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ShortyRepositoryError {
+    #[error("Query Error")]
+    QueryError,
+    #[error("Row Value Error")]
+    RowValueError,
+    #[error("Borrow Conn Error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct ShortyRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl ShortyRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(&'_ self) -> Result<MutexGuard<'_, Connection>, Report<ShortyRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(ShortyRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl ShortyRepository {
+    pub fn fetch_redirect(
+        &self,
+        url_path: String,
+    ) -> Result<Option<String>, Report<ShortyRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/shorty_repository/fetch_redirect.sql"))
+            .change_context(ShortyRepositoryError::QueryError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let row: Option<String> = stmt
+            .query_one(
+                named_params! {
+                    ":url_path": url_path,
+                },
+                |row| row.get("url_redirect"),
+            )
+            .optional()
+            .change_context(ShortyRepositoryError::RowValueError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+impl ShortyRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for ShortyRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
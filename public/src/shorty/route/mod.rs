@@ -0,0 +1,22 @@
+use crate::shorty::service::shorty_service::ShortyService;
+use poem::web::{Path, Redirect};
+use poem::{Endpoint, get, handler};
+use shared::utils::context::Dep;
+use shared::utils::error::FromErrorStack;
+
+pub const SHORTY_PATH: &str = "/:url_path";
+
+#[handler]
+fn shorty_redirect(
+    Dep(shorty_service): Dep<ShortyService>,
+    Path(url_path): Path<String>,
+) -> poem::Result<Redirect> {
+    let url_redirect = shorty_service
+        .fetch_redirect(&url_path)
+        .map_err(poem::Error::from_error_stack)?;
+    Ok(Redirect::temporary(url_redirect))
+}
+
+pub fn shorty_route() -> impl Endpoint {
+    get(shorty_redirect)
+}
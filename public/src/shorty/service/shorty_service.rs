@@ -0,0 +1,83 @@
+use crate::shorty::repository::shorty_repository::ShortyRepository;
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use shared::utils::context::{Context, ContextError, FromContext};
+use shared::utils::error::ExtraResultExt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ShortyServiceError {
+    #[error("DB error")]
+    DbError,
+    #[error("Not found")]
+    NotFound,
+}
+
+pub struct ShortyService {
+    shorty_repository: ShortyRepository,
+}
+
+impl ShortyService {
+    pub fn new(shorty_repository: ShortyRepository) -> Self {
+        Self { shorty_repository }
+    }
+
+    pub fn fetch_redirect(&self, url_path: &str) -> Result<String, Report<ShortyServiceError>> {
+        self.shorty_repository
+            .fetch_redirect(url_path.to_string())
+            .change_context(ShortyServiceError::DbError)
+            .log_it()?
+            .ok_or_else(|| Report::new(ShortyServiceError::NotFound).attach(StatusCode::NOT_FOUND))
+    }
+}
+
+impl FromContext for ShortyService {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shorty::repository::shorty_repository::ShortyRepositoryError;
+
+    #[test]
+    fn test_shorty_service_fetch_redirect_success() {
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository
+            .mock_fetch_redirect("hello-world".to_string())
+            .returns_once(Ok(Some("https://example.com/".to_string())));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.fetch_redirect("hello-world");
+        assert_eq!(result.ok(), Some("https://example.com/".to_string()));
+    }
+
+    #[test]
+    fn test_shorty_service_fetch_redirect_not_found() {
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository
+            .mock_fetch_redirect("hello-world".to_string())
+            .returns_once(Ok(None));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.fetch_redirect("hello-world");
+        assert!(result.is_err());
+        let result = result.err().unwrap();
+        let error_code = result.downcast_ref::<StatusCode>().unwrap();
+        assert_eq!(error_code, &StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_shorty_service_fetch_redirect_error() {
+        let mut shorty_repository = ShortyRepository::new_mock();
+        shorty_repository
+            .mock_fetch_redirect("hello-world".to_string())
+            .returns_once(Err(Report::new(ShortyRepositoryError::QueryError)));
+
+        let shorty_service = ShortyService::new(shorty_repository);
+        let result = shorty_service.fetch_redirect("hello-world");
+        assert!(result.is_err());
+    }
+}
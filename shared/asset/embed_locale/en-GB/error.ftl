@@ -0,0 +1,5 @@
+# Error
+error-internal-title = Something went wrong
+error-internal-message = The error has been logged, please try again later.
+error-internal-home = Back to home
+error-internal-request-id = Request ID:
@@ -0,0 +1,142 @@
+use crate::utils::config::ConfigPointer;
+use crate::utils::config::cipher::CipherConfig;
+use crate::utils::context::{Context, ContextError, FromContext};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use error_stack::{Report, ResultExt};
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::OnceCell;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error)]
+pub enum CipherError {
+    #[error("Key file error")]
+    KeyFileError,
+    #[error("Invalid key")]
+    InvalidKey,
+    #[error("Encrypt error")]
+    EncryptError,
+    #[error("Decrypt error")]
+    DecryptError,
+}
+
+/// AES-256-GCM for values that must be readable again, such as TOTP secrets.
+///
+/// Ciphertexts are stored as `nonce || ciphertext`.
+#[derive(Clone)]
+pub struct SecretCipher(Arc<Aes256Gcm>);
+
+impl SecretCipher {
+    pub fn new(key: &[u8; KEY_LEN]) -> Self {
+        Self(Arc::new(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))))
+    }
+
+    /// Loads the key from `config.key_file`, writing a fresh random key when the file
+    /// does not exist yet.
+    pub fn from_config(config: &CipherConfig) -> Result<Self, Report<CipherError>> {
+        if let Some(cipher) = Self::read_key_file(config)? {
+            return Ok(cipher);
+        }
+        let key = Aes256Gcm::generate_key(&mut OsRng);
+        write_key_file(&config.key_file, &encode_hex(&key))
+            .change_context(CipherError::KeyFileError)
+            .attach_with(|| format!("key file: {}", config.key_file))?;
+        Ok(Self(Arc::new(Aes256Gcm::new(&key))))
+    }
+
+    /// Loads the key from `config.key_file`, `None` when the file does not exist yet.
+    pub fn read_key_file(config: &CipherConfig) -> Result<Option<Self>, Report<CipherError>> {
+        let key_hex = match fs::read_to_string(&config.key_file) {
+            Ok(key_hex) => key_hex,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Report::new(err)
+                    .change_context(CipherError::KeyFileError)
+                    .attach(format!("key file: {}", config.key_file)));
+            }
+        };
+
+        let key: [u8; KEY_LEN] = decode_hex(key_hex.trim())
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| Report::new(CipherError::InvalidKey))
+            .attach_with(|| format!("key file: {}", config.key_file))?;
+        Ok(Some(Self::new(&key)))
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Box<[u8]>, Report<CipherError>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| Report::new(CipherError::EncryptError))?;
+
+        let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        output.extend_from_slice(&nonce);
+        output.extend_from_slice(&ciphertext);
+        Ok(output.into())
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Report<CipherError>> {
+        if data.len() < NONCE_LEN {
+            return Err(Report::new(CipherError::DecryptError));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        self.0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Report::new(CipherError::DecryptError))
+    }
+}
+
+/// Fills a buffer from the operating system CSPRNG.
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn write_key_file(path: &str, key_hex: &str) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    std::io::Write::write_all(&mut options.open(path)?, key_hex.as_bytes())
+}
+
+/// Lowercase hex, as used for key files and token digests.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Inverse of `encode_hex`, `None` for odd lengths or non hex digits.
+pub fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+static SECRET_CIPHER_CACHE: OnceCell<SecretCipher> = OnceCell::const_new();
+
+impl FromContext for SecretCipher {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let secret_cipher: Result<&Self, Report<ContextError>> = SECRET_CIPHER_CACHE
+            .get_or_try_init(|| async {
+                let config: ConfigPointer = ctx.inject().await?;
+                Self::from_config(&config.cipher).change_context(ContextError::ConfigError)
+            })
+            .await;
+        Ok(secret_cipher?.clone())
+    }
+}
@@ -0,0 +1,181 @@
+use crate::utils::config::poem::PoemConfig;
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::request_cache::RequestCacheExt;
+use error_stack::Report;
+use ipnet::IpNet;
+use poem::http::HeaderMap;
+use poem::{FromRequest, Request, RequestBody};
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Address of the client that made the request, looking through trusted reverse proxies.
+///
+/// The proxies come from the `PoemConfig` of the server, added to the request with
+/// `.data(Arc<PoemConfig>)`; without it the peer address is used as is.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClientIp(Option<IpAddr>);
+
+impl ClientIp {
+    /// Missing when the peer is not connected over TCP.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.0
+    }
+
+    fn new(req: &Request) -> Self {
+        let peer = req
+            .remote_addr()
+            .as_socket_addr()
+            .map(|addr| addr.ip().to_canonical());
+        let trusted = req
+            .data::<Arc<PoemConfig>>()
+            .map(|config| config.trusted_proxies.as_slice())
+            .unwrap_or_default();
+        Self(peer.map(|peer| resolve(peer, req.headers(), trusted)))
+    }
+}
+
+impl Display for ClientIp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(ip) => ip.fmt(f),
+            None => f.write_str("-"),
+        }
+    }
+}
+
+/// Walks the forwarding chain from the nearest hop back, for as long as the hop that
+/// reported the next address is trusted.
+///
+/// `Forwarded` is preferred over `X-Forwarded-For`, which is preferred over `X-Real-IP`.
+fn resolve(peer: IpAddr, headers: &HeaderMap, trusted: &[IpNet]) -> IpAddr {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|net| net.contains(ip));
+    if !is_trusted(&peer) {
+        return peer;
+    }
+
+    let chain = forwarded_chain(headers);
+    let mut client = peer;
+    for hop in chain.iter().rev() {
+        if !is_trusted(&client) {
+            break;
+        }
+        match parse_hop(hop) {
+            Some(ip) => client = ip,
+            None => break,
+        }
+    }
+    client
+}
+
+/// Addresses listed by the proxies, the client first.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<String> {
+    let values = |name| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|value| value.trim().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let forwarded: Vec<_> = values("forwarded")
+        .iter()
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                name.eq_ignore_ascii_case("for")
+                    .then(|| value.trim_matches('"').to_string())
+            })
+        })
+        .collect();
+    if !forwarded.is_empty() {
+        return forwarded;
+    }
+    let x_forwarded_for = values("x-forwarded-for");
+    if !x_forwarded_for.is_empty() {
+        return x_forwarded_for;
+    }
+    values("x-real-ip")
+}
+
+/// Accepts a bare address, `[v6]`, or either with a port; `unknown` and obfuscated
+/// identifiers are rejected.
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    if let Ok(ip) = hop.parse::<IpAddr>() {
+        return Some(ip.to_canonical());
+    }
+    if let Ok(addr) = hop.parse::<SocketAddr>() {
+        return Some(addr.ip().to_canonical());
+    }
+    hop.strip_prefix('[')?
+        .strip_suffix(']')?
+        .parse::<IpAddr>()
+        .ok()
+        .map(|ip| ip.to_canonical())
+}
+
+impl<'a> FromRequest<'a> for ClientIp {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+        req.get_or_init_cache(|| async { Ok(Self::new(req)) }).await
+    }
+}
+
+impl FromContext for ClientIp {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let req = ctx.req_result()?;
+        req.get_or_init_cache(|| async { Ok(Self::new(req)) }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn test_resolve_ignores_headers_from_untrusted_peer() {
+        let trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+        let headers = headers(&[("x-forwarded-for", "1.2.3.4")]);
+        assert_eq!(
+            resolve(ip("192.0.2.1"), &headers, &trusted),
+            ip("192.0.2.1")
+        );
+        assert_eq!(resolve(ip("10.0.0.2"), &headers, &[]), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn test_resolve_walks_trusted_hops() {
+        let trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+
+        let spoofed = headers(&[("x-forwarded-for", "6.6.6.6, 1.2.3.4, 10.0.0.5")]);
+        assert_eq!(resolve(ip("10.0.0.2"), &spoofed, &trusted), ip("1.2.3.4"));
+
+        let forwarded = headers(&[
+            ("forwarded", r#"for="[2001:db8::17]:4711";proto=https"#),
+            ("x-forwarded-for", "1.2.3.4"),
+        ]);
+        assert_eq!(
+            resolve(ip("10.0.0.2"), &forwarded, &trusted),
+            ip("2001:db8::17")
+        );
+
+        let real_ip = headers(&[("x-real-ip", "1.2.3.4")]);
+        assert_eq!(resolve(ip("10.0.0.2"), &real_ip, &trusted), ip("1.2.3.4"));
+
+        let hidden = headers(&[("forwarded", "for=_hidden, for=10.0.0.7")]);
+        assert_eq!(resolve(ip("10.0.0.2"), &hidden, &trusted), ip("10.0.0.7"));
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    /// NCSA common log format.
+    Common,
+    /// Common plus the referer and user agent.
+    Combined,
+    /// Combined plus the latency and `htmx` for htmx requests, not for NCSA parsers.
+    #[default]
+    Extended,
+    /// One JSON object per request.
+    Json,
+}
+
+/// Request logging of both servers, written at info level with the `access` target.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    pub format: AccessLogFormat,
+    /// Requests whose path starts with any of these are not logged.
+    pub exclude_paths: Vec<String>,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            format: AccessLogFormat::Extended,
+            exclude_paths: vec!["/assets/".to_string()],
+        }
+    }
+}
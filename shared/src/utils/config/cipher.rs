@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CipherConfig {
+    /// Hex encoded 256 bit key, created with a random key on first use when missing.
+    pub key_file: String,
+}
+
+impl Default for CipherConfig {
+    fn default() -> Self {
+        Self {
+            key_file: "./cipher.key".to_string(),
+        }
+    }
+}
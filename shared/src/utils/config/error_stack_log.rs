@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// How long `error_stack` rows are kept and listed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorStackConfig {
+    /// Used by the retention job and as the window of the stack list.
+    pub retention_days: u32,
+    /// Ages offered by the clear action on the stack page.
+    pub clear_choices_days: Vec<u32>,
+    /// Occurrences kept per error group, older ones are dropped as new ones arrive.
+    pub samples_per_group: u32,
+    /// Error groups shown per page of the stack list.
+    pub page_size: u32,
+}
+
+impl Default for ErrorStackConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            clear_choices_days: vec![7, 30, 90],
+            samples_per_group: 20,
+            page_size: 25,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// Background job scheduler, schedules are `every <n><s|m|h|d>` or five field cron (UTC).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobsConfig {
+    pub enabled: bool,
+    /// An unfinished run older than this is assumed dead and no longer blocks the next one.
+    pub stale_run_secs: u64,
+    /// Finished runs kept per job in `job_runs`.
+    pub history_per_job: u32,
+    pub session_cleanup_schedule: String,
+    pub stack_retention_schedule: String,
+    pub session_store_cleanup_schedule: String,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stale_run_secs: 3600,
+            history_per_job: 50,
+            session_cleanup_schedule: "every 1h".to_string(),
+            stack_retention_schedule: "30 3 * * *".to_string(),
+            session_store_cleanup_schedule: "every 15m".to_string(),
+        }
+    }
+}
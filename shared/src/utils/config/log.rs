@@ -0,0 +1,41 @@
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines, coloured when written to a terminal.
+    #[default]
+    Pretty,
+    /// One JSON object per line, for the log pipeline.
+    Json,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// Level of any module not listed in `modules`.
+    pub level: LevelFilter,
+    /// Levels by module path prefix, the longest matching prefix wins.
+    pub modules: BTreeMap<String, LevelFilter>,
+    /// Also writes every record to this file, empty to only log to stderr.
+    pub file_path: String,
+    /// The file is rotated to `<file_path>.1` once it would grow past this size.
+    pub file_max_bytes: u64,
+    /// Rotated files kept next to the current one.
+    pub file_max_files: u32,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Pretty,
+            level: LevelFilter::Info,
+            modules: BTreeMap::new(),
+            file_path: "".to_string(),
+            file_max_bytes: 10 * 1024 * 1024,
+            file_max_files: 5,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// Back-off and lockout policy for failed backoffice logins.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginThrottleConfig {
+    /// Failed attempts on one username before the account is locked.
+    pub lockout_threshold: u32,
+    /// Failed attempts from one client IP, across usernames, before that IP is
+    /// refused until its failures reset.
+    pub ip_lockout_threshold: u32,
+    /// Wait after the first failure, doubled on each further failure.
+    pub backoff_base_secs: u64,
+    pub backoff_max_secs: u64,
+    /// Failures older than this no longer count.
+    pub reset_after_secs: u64,
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        Self {
+            lockout_threshold: 10,
+            ip_lockout_threshold: 50,
+            backoff_base_secs: 1,
+            backoff_max_secs: 300,
+            reset_after_secs: 86400,
+        }
+    }
+}
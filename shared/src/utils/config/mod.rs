@@ -1,30 +1,64 @@
+use self::log::LogConfig;
 use crate::utils::context::{Context, ContextError, FromContext};
+use access_log::AccessLogConfig;
+use cipher::CipherConfig;
 use error_stack::{FutureExt, Report, ResultExt};
+use error_stack_log::ErrorStackConfig;
 use figment::providers::{Format, Serialized, Toml};
 use figment::{Figment, Profile};
+use jobs::JobsConfig;
+use login_throttle::LoginThrottleConfig;
+use password::PasswordConfig;
 use poem::PoemConfig;
 use serde::{Deserialize, Serialize};
+use session::SessionConfig;
 use sqlite::SqliteConfig;
 use std::env::var;
 use std::ops::Deref;
 use std::sync::{Arc, Weak};
 use thiserror::Error;
 use tokio::sync::OnceCell;
+use two_factor::TwoFactorConfig;
 
+pub mod access_log;
+pub mod cipher;
+pub mod error_stack_log;
+pub mod jobs;
+pub mod log;
+pub mod login_throttle;
+pub mod password;
 pub mod poem;
+pub mod session;
 pub mod sqlite;
+pub mod two_factor;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("Config did not parse")]
     ParseError,
+    #[error("Config did not serialize")]
+    SerializeError,
 }
 
+const REDACTED: &str = "[redacted]";
+/// Dotted paths of the fields holding a secret value. The session and cipher keys
+/// only appear as the file or environment variable they are read from, so none do yet.
+const SECRET_PATHS: &[&str] = &[];
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub poem_public: Arc<PoemConfig>,
     pub poem_backoffice: Arc<PoemConfig>,
     pub sqlite: Arc<SqliteConfig>,
+    pub password: Arc<PasswordConfig>,
+    pub login_throttle: Arc<LoginThrottleConfig>,
+    pub cipher: Arc<CipherConfig>,
+    pub two_factor: Arc<TwoFactorConfig>,
+    pub jobs: Arc<JobsConfig>,
+    pub error_stack: Arc<ErrorStackConfig>,
+    pub log: Arc<LogConfig>,
+    pub access_log: Arc<AccessLogConfig>,
+    pub session: Arc<SessionConfig>,
 }
 
 impl Default for Config {
@@ -34,8 +68,18 @@ impl Default for Config {
             poem_backoffice: Arc::new(PoemConfig {
                 address: "127.0.0.1".to_string(),
                 port: 8001,
+                trusted_proxies: Vec::new(),
             }),
             sqlite: Arc::new(SqliteConfig::default()),
+            password: Arc::new(PasswordConfig::default()),
+            login_throttle: Arc::new(LoginThrottleConfig::default()),
+            cipher: Arc::new(CipherConfig::default()),
+            two_factor: Arc::new(TwoFactorConfig::default()),
+            jobs: Arc::new(JobsConfig::default()),
+            error_stack: Arc::new(ErrorStackConfig::default()),
+            log: Arc::new(LogConfig::default()),
+            access_log: Arc::new(AccessLogConfig::default()),
+            session: Arc::new(SessionConfig::default()),
         }
     }
 }
@@ -79,6 +123,31 @@ impl Config {
 
         Ok(Arc::downgrade(config?))
     }
+
+    /// Renders the effective config as TOML, with the value at each of `SECRET_PATHS`
+    /// replaced by a placeholder.
+    pub fn as_redacted_toml(&self) -> Result<String, Report<ConfigError>> {
+        let mut value = toml::Value::try_from(self).change_context(ConfigError::SerializeError)?;
+        for path in SECRET_PATHS {
+            redact_path(&mut value, path);
+        }
+        toml::to_string_pretty(&value).change_context(ConfigError::SerializeError)
+    }
+}
+
+fn redact_path(value: &mut toml::Value, path: &str) {
+    let mut parts = path.split('.').peekable();
+    let mut value = value;
+    while let Some(part) = parts.next() {
+        let Some(child) = value.get_mut(part) else {
+            return;
+        };
+        if parts.peek().is_none() {
+            *child = toml::Value::String(REDACTED.to_string());
+            return;
+        }
+        value = child;
+    }
 }
 
 pub struct ConfigPointer(Arc<Config>);
@@ -107,3 +176,43 @@ impl FromContext for ConfigPointer {
         Ok(Self(config))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_redacted_toml_prints_settings() {
+        let config = Config {
+            session: Arc::new(SessionConfig {
+                key_file: "/etc/app/session.key".to_string(),
+                ..SessionConfig::default()
+            }),
+            ..Config::default()
+        };
+
+        let toml = config.as_redacted_toml().unwrap();
+        assert!(toml.contains("argon2_memory_kib = 19456"));
+        assert!(toml.contains("key_file = \"./cipher.key\""));
+        assert!(toml.contains("key_file = \"/etc/app/session.key\""));
+        assert!(toml.contains("key_env = \""));
+        assert!(!toml.contains(REDACTED));
+    }
+
+    #[test]
+    fn test_redact_path() {
+        let mut value: toml::Value = toml::from_str(
+            "[smtp]\nhost = \"mail\"\npassword = \"hunter2\"\n[password]\nmemory_kib = 1",
+        )
+        .unwrap();
+        redact_path(&mut value, "smtp.password");
+        redact_path(&mut value, "smtp.missing.token");
+        redact_path(&mut value, "missing");
+
+        let toml = toml::to_string(&value).unwrap();
+        assert!(!toml.contains("hunter2"));
+        assert!(toml.contains("password = \"[redacted]\""));
+        assert!(toml.contains("host = \"mail\""));
+        assert!(toml.contains("memory_kib = 1"));
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Argon2id cost parameters used when hashing new passwords.
+///
+/// Stored hashes made with weaker parameters are upgraded on the next successful login.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasswordConfig {
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self {
+            argon2_memory_kib: 19 * 1024,
+            argon2_iterations: 2,
+            argon2_parallelism: 1,
+        }
+    }
+}
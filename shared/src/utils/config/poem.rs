@@ -1,9 +1,13 @@
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PoemConfig {
     pub address: String,
     pub port: u16,
+    /// Reverse proxies whose forwarding headers are believed, as CIDR blocks such as
+    /// `10.0.0.0/8` or `127.0.0.1/32`. Empty ignores the headers.
+    pub trusted_proxies: Vec<IpNet>,
 }
 
 impl Default for PoemConfig {
@@ -11,6 +15,7 @@ impl Default for PoemConfig {
         Self {
             address: "127.0.0.1".to_string(),
             port: 8000,
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionSecurity {
+    /// Encrypted, the browser can neither read nor change the session.
+    #[default]
+    Private,
+    /// Readable by the browser, but any change invalidates the session.
+    Signed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionSameSite {
+    Strict,
+    #[default]
+    Lax,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStore {
+    /// The whole session travels in the cookie.
+    #[default]
+    Cookie,
+    /// The cookie only carries an id, the session lives in the `sessions` table.
+    Sqlite,
+}
+
+/// The backoffice session cookie, which carries flash messages and pending logins.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Environment variable holding the hex encoded 512 bit key, checked before `key_file`.
+    pub key_env: String,
+    /// File holding the hex encoded key, "" to only read `key_env`.
+    pub key_file: String,
+    pub security: SessionSecurity,
+    pub cookie_name: String,
+    pub same_site: SessionSameSite,
+    pub secure: bool,
+    /// Max-Age of the cookie, 0 to keep it until the browser closes.
+    pub ttl_secs: u64,
+    pub store: SessionStore,
+    /// A `sqlite` session unused for this long expires.
+    pub idle_timeout_secs: u64,
+    /// A `sqlite` session expires this long after it was created, however active.
+    pub absolute_timeout_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            key_env: format!(
+                "{}_SESSION_KEY",
+                option_env!("ENV_PROJECT_NAME_SHOUT_SNAKE").unwrap_or("APP")
+            ),
+            key_file: String::new(),
+            security: SessionSecurity::Private,
+            cookie_name: "session".to_string(),
+            same_site: SessionSameSite::Lax,
+            secure: true,
+            ttl_secs: 0,
+            store: SessionStore::Cookie,
+            idle_timeout_secs: 7200,
+            absolute_timeout_secs: 86400,
+        }
+    }
+}
@@ -3,12 +3,19 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SqliteConfig {
     pub path: String,
+    pub pool_size: u32,
+    pub busy_timeout_ms: u64,
+    /// How long `borrow_conn` waits for a free connection, blocking its thread meanwhile.
+    pub acquire_timeout_ms: u64,
 }
 
 impl Default for SqliteConfig {
     fn default() -> Self {
         Self {
             path: "./sqlite.db".to_string(),
+            pool_size: 8,
+            busy_timeout_ms: 5000,
+            acquire_timeout_ms: 5000,
         }
     }
 }
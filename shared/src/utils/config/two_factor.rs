@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorConfig {
+    /// Shown by authenticator apps next to the account name.
+    pub issuer: String,
+    pub recovery_code_count: usize,
+    /// How long the second login step stays open after the password was accepted.
+    pub pending_login_secs: u64,
+}
+
+impl Default for TwoFactorConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "Backoffice".to_string(),
+            recovery_code_count: 10,
+            pending_login_secs: 300,
+        }
+    }
+}
@@ -1,2 +0,0 @@
-insert into backoffice_users (username, password, role)
-values (:username, :password, 'root')
\ No newline at end of file
@@ -0,0 +1,2 @@
+insert into schema_migrations (scope, version, name, applied_at)
+values (:scope, :version, :name, :applied_at)
@@ -0,0 +1,3 @@
+select coalesce(max(version), 0) as version
+from schema_migrations
+where scope = :scope
@@ -0,0 +1,8 @@
+create table if not exists schema_migrations
+(
+    scope      text    not null,
+    version    integer not null,
+    name       text    not null,
+    applied_at text    not null,
+    primary key (scope, version)
+);
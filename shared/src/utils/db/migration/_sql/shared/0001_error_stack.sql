@@ -0,0 +1,8 @@
+create table if not exists error_stack
+(
+    id            integer primary key autoincrement not null,
+    error_name    text                              not null,
+    error_summary text                              not null,
+    error_stack   text                              not null,
+    reported_at   text                              not null
+);
@@ -0,0 +1,11 @@
+create table job_runs
+(
+    id          integer primary key autoincrement not null,
+    job_name    text                              not null,
+    started_at  text                              not null,
+    finished_at text,
+    status      text                              not null,
+    message     text
+);
+
+create index job_runs_job_name on job_runs (job_name, id);
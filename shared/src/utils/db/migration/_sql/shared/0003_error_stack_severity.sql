@@ -0,0 +1,4 @@
+alter table error_stack
+    add column severity text not null default 'error';
+
+create index error_stack_reported_at on error_stack (reported_at);
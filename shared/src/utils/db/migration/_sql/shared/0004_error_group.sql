@@ -0,0 +1,36 @@
+create table if not exists error_group
+(
+    id            integer primary key autoincrement not null,
+    fingerprint   text                              not null unique,
+    severity      text                              not null,
+    error_name    text                              not null,
+    error_summary text                              not null,
+    first_seen    text                              not null,
+    last_seen     text                              not null,
+    occurrences   integer                           not null
+);
+
+create index error_group_last_seen on error_group (last_seen);
+
+alter table error_stack
+    add column group_id integer references error_group (id) on delete cascade;
+
+-- Rows logged before fingerprinting are grouped by name and summary,
+-- min(severity) picks the most severe as the names sort critical < error < warning.
+insert into error_group (fingerprint, severity, error_name, error_summary, first_seen, last_seen, occurrences)
+select 'legacy:' || error_name || ':' || error_summary,
+       min(severity),
+       error_name,
+       error_summary,
+       min(reported_at),
+       max(reported_at),
+       count(*)
+from error_stack
+group by error_name, error_summary;
+
+update error_stack
+set group_id = (select id
+                from error_group
+                where fingerprint = 'legacy:' || error_stack.error_name || ':' || error_stack.error_summary);
+
+create index error_stack_group_id on error_stack (group_id, id);
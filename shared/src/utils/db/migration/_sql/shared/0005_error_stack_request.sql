@@ -0,0 +1,14 @@
+alter table error_stack
+    add column server text;
+alter table error_stack
+    add column method text;
+alter table error_stack
+    add column path text;
+alter table error_stack
+    add column query text;
+alter table error_stack
+    add column user_id integer;
+alter table error_stack
+    add column htmx text;
+alter table error_stack
+    add column headers text;
@@ -0,0 +1,4 @@
+alter table error_stack
+    add column request_id text;
+
+create index error_stack_request_id on error_stack (request_id);
@@ -0,0 +1,18 @@
+alter table error_group
+    add column status text not null default 'new';
+
+create index error_group_status on error_group (status, last_seen);
+
+-- Who changed the status of a group and why, a change made because a resolved
+-- group happened again has no user.
+create table if not exists error_group_status_change
+(
+    id         integer primary key autoincrement not null,
+    group_id   integer                           not null references error_group (id) on delete cascade,
+    status     text                              not null,
+    user_id    integer,
+    note       text,
+    changed_at text                              not null
+);
+
+create index error_group_status_change_group_id on error_group_status_change (group_id, id);
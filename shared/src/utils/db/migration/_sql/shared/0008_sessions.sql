@@ -0,0 +1,11 @@
+create table sessions
+(
+    id_hash               text primary key not null,
+    entries               text             not null,
+    created_at            text             not null,
+    last_seen_at          text             not null,
+    expire_after          text             not null,
+    absolute_expire_after text             not null
+);
+
+create index sessions_expire_after on sessions (expire_after);
@@ -0,0 +1,4 @@
+-- The username is kept with the change, this table is shared and can't join the
+-- users of a crate, and the name stays readable after the user is removed.
+alter table error_group_status_change
+    add column username text;
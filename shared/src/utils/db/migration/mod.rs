@@ -0,0 +1,400 @@
+use crate::utils::context::fetch_context;
+use crate::utils::db::{BorrowConnectionExt, ConnectionMarker, SqliteClient};
+use chrono::Utc;
+use error_stack::{Report, ResultExt};
+use log::info;
+use rusqlite::{Connection, TransactionBehavior, named_params};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("Borrow Conn error")]
+    BorrowConnError,
+    #[error("Query error")]
+    QueryError,
+    #[error("Migration `{scope}` version {version} is out of order")]
+    OutOfOrder { scope: &'static str, version: u32 },
+    #[error(
+        "Database `{scope}` schema is at version {db_version}, but this binary only knows up to version {binary_version}"
+    )]
+    DatabaseNewer {
+        scope: &'static str,
+        db_version: u32,
+        binary_version: u32,
+    },
+    #[error("Migration `{scope}` version {version} ({name}) failed")]
+    MigrationFailed {
+        scope: &'static str,
+        version: u32,
+        name: &'static str,
+    },
+}
+
+/// A single embedded schema change, applied at most once per database.
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Ordered list of migrations owned by one crate.
+///
+/// Versions are tracked per `scope`, so every crate numbers its own migrations from 1.
+pub struct Migrations {
+    pub scope: &'static str,
+    pub migrations: &'static [Migration],
+}
+
+impl Migrations {
+    pub fn latest_version(&self) -> u32 {
+        self.migrations
+            .iter()
+            .map(|migration| migration.version)
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn check_order(&self) -> Result<(), Report<MigrationError>> {
+        let mut previous = 0;
+        for migration in self.migrations {
+            if migration.version <= previous {
+                return Err(Report::new(MigrationError::OutOfOrder {
+                    scope: self.scope,
+                    version: migration.version,
+                }));
+            }
+            previous = migration.version;
+        }
+        Ok(())
+    }
+}
+
+pub static SHARED_MIGRATIONS: Migrations = Migrations {
+    scope: "shared",
+    migrations: &[
+        Migration {
+            version: 1,
+            name: "error_stack",
+            sql: include_str!("_sql/shared/0001_error_stack.sql"),
+        },
+        Migration {
+            version: 2,
+            name: "job_runs",
+            sql: include_str!("_sql/shared/0002_job_runs.sql"),
+        },
+        Migration {
+            version: 3,
+            name: "error_stack_severity",
+            sql: include_str!("_sql/shared/0003_error_stack_severity.sql"),
+        },
+        Migration {
+            version: 4,
+            name: "error_group",
+            sql: include_str!("_sql/shared/0004_error_group.sql"),
+        },
+        Migration {
+            version: 5,
+            name: "error_stack_request",
+            sql: include_str!("_sql/shared/0005_error_stack_request.sql"),
+        },
+        Migration {
+            version: 6,
+            name: "error_stack_request_id",
+            sql: include_str!("_sql/shared/0006_error_stack_request_id.sql"),
+        },
+        Migration {
+            version: 7,
+            name: "error_group_status",
+            sql: include_str!("_sql/shared/0007_error_group_status.sql"),
+        },
+        Migration {
+            version: 8,
+            name: "sessions",
+            sql: include_str!("_sql/shared/0008_sessions.sql"),
+        },
+        Migration {
+            version: 9,
+            name: "error_group_status_username",
+            sql: include_str!("_sql/shared/0009_error_group_status_username.sql"),
+        },
+    ],
+};
+
+/// Schema version of one scope, as recorded in the database and as known by the binary.
+pub struct MigrationStatus {
+    pub scope: &'static str,
+    pub db_version: u32,
+    pub binary_version: u32,
+}
+
+impl MigrationStatus {
+    pub fn is_pending(&self) -> bool {
+        self.db_version < self.binary_version
+    }
+
+    pub fn is_newer(&self) -> bool {
+        self.db_version > self.binary_version
+    }
+}
+
+impl MigrationStatus {
+    /// Reads the recorded version of `migrations`; a database without a
+    /// `schema_migrations` table is at version 0. Never writes, so `conn` may be read-only.
+    pub fn read(
+        conn: &Connection,
+        migrations: &Migrations,
+    ) -> Result<Self, Report<MigrationError>> {
+        let tracked: bool = conn
+            .query_one(
+                include_str!("_sql/has_schema_migrations.sql"),
+                named_params! {},
+                |row| row.get("tracked"),
+            )
+            .change_context(MigrationError::QueryError)?;
+
+        let db_version: u32 = if tracked {
+            conn.query_one(
+                include_str!("_sql/current_version.sql"),
+                named_params! {
+                    ":scope": migrations.scope,
+                },
+                |row| row.get("version"),
+            )
+            .change_context(MigrationError::QueryError)?
+        } else {
+            0
+        };
+
+        Ok(Self {
+            scope: migrations.scope,
+            db_version,
+            binary_version: migrations.latest_version(),
+        })
+    }
+}
+
+impl<T: ConnectionMarker> SqliteClient<T> {
+    pub fn migration_status(
+        &self,
+        migrations: &Migrations,
+    ) -> Result<MigrationStatus, Report<MigrationError>> {
+        let conn = self
+            .borrow_conn()
+            .change_context(MigrationError::BorrowConnError)?;
+        MigrationStatus::read(&conn, migrations)
+    }
+
+    /// Applies every pending migration of `migrations` inside one transaction.
+    ///
+    /// Returns the versions that were applied.
+    pub fn migrate(&self, migrations: &Migrations) -> Result<Vec<u32>, Report<MigrationError>> {
+        migrations.check_order()?;
+
+        let mut conn = self
+            .borrow_conn()
+            .change_context(MigrationError::BorrowConnError)?;
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .change_context(MigrationError::QueryError)?;
+
+        tx.execute_batch(include_str!("_sql/schema_migrations.sql"))
+            .change_context(MigrationError::QueryError)?;
+
+        let db_version: u32 = tx
+            .query_one(
+                include_str!("_sql/current_version.sql"),
+                named_params! {
+                    ":scope": migrations.scope,
+                },
+                |row| row.get("version"),
+            )
+            .change_context(MigrationError::QueryError)?;
+
+        let binary_version = migrations.latest_version();
+        if db_version > binary_version {
+            return Err(Report::new(MigrationError::DatabaseNewer {
+                scope: migrations.scope,
+                db_version,
+                binary_version,
+            }));
+        }
+
+        let mut applied = vec![];
+        for migration in migrations
+            .migrations
+            .iter()
+            .filter(|migration| migration.version > db_version)
+        {
+            let migration_failed = || MigrationError::MigrationFailed {
+                scope: migrations.scope,
+                version: migration.version,
+                name: migration.name,
+            };
+            tx.execute_batch(migration.sql)
+                .change_context_lazy(migration_failed)?;
+            tx.execute(
+                include_str!("_sql/add_migration.sql"),
+                named_params! {
+                    ":scope": migrations.scope,
+                    ":version": migration.version,
+                    ":name": migration.name,
+                    ":applied_at": Utc::now(),
+                },
+            )
+            .change_context_lazy(migration_failed)?;
+            applied.push(migration.version);
+        }
+
+        tx.commit().change_context(MigrationError::QueryError)?;
+
+        Ok(applied)
+    }
+}
+
+/// Brings the database up to date, running the shared migrations first and then
+/// each registered crate's migrations in the given order.
+///
+/// Returns the number of migrations applied.
+pub async fn run_migrations(registered: &[&Migrations]) -> Result<usize, Report<MigrationError>> {
+    let sqlite_client: SqliteClient = fetch_context()
+        .await
+        .change_context(MigrationError::BorrowConnError)?;
+
+    let mut applied = 0;
+    for migrations in [&SHARED_MIGRATIONS].iter().chain(registered.iter()) {
+        for version in sqlite_client.migrate(migrations)? {
+            info!("Applied migration {} v{}", migrations.scope, version);
+            applied += 1;
+        }
+    }
+
+    Ok(applied)
+}
+
+/// Reports the schema version of the shared migrations and every registered crate.
+pub fn migration_statuses(
+    conn: &Connection,
+    registered: &[&Migrations],
+) -> Result<Vec<MigrationStatus>, Report<MigrationError>> {
+    [&SHARED_MIGRATIONS]
+        .iter()
+        .chain(registered.iter())
+        .map(|migrations| MigrationStatus::read(conn, migrations))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::tests::client;
+    use std::fs::remove_dir_all;
+
+    static MIGRATIONS: Migrations = Migrations {
+        scope: "test",
+        migrations: &[
+            Migration {
+                version: 1,
+                name: "create",
+                sql: "create table items (name text not null);",
+            },
+            Migration {
+                version: 2,
+                name: "seed",
+                sql: "insert into items (name) values ('first');",
+            },
+        ],
+    };
+
+    static FIRST_ONLY: Migrations = Migrations {
+        scope: "test",
+        migrations: &[Migration {
+            version: 1,
+            name: "create",
+            sql: "create table items (name text not null);",
+        }],
+    };
+
+    static OUT_OF_ORDER: Migrations = Migrations {
+        scope: "test",
+        migrations: &[
+            Migration {
+                version: 2,
+                name: "seed",
+                sql: "insert into items (name) values ('first');",
+            },
+            Migration {
+                version: 1,
+                name: "create",
+                sql: "create table items (name text not null);",
+            },
+        ],
+    };
+
+    #[test]
+    fn test_migrate_applies_in_order_once() {
+        let (client, dir) = client(1, 1000);
+
+        assert_eq!(client.migrate(&MIGRATIONS).unwrap(), vec![1, 2]);
+        assert!(client.migrate(&MIGRATIONS).unwrap().is_empty());
+
+        let items: u32 = client
+            .borrow_conn()
+            .unwrap()
+            .query_one("select count(*) from items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(items, 1);
+        let status = client.migration_status(&MIGRATIONS).unwrap();
+        assert_eq!((status.db_version, status.binary_version), (2, 2));
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_migration_statuses_read_untracked_database() {
+        let (client, dir) = client(1, 1000);
+        let conn = client.borrow_conn().unwrap();
+
+        let statuses = migration_statuses(&conn, &[&MIGRATIONS]).unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.iter().all(|status| status.db_version == 0));
+        assert!(statuses[1].is_pending());
+        let tracked: bool = conn
+            .query_one(include_str!("_sql/has_schema_migrations.sql"), [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert!(!tracked);
+        drop(conn);
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_refuses_newer_database() {
+        let (client, dir) = client(1, 1000);
+        client.migrate(&MIGRATIONS).unwrap();
+
+        let err = client.migrate(&FIRST_ONLY).unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            MigrationError::DatabaseNewer {
+                db_version: 2,
+                binary_version: 1,
+                ..
+            }
+        ));
+        assert!(client.migration_status(&FIRST_ONLY).unwrap().is_newer());
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_rejects_out_of_order_versions() {
+        let (client, dir) = client(1, 1000);
+
+        let err = client.migrate(&OUT_OF_ORDER).unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            MigrationError::OutOfOrder { version: 1, .. }
+        ));
+        assert_eq!(client.migration_status(&MIGRATIONS).unwrap().db_version, 0);
+        remove_dir_all(&dir).unwrap();
+    }
+}
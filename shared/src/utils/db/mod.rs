@@ -1,14 +1,22 @@
 use crate::utils::config::ConfigPointer;
+use crate::utils::config::sqlite::SqliteConfig;
 use crate::utils::context::{Context, ContextError, FromContext};
 use crate::utils::error::{ExtraResultExt, FromIntoStackError, LogItExt};
-use crate::utils::password::Password;
 use error_stack::{Report, ResultExt};
 use poem::http::StatusCode;
-use rusqlite::{Connection, named_params};
+use rusqlite::{Connection, OpenFlags};
 use std::marker::PhantomData;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tokio::runtime::{Handle, RuntimeFlavor};
 use tokio::sync::OnceCell;
+use tokio::task::block_in_place;
+
+pub mod migration;
 
 pub trait ConnectionMarker: Send + Sync {}
 
@@ -22,60 +30,131 @@ pub enum SqliteClientError {
     SqliteFileEmpty,
     #[error("Connection error")]
     Connection,
-    #[error("Init failed")]
-    InitFailed,
     #[error("Connection Option Empty error")]
     OptionEmpty,
-    #[error("Lock error: {0}")]
-    LockError(String),
+    #[error("Pool timeout")]
+    PoolTimeout,
+    #[error("Sqlite file missing")]
+    FileMissing,
 }
 
 impl FromIntoStackError for SqliteClientError {}
 
-pub struct SqliteClient<T = DefaultConnection>(Arc<Mutex<Connection>>, PhantomData<T>)
+const MAX_ACQUIRE_BACKOFF: Duration = Duration::from_millis(5);
+
+/// A fixed set of connections, each behind its own mutex, so `borrow_conn` keeps
+/// handing out a `MutexGuard<'_, Connection>`.
+struct ConnectionPool {
+    connections: Box<[Mutex<Connection>]>,
+    next: AtomicUsize,
+    acquire_timeout: Duration,
+}
+
+impl ConnectionPool {
+    /// Takes the first free connection, waiting up to `acquire_timeout` when all are busy.
+    ///
+    /// On a multi-thread tokio runtime the wait runs under `block_in_place`, so the
+    /// worker hands its other tasks to another thread meanwhile.
+    fn acquire(&self) -> Result<MutexGuard<'_, Connection>, Report<SqliteClientError>> {
+        if let Some(conn) = self.try_acquire() {
+            return Ok(conn);
+        }
+        match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+            Ok(RuntimeFlavor::MultiThread) => block_in_place(|| self.wait_acquire()),
+            _ => self.wait_acquire(),
+        }
+    }
+
+    /// One pass over the connections, starting from a rotating slot.
+    fn try_acquire(&self) -> Option<MutexGuard<'_, Connection>> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..self.connections.len() {
+            let slot = &self.connections[(start + offset) % self.connections.len()];
+            match slot.try_lock() {
+                Ok(conn) => return Some(conn),
+                Err(TryLockError::Poisoned(poisoned)) => {
+                    slot.clear_poison();
+                    return Some(poisoned.into_inner());
+                }
+                Err(TryLockError::WouldBlock) => {}
+            }
+        }
+        None
+    }
+
+    fn wait_acquire(&self) -> Result<MutexGuard<'_, Connection>, Report<SqliteClientError>> {
+        let deadline = Instant::now() + self.acquire_timeout;
+        let mut backoff = Duration::from_micros(50);
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Report::new(SqliteClientError::PoolTimeout).attach(format!(
+                    "all {} connections busy for {:?}",
+                    self.connections.len(),
+                    self.acquire_timeout
+                )));
+            }
+            sleep(backoff.min(deadline - now));
+            backoff = (backoff * 2).min(MAX_ACQUIRE_BACKOFF);
+            if let Some(conn) = self.try_acquire() {
+                return Ok(conn);
+            }
+        }
+    }
+}
+
+pub struct SqliteClient<T = DefaultConnection>(Arc<ConnectionPool>, PhantomData<T>)
 where
     T: ConnectionMarker;
 
 impl<T: ConnectionMarker> SqliteClient<T> {
-    pub fn new(sqlite_path: String) -> Result<Self, Report<SqliteClientError>> {
-        if sqlite_path.is_empty() {
+    pub fn new(sqlite_config: &SqliteConfig) -> Result<Self, Report<SqliteClientError>> {
+        if sqlite_config.path.is_empty() {
             return Err(SqliteClientError::SqliteFileEmpty
                 .into_stack_error_critical("Sqlite file path is empty".to_string()));
         }
-        let file_exist = std::fs::metadata(&sqlite_path).is_ok();
-
-        let conn = Connection::open(sqlite_path)
+        let busy_timeout = Duration::from_millis(sqlite_config.busy_timeout_ms);
+        let connections = (0..sqlite_config.pool_size.max(1))
+            .map(|_| {
+                let conn = Connection::open(&sqlite_config.path)?;
+                conn.busy_timeout(busy_timeout)?;
+                conn.pragma_update(None, "foreign_keys", true)?;
+                conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+                Ok(Mutex::new(conn))
+            })
+            .collect::<Result<_, rusqlite::Error>>()
             .change_context(SqliteClientError::Connection)
-            .attach_critical("Sqlite Connection failed".to_string())?;
-        if !file_exist {
-            conn.execute_batch(include_str!("_sql/init.sql"))
-                .change_context(SqliteClientError::InitFailed)
-                .attach_critical("Init failed".to_string())?;
-
-            let password = Password::hash_password("banana".to_string())
-                .change_context(SqliteClientError::InitFailed)
-                .attach_critical("Failed to hash password".to_string())?
-                .encode_to_msg_pack()
-                .change_context(SqliteClientError::InitFailed)
-                .attach_critical("Failed to encode password".to_string())?;
-
-            conn.execute(
-                include_str!("_sql/add_user.sql",),
-                named_params! {
-                    ":username": "admin",
-                    ":password": password.to_vec(),
-                },
-            )
-            .change_context(SqliteClientError::InitFailed)
-            .attach_critical("Failed to create default user".to_string())?;
-        }
+            .attach_critical("Sqlite Connection Pool failed".to_string())?;
 
-        Ok(SqliteClient(Arc::new(Mutex::new(conn)), PhantomData))
+        Ok(SqliteClient(
+            Arc::new(ConnectionPool {
+                connections,
+                next: AtomicUsize::new(0),
+                acquire_timeout: Duration::from_millis(sqlite_config.acquire_timeout_ms),
+            }),
+            PhantomData,
+        ))
     }
+}
 
-    pub fn get_conn(&self) -> &Mutex<Connection> {
-        self.0.as_ref()
+/// Opens the database for inspection only: a missing file is an error rather than
+/// created, and nothing can be written through the connection.
+pub fn open_read_only(
+    sqlite_config: &SqliteConfig,
+) -> Result<Connection, Report<SqliteClientError>> {
+    if !Path::new(&sqlite_config.path).exists() {
+        return Err(Report::new(SqliteClientError::FileMissing)
+            .attach(format!("sqlite file: {}", sqlite_config.path)));
     }
+    let conn = Connection::open_with_flags(
+        &sqlite_config.path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .change_context(SqliteClientError::Connection)
+    .attach_with(|| format!("sqlite file: {}", sqlite_config.path))?;
+    conn.busy_timeout(Duration::from_millis(sqlite_config.busy_timeout_ms))
+        .change_context(SqliteClientError::Connection)?;
+    Ok(conn)
 }
 
 impl<T: ConnectionMarker> Clone for SqliteClient<T> {
@@ -91,7 +170,7 @@ impl FromContext for SqliteClient {
         let sqlite_client: Result<&Self, Report<ContextError>> = SQLITE_CLIENT_CACHE
             .get_or_try_init(|| async {
                 let config: ConfigPointer = ctx.inject().await?;
-                Ok(Self::new(config.sqlite.path.clone()).change_context(ContextError::Other)?)
+                Ok(Self::new(&config.sqlite).change_context(ContextError::Other)?)
             })
             .await;
         Ok(sqlite_client?.clone())
@@ -104,11 +183,9 @@ pub trait BorrowConnectionExt {
 
 impl<T: ConnectionMarker> BorrowConnectionExt for SqliteClient<T> {
     fn borrow_conn(&'_ self) -> Result<MutexGuard<'_, Connection>, Report<SqliteClientError>> {
-        self.0.lock().map_err(|err| {
-            Report::new(SqliteClientError::LockError(err.to_string()))
-                .attach(StatusCode::INTERNAL_SERVER_ERROR)
-                .log_it()
-        })
+        self.0
+            .acquire()
+            .map_err(|report| report.attach(StatusCode::INTERNAL_SERVER_ERROR).log_it())
     }
 }
 
@@ -123,3 +200,121 @@ impl<T: ConnectionMarker> BorrowConnectionExt for Option<SqliteClient<T>> {
             .borrow_conn()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use std::path::PathBuf;
+    use std::sync::mpsc::channel;
+    use std::thread::scope;
+    use uuid::Uuid;
+
+    pub(crate) fn client(pool_size: u32, acquire_timeout_ms: u64) -> (SqliteClient, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("db-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let config = SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            pool_size,
+            acquire_timeout_ms,
+            ..Default::default()
+        };
+        (SqliteClient::new(&config).unwrap(), dir)
+    }
+
+    #[test]
+    fn test_open_read_only_does_not_create_or_write() {
+        let dir = std::env::temp_dir().join(format!("db-test-{}", Uuid::new_v4().simple()));
+        create_dir_all(&dir).unwrap();
+        let config = SqliteConfig {
+            path: dir.join("sqlite.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+
+        let err = open_read_only(&config).unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            SqliteClientError::FileMissing
+        ));
+        assert!(!Path::new(&config.path).exists());
+
+        drop(SqliteClient::<DefaultConnection>::new(&config).unwrap());
+        let conn = open_read_only(&config).unwrap();
+        assert!(conn.execute_batch("create table t (id integer)").is_err());
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_borrow_conn_hands_out_every_connection() {
+        let (client, dir) = client(2, 50);
+        let first = client.borrow_conn().unwrap();
+        let second = client.borrow_conn().unwrap();
+        first.execute_batch("create table t (id integer)").unwrap();
+        second.execute_batch("select 1").unwrap();
+
+        let err = client.borrow_conn().unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            SqliteClientError::PoolTimeout
+        ));
+        drop(first);
+        assert!(client.borrow_conn().is_ok());
+        drop(second);
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_borrow_conn_times_out_after_acquire_timeout() {
+        let (client, dir) = client(1, 100);
+        let held = client.borrow_conn().unwrap();
+        let started = Instant::now();
+        assert!(client.borrow_conn().is_err());
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        drop(held);
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_borrow_conn_waits_for_a_released_connection() {
+        let (client, dir) = client(1, 5000);
+        let (held_tx, held_rx) = channel();
+        scope(|s| {
+            s.spawn(|| {
+                let _conn = client.borrow_conn().unwrap();
+                held_tx.send(()).unwrap();
+                sleep(Duration::from_millis(50));
+            });
+            held_rx.recv().unwrap();
+            let started = Instant::now();
+            assert!(client.borrow_conn().is_ok());
+            assert!(started.elapsed() < Duration::from_millis(5000));
+        });
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_borrow_conn_wait_leaves_the_worker_to_other_tasks() {
+        let (client, dir) = client(1, 1000);
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+        let held = client.borrow_conn().unwrap();
+        let (started_tx, started_rx) = channel();
+        let waiting_client = client.clone();
+        let waiting = runtime.spawn(async move {
+            started_tx.send(()).unwrap();
+            waiting_client.borrow_conn().is_ok()
+        });
+        started_rx.recv().unwrap();
+
+        let (ran_tx, ran_rx) = channel();
+        runtime.spawn(async move { ran_tx.send(()).unwrap() });
+        assert!(ran_rx.recv_timeout(Duration::from_millis(500)).is_ok());
+
+        drop(held);
+        assert!(runtime.block_on(waiting).unwrap());
+        remove_dir_all(&dir).unwrap();
+    }
+}
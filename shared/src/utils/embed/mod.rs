@@ -1,6 +1,7 @@
 use poem::http::Uri;
 use poem::{Endpoint, IntoEndpoint, Request};
-use rust_embed::EmbeddedFile;
+use rust_embed::{Embed, EmbeddedFile};
+use std::collections::HashMap;
 
 pub trait EmbedAsString {
     fn as_string(&self) -> String;
@@ -14,6 +15,21 @@ impl EmbedAsString for Option<EmbeddedFile> {
     }
 }
 
+/// Locale files used by both servers, such as the internal error page.
+#[derive(Embed)]
+#[folder = "$CARGO_MANIFEST_DIR/asset/embed_locale/"]
+pub struct SharedAssetLocale;
+
+/// Appends every `<locale>/*.ftl` file of `E` to the resources of that locale in `map`.
+pub fn add_locale_files<E: Embed>(map: &mut HashMap<String, String>) {
+    for value in E::iter() {
+        let locale = value.split("/").next().unwrap_or_default();
+        let str = map.entry(locale.to_string()).or_default();
+        str.push_str(&E::get(&value).as_string());
+        str.push('\n');
+    }
+}
+
 struct EnforceMinJsOnProd<EP: Endpoint>(EP);
 
 impl<EP: Endpoint> Endpoint for EnforceMinJsOnProd<EP> {
@@ -10,4 +10,10 @@ pub enum MainError {
     LocaleError,
     #[error("Thread error")]
     ThreadError,
+    #[error("DB error")]
+    DbError,
+    #[error("Migration error")]
+    MigrationError,
+    #[error("Command error")]
+    CommandError,
 }
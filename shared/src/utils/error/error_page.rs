@@ -0,0 +1,59 @@
+use crate::utils::locale::LocaleExt;
+use maud::{Markup, html};
+use poem::http::StatusCode;
+use poem::i18n::Locale;
+use poem::{IntoResponse, Response};
+
+/// Shown when a request panicked, built without the request as it has been consumed by then.
+///
+/// `layout` wraps the content in the page of the calling server.
+pub fn internal_error_page(
+    locale: Option<&Locale>,
+    request_id: Option<&str>,
+    layout: impl FnOnce(String, Markup) -> Markup,
+) -> Response {
+    let text = |key: &str, default: &str| match locale {
+        Some(locale) => locale.text_with_default(key, default),
+        None => default.to_string(),
+    };
+    let title = text("error-internal-title", "Something went wrong");
+    let message = text(
+        "error-internal-message",
+        "The error has been logged, please try again later.",
+    );
+    let home = text("error-internal-home", "Back to home");
+    let request_id_label = text("error-internal-request-id", "Request ID:");
+
+    let content = html! {
+        h1 { (title) }
+        p { (message) }
+        @if let Some(request_id) = request_id {
+            p { (request_id_label) " " code { (request_id) } }
+        }
+        p { a href="/" { (home) } }
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, layout(title, content)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    #[test]
+    fn test_internal_error_page_without_locale() {
+        let resp = internal_error_page(None, Some("abc123"), |title, content| {
+            html! { main title=(title) { (content) } }
+        });
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = resp
+            .into_body()
+            .into_string()
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert!(body.starts_with(r#"<main title="Something went wrong"><h1>"#));
+        assert!(body.contains("Request ID: <code>abc123</code>"));
+    }
+}
@@ -1,10 +1,13 @@
 pub mod boot_error;
+pub mod error_page;
 
+use crate::utils::request_id::RequestId;
 use error_stack::{Report, ResultExt};
 use poem::error::ResponseError;
 use poem::http::StatusCode;
 use poem::web::Json;
 use poem::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::error::Error;
 use std::fmt::{Debug, Display};
@@ -41,6 +44,59 @@ pub struct CriticalError(pub String);
 
 impl FromIntoStackError for CriticalError {}
 
+/// How bad a logged error is, stored with every `error_stack` row.
+///
+/// Attach one to a `Report` to set it explicitly, otherwise a `CriticalError` attachment
+/// makes it `Critical`, a 4xx `StatusCode` makes it a `Warning` and anything else,
+/// including a report without a status, is an `Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Critical,
+    #[default]
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Critical, Severity::Error, Severity::Warning];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+
+    pub fn from_report<C>(err: &Report<C>) -> Self {
+        match err.downcast_ref::<Severity>() {
+            Some(severity) => *severity,
+            None if CriticalError::is_in_error_stack(err) => Self::Critical,
+            None => match err.downcast_ref::<StatusCode>() {
+                Some(status) if status.is_client_error() => Self::Warning,
+                _ => Self::Error,
+            },
+        }
+    }
+}
+
+impl From<&str> for Severity {
+    fn from(value: &str) -> Self {
+        match value {
+            "critical" => Self::Critical,
+            "warning" => Self::Warning,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Severity: {}", self.as_str())
+    }
+}
+
 pub fn check_is_critical_error<C>(err: Report<C>) -> Result<Report<C>, Report<C>> {
     if CriticalError::is_in_error_stack::<C>(&err) {
         return Err(err);
@@ -61,6 +117,8 @@ pub trait ExtraResultExt: ResultExt {
 
     fn log_it(self) -> Result<Self::Ok, Report<Self::Context>>;
 
+    fn attach_severity(self, severity: Severity) -> Result<Self::Ok, Report<Self::Context>>;
+
     fn attach_critical_lazy<F>(self, msg: F) -> Result<Self::Ok, Report<Self::Context>>
     where
         F: FnOnce() -> String;
@@ -101,6 +159,13 @@ where
         }
     }
 
+    fn attach_severity(self, severity: Severity) -> Self {
+        match self {
+            Ok(ok) => Ok(ok),
+            Err(report) => Err(report.attach(severity)),
+        }
+    }
+
     fn attach_critical_lazy<F>(self, msg: F) -> Self
     where
         F: FnOnce() -> String,
@@ -165,8 +230,9 @@ impl<T> LogItExt for Report<T> {
 
 pub struct ErrorStackUseJson;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct LogData {
+    pub severity: Severity,
     pub name: String,
     pub summary: String,
     pub details: String,
@@ -201,11 +267,14 @@ impl<T> ResponseError for ErrorStack<T> {
         Self: Error + Send + Sync + 'static,
     {
         let status = self.status();
-        let body = if cfg!(debug_assertions) {
+        let mut body = if cfg!(debug_assertions) {
             format!("{}\n{:?}", status, self.0)
         } else {
             format!("{}\n{}", status, self.0)
         };
+        if let Some(request_id) = RequestId::current() {
+            body.push_str(&format!("\nRequest ID: {}", request_id));
+        }
         match self.0.downcast_ref::<ErrorStackUseJson>() {
             Some(_) => {
                 let json = Json(json!({"msg": body}));
@@ -237,6 +306,7 @@ impl FromErrorStack for poem::Error {
             None => Self::from(ErrorStack(err)),
             Some(_) => {
                 let data = LogData {
+                    severity: Severity::from_report(&err),
                     name: format!("{}", err),
                     summary: format!("{:#}", err),
                     details: format!("{:?}", err),
@@ -248,3 +318,37 @@ impl FromErrorStack for poem::Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Error)]
+    #[error("test error")]
+    struct TestError;
+
+    #[test]
+    fn test_severity_from_status_code() {
+        let not_found = Report::new(TestError).attach(StatusCode::NOT_FOUND);
+        assert_eq!(Severity::from_report(&not_found), Severity::Warning);
+
+        let internal = Report::new(TestError).attach(StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(Severity::from_report(&internal), Severity::Error);
+
+        let no_status = Report::new(TestError);
+        assert_eq!(Severity::from_report(&no_status), Severity::Error);
+    }
+
+    #[test]
+    fn test_severity_explicit_and_critical_win_over_status_code() {
+        let critical = Report::new(TestError)
+            .attach(StatusCode::BAD_REQUEST)
+            .attach(CriticalError("disk full".to_string()));
+        assert_eq!(Severity::from_report(&critical), Severity::Critical);
+
+        let explicit = Report::new(TestError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)
+            .attach(Severity::Warning);
+        assert_eq!(Severity::from_report(&explicit), Severity::Warning);
+    }
+}
@@ -7,10 +7,12 @@ use error_stack::{Report, ResultExt};
 use poem::http::header;
 use poem::web::Redirect;
 use poem::{FromRequest, IntoResponse, Request, RequestBody, Response};
+use serde::Serialize;
 use serde_json::json;
 use std::ops::Deref;
 use std::sync::Arc;
 
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct HtmxHeaderData {
     pub boosted: bool,
     pub current_url: Option<String>,
@@ -23,7 +25,7 @@ pub struct HtmxHeaderData {
 }
 
 impl HtmxHeaderData {
-    fn new(req: &Request) -> Self {
+    pub(crate) fn new(req: &Request) -> Self {
         let headers = req.headers();
         Self {
             boosted: headers
@@ -0,0 +1,344 @@
+pub mod model;
+pub mod repository;
+pub mod schedule;
+
+use crate::utils::config::jobs::JobsConfig;
+use crate::utils::context::{Context, ContextError, FromContext, fetch_context};
+use crate::utils::jobs::model::JobRunStatus;
+use crate::utils::jobs::repository::job_run_repository::JobRunRepository;
+use crate::utils::jobs::schedule::Schedule;
+use chrono::{DateTime, Duration, Utc};
+use error_stack::{Report, ResultExt};
+use log::{error, info, warn};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JobError {
+    #[error("Invalid schedule `{0}`")]
+    InvalidSchedule(String),
+    #[error("Invalid stale run duration of {0} seconds")]
+    InvalidStaleRun(u64),
+    #[error("Unknown job `{0}`")]
+    UnknownJob(String),
+    #[error("Dependency error")]
+    DependencyError,
+    #[error("DB error")]
+    DbError,
+    #[error("Job failed")]
+    Failed,
+    #[error("Job panicked")]
+    Panicked,
+}
+
+/// A unit of background work, built from the context before every run.
+pub trait Job: FromContext + 'static {
+    /// Returns a short summary that is stored with the run.
+    fn run(&self) -> impl Future<Output = Result<String, Report<JobError>>> + Send;
+}
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<String, Report<JobError>>> + Send>>;
+
+struct RegisteredJob {
+    name: &'static str,
+    schedule: Schedule,
+    runner: Box<dyn Fn() -> JobFuture + Send + Sync>,
+    running: AtomicBool,
+    next_run: Mutex<Option<DateTime<Utc>>>,
+}
+
+/// The jobs handed to `JobScheduler::start`.
+#[derive(Default)]
+pub struct Jobs(Vec<RegisteredJob>);
+
+impl Jobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<J: Job>(mut self, name: &'static str, schedule: Schedule) -> Self {
+        self.0.push(RegisteredJob {
+            name,
+            schedule,
+            runner: Box::new(|| {
+                Box::pin(async {
+                    let job: J = fetch_context()
+                        .await
+                        .change_context(JobError::DependencyError)?;
+                    job.run().await
+                })
+            }),
+            running: AtomicBool::new(false),
+            next_run: Mutex::new(None),
+        });
+        self
+    }
+}
+
+/// Snapshot of a registered job.
+pub struct JobInfo {
+    pub name: &'static str,
+    pub schedule: String,
+    pub next_run: Option<DateTime<Utc>>,
+    pub running: bool,
+}
+
+struct SchedulerInner {
+    jobs: Box<[RegisteredJob]>,
+    stale_run: Duration,
+    history_per_job: u32,
+}
+
+#[derive(Clone)]
+pub struct JobScheduler(Arc<SchedulerInner>);
+
+static JOB_SCHEDULER: OnceLock<JobScheduler> = OnceLock::new();
+
+/// Longest sleep between two checks, so a changed wall clock is noticed.
+const MAX_TICK: std::time::Duration = std::time::Duration::from_secs(60);
+
+impl JobScheduler {
+    /// Builds a scheduler without starting it, `start` is what the servers call.
+    pub fn new(jobs: Jobs, config: &JobsConfig) -> Result<Self, Report<JobError>> {
+        let stale_run = i64::try_from(config.stale_run_secs)
+            .ok()
+            .and_then(Duration::try_seconds)
+            .ok_or_else(|| Report::new(JobError::InvalidStaleRun(config.stale_run_secs)))?;
+        let scheduler = Self(Arc::new(SchedulerInner {
+            jobs: jobs.0.into(),
+            stale_run,
+            history_per_job: config.history_per_job,
+        }));
+        if config.enabled {
+            let now = Utc::now();
+            for job in scheduler.0.jobs.iter() {
+                *job.next_run.lock().unwrap_or_else(|e| e.into_inner()) =
+                    job.schedule.next_after(now);
+            }
+        }
+        Ok(scheduler)
+    }
+
+    /// Starts the scheduler loop, the first call per process wins.
+    ///
+    /// With `enabled = false` jobs are still listed and can be run by hand.
+    pub fn start(jobs: Jobs, config: &JobsConfig) -> Result<Self, Report<JobError>> {
+        if let Some(scheduler) = JOB_SCHEDULER.get() {
+            return Ok(scheduler.clone());
+        }
+        let scheduler = Self::new(jobs, config)?;
+        Ok(JOB_SCHEDULER
+            .get_or_init(|| {
+                if config.enabled {
+                    tokio::spawn(scheduler.clone().run_loop());
+                }
+                scheduler
+            })
+            .clone())
+    }
+
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.0
+            .jobs
+            .iter()
+            .map(|job| JobInfo {
+                name: job.name,
+                schedule: job.schedule.to_string(),
+                next_run: *job.next_run.lock().unwrap_or_else(|e| e.into_inner()),
+                running: job.running.load(Ordering::Acquire),
+            })
+            .collect()
+    }
+
+    /// Runs a job in the background, returns false when it is already running.
+    pub fn run_now(&self, name: &str) -> Result<bool, Report<JobError>> {
+        let index = self
+            .0
+            .jobs
+            .iter()
+            .position(|job| job.name == name)
+            .ok_or_else(|| Report::new(JobError::UnknownJob(name.to_string())))?;
+        if self.0.jobs[index].running.load(Ordering::Acquire) {
+            return Ok(false);
+        }
+        tokio::spawn(self.clone().execute(index));
+        Ok(true)
+    }
+
+    async fn run_loop(self) {
+        loop {
+            let now = Utc::now();
+            let mut earliest: Option<DateTime<Utc>> = None;
+            for (index, job) in self.0.jobs.iter().enumerate() {
+                let mut next_run = job.next_run.lock().unwrap_or_else(|e| e.into_inner());
+                if next_run.is_some_and(|next_run| next_run <= now) {
+                    *next_run = job.schedule.next_after(now);
+                    tokio::spawn(self.clone().execute(index));
+                }
+                if let Some(next_run) = *next_run {
+                    earliest = Some(earliest.map_or(next_run, |earliest| earliest.min(next_run)));
+                }
+            }
+            let wait = earliest
+                .and_then(|earliest| (earliest - now).to_std().ok())
+                .unwrap_or(MAX_TICK)
+                .min(MAX_TICK);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    async fn execute(self, index: usize) {
+        let job = &self.0.jobs[index];
+        if job.running.swap(true, Ordering::AcqRel) {
+            warn!("Job {} is still running, skipped", job.name);
+            return;
+        }
+        let _running = RunningGuard(&job.running);
+        if let Err(report) = self.record_run(job).await {
+            error!("{:?}", report);
+        }
+    }
+
+    async fn record_run(&self, job: &RegisteredJob) -> Result<(), Report<JobError>> {
+        let job_run_repository: JobRunRepository = fetch_context()
+            .await
+            .change_context(JobError::DependencyError)?;
+        let now = Utc::now();
+        let Some(run_id) = job_run_repository
+            .start_run(job.name, now, now - self.0.stale_run)
+            .change_context(JobError::DbError)?
+        else {
+            info!("Job {} is running elsewhere, skipped", job.name);
+            return Ok(());
+        };
+
+        let result = match tokio::spawn((job.runner)()).await {
+            Ok(result) => result,
+            Err(err) => Err(Report::new(JobError::Panicked).attach(err.to_string())),
+        };
+        let (status, message) = match result {
+            Ok(message) => {
+                info!("Job {} succeeded: {}", job.name, message);
+                (JobRunStatus::Succeeded, message)
+            }
+            Err(report) => {
+                error!("Job {} failed: {:?}", job.name, report);
+                (JobRunStatus::Failed, format!("{:#}", report))
+            }
+        };
+
+        job_run_repository
+            .finish_run(run_id, status, message)
+            .change_context(JobError::DbError)?;
+        job_run_repository
+            .prune_runs(job.name, self.0.history_per_job)
+            .change_context(JobError::DbError)
+    }
+}
+
+/// Clears the running flag even when the run future is dropped or panics.
+struct RunningGuard<'a>(&'a AtomicBool);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl FromContext for JobScheduler {
+    async fn from_context(_ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        JOB_SCHEDULER
+            .get()
+            .cloned()
+            .ok_or_else(|| Report::new(ContextError::Other).attach("Job scheduler not started"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopJob;
+
+    impl Job for NoopJob {
+        async fn run(&self) -> Result<String, Report<JobError>> {
+            Ok(String::new())
+        }
+    }
+
+    impl FromContext for NoopJob {
+        async fn from_context(_ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+            Ok(Self)
+        }
+    }
+
+    fn jobs() -> Jobs {
+        Jobs::new()
+            .register::<NoopJob>("first", Schedule::parse("every 1h").unwrap())
+            .register::<NoopJob>("second", Schedule::parse("30 3 * * *").unwrap())
+    }
+
+    #[test]
+    fn test_new_schedules_next_runs_when_enabled() {
+        let before = Utc::now();
+        let scheduler = JobScheduler::new(jobs(), &JobsConfig::default()).unwrap();
+
+        let infos = scheduler.jobs();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].name, "first");
+        assert_eq!(infos[0].schedule, "every 1h");
+        let next_run = infos[0].next_run.unwrap();
+        assert!(next_run >= before + Duration::hours(1));
+        assert!(next_run <= Utc::now() + Duration::hours(1));
+        assert_eq!(infos[1].schedule, "30 3 * * *");
+        assert!(infos[1].next_run.is_some());
+        assert!(infos.iter().all(|info| !info.running));
+    }
+
+    #[test]
+    fn test_new_disabled_has_no_next_runs() {
+        let config = JobsConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let scheduler = JobScheduler::new(jobs(), &config).unwrap();
+        assert!(scheduler.jobs().iter().all(|info| info.next_run.is_none()));
+    }
+
+    #[test]
+    fn test_new_rejects_out_of_range_stale_run() {
+        let config = JobsConfig {
+            stale_run_secs: u64::MAX,
+            ..Default::default()
+        };
+        let err = JobScheduler::new(jobs(), &config).err().unwrap();
+        assert!(matches!(
+            err.current_context(),
+            JobError::InvalidStaleRun(u64::MAX)
+        ));
+    }
+
+    #[test]
+    fn test_run_now_unknown_or_running_job() {
+        let scheduler = JobScheduler::new(jobs(), &JobsConfig::default()).unwrap();
+
+        let err = scheduler.run_now("missing").unwrap_err();
+        assert!(matches!(err.current_context(), JobError::UnknownJob(_)));
+
+        let _running = {
+            scheduler.0.jobs[1].running.store(true, Ordering::Release);
+            RunningGuard(&scheduler.0.jobs[1].running)
+        };
+        assert!(!scheduler.run_now("second").unwrap());
+        assert!(scheduler.jobs()[1].running);
+    }
+
+    #[test]
+    fn test_running_guard_clears_flag() {
+        let running = AtomicBool::new(true);
+        drop(RunningGuard(&running));
+        assert!(!running.load(Ordering::Acquire));
+    }
+}
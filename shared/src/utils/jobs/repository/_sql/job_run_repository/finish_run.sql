@@ -0,0 +1,5 @@
+update job_runs
+set finished_at = :now,
+    status      = :status,
+    message     = :message
+where id = :id
@@ -0,0 +1,3 @@
+select id, job_name, started_at, finished_at, status, message
+from job_runs
+where id in (select max(id) from job_runs group by job_name)
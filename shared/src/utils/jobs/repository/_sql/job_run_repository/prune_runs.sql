@@ -0,0 +1,8 @@
+delete
+from job_runs
+where job_name = :job_name
+  and id not in (select id
+                 from job_runs
+                 where job_name = :job_name
+                 order by id desc
+                 limit :keep)
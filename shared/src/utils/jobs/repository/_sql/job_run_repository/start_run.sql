@@ -0,0 +1,8 @@
+insert into job_runs (job_name, started_at, status)
+select :job_name, :now, 'running'
+where not exists (select 1
+                  from job_runs
+                  where job_name = :job_name
+                    and finished_at is null
+                    and started_at > :stale_before)
+returning id
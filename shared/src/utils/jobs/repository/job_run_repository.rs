@@ -0,0 +1,269 @@
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::db::{BorrowConnectionExt, SqliteClient};
+use crate::utils::jobs::model::{JobRun, JobRunStatus};
+use chrono::{DateTime, Utc};
+use error_stack::{Report, ResultExt};
+use rusqlite::{Connection, OptionalExtension, named_params};
+use std::sync::{Arc, MutexGuard};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JobRunRepositoryError {
+    #[error("Query error")]
+    QueryError,
+    #[error("Row Value error")]
+    RowValueError,
+    #[error("Borrow Conn error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct JobRunRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl JobRunRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(&'_ self) -> Result<MutexGuard<'_, Connection>, Report<JobRunRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(JobRunRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl JobRunRepository {
+    /// Records the start of a run, unless another run of the same job started after
+    /// `stale_before` and has not finished yet.
+    pub fn start_run(
+        &self,
+        job_name: &str,
+        now: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<Option<i64>, Report<JobRunRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.query_one(
+            include_str!("_sql/job_run_repository/start_run.sql"),
+            named_params! {
+                ":job_name": job_name,
+                ":now": now,
+                ":stale_before": stale_before,
+            },
+            |row| row.get("id"),
+        )
+        .optional()
+        .change_context(JobRunRepositoryError::QueryError)
+    }
+
+    pub fn finish_run(
+        &self,
+        id: i64,
+        status: JobRunStatus,
+        message: String,
+    ) -> Result<(), Report<JobRunRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/job_run_repository/finish_run.sql"),
+            named_params! {
+                ":id": id,
+                ":now": Utc::now(),
+                ":status": status.as_str(),
+                ":message": message,
+            },
+        )
+        .change_context(JobRunRepositoryError::QueryError)?;
+
+        Ok(())
+    }
+
+    /// Keeps only the latest `keep` runs of a job.
+    pub fn prune_runs(
+        &self,
+        job_name: &str,
+        keep: u32,
+    ) -> Result<(), Report<JobRunRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/job_run_repository/prune_runs.sql"),
+            named_params! {
+                ":job_name": job_name,
+                ":keep": keep,
+            },
+        )
+        .change_context(JobRunRepositoryError::QueryError)?;
+
+        Ok(())
+    }
+
+    /// The most recent run of every job that has run at least once.
+    pub fn list_last_runs(&self) -> Result<Arc<[JobRun]>, Report<JobRunRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        let mut stmt = conn
+            .prepare_cached(include_str!("_sql/job_run_repository/list_last_runs.sql"))
+            .change_context(JobRunRepositoryError::QueryError)?;
+
+        let rows = stmt
+            .query_map(named_params! {}, |row| {
+                Ok(JobRun {
+                    id: row.get("id")?,
+                    job_name: row.get("job_name")?,
+                    started_at: row.get("started_at")?,
+                    finished_at: row.get("finished_at")?,
+                    status: JobRunStatus::from(row.get::<_, String>("status")?.as_str()),
+                    message: row.get("message")?,
+                })
+            })
+            .change_context(JobRunRepositoryError::RowValueError)?;
+
+        let runs = rows
+            .collect::<Result<Vec<_>, _>>()
+            .change_context(JobRunRepositoryError::RowValueError)?;
+
+        Ok(runs.into())
+    }
+}
+
+#[cfg(test)]
+impl JobRunRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for JobRunRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::migration::SHARED_MIGRATIONS;
+    use crate::utils::db::tests::client;
+    use chrono::TimeDelta;
+    use std::fs::remove_dir_all;
+
+    fn repository() -> (JobRunRepository, std::path::PathBuf) {
+        let (client, dir) = client(1, 1000);
+        client.migrate(&SHARED_MIGRATIONS).unwrap();
+        (JobRunRepository::new(client), dir)
+    }
+
+    #[test]
+    fn test_start_run_skips_while_running() {
+        let (repository, dir) = repository();
+        let now = Utc::now();
+        let stale_before = now - TimeDelta::hours(1);
+
+        let first = repository.start_run("job", now, stale_before).unwrap();
+        assert!(first.is_some());
+        assert_eq!(
+            repository.start_run("job", now, stale_before).unwrap(),
+            None
+        );
+        // Other jobs are not blocked.
+        assert!(
+            repository
+                .start_run("other", now, stale_before)
+                .unwrap()
+                .is_some()
+        );
+        // A run that started before `stale_before` no longer blocks.
+        assert!(
+            repository
+                .start_run("job", now, now + TimeDelta::seconds(1))
+                .unwrap()
+                .is_some()
+        );
+
+        repository
+            .finish_run(first.unwrap(), JobRunStatus::Succeeded, "done".to_string())
+            .unwrap();
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_finish_run_and_list_last_runs() {
+        let (repository, dir) = repository();
+        let now = Utc::now();
+        let stale_before = now - TimeDelta::hours(1);
+
+        let first = repository
+            .start_run("job", now, stale_before)
+            .unwrap()
+            .unwrap();
+        repository
+            .finish_run(first, JobRunStatus::Failed, "boom".to_string())
+            .unwrap();
+        let second = repository
+            .start_run("job", now, stale_before)
+            .unwrap()
+            .unwrap();
+        repository
+            .finish_run(second, JobRunStatus::Succeeded, "done".to_string())
+            .unwrap();
+        repository.start_run("other", now, stale_before).unwrap();
+
+        let mut runs = repository.list_last_runs().unwrap().to_vec();
+        runs.sort_by(|a, b| a.job_name.cmp(&b.job_name));
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].id, second);
+        assert_eq!(runs[0].status, JobRunStatus::Succeeded);
+        assert_eq!(runs[0].message.as_deref(), Some("done"));
+        assert!(runs[0].finished_at.is_some());
+        assert_eq!(runs[1].job_name, "other");
+        assert_eq!(runs[1].status, JobRunStatus::Running);
+        assert!(runs[1].finished_at.is_none());
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_runs_keeps_latest_of_job() {
+        let (repository, dir) = repository();
+        let now = Utc::now();
+        let stale_before = now - TimeDelta::hours(1);
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let id = repository
+                .start_run("job", now, stale_before)
+                .unwrap()
+                .unwrap();
+            repository
+                .finish_run(id, JobRunStatus::Succeeded, String::new())
+                .unwrap();
+            ids.push(id);
+        }
+        let other = repository
+            .start_run("other", now, stale_before)
+            .unwrap()
+            .unwrap();
+
+        repository.prune_runs("job", 1).unwrap();
+
+        let conn = repository.borrow_conn().unwrap();
+        let remaining: Vec<i64> = conn
+            .prepare("select id from job_runs order by id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(remaining, vec![ids[2], other]);
+        drop(conn);
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1 @@
+pub mod job_run_repository;
@@ -0,0 +1,295 @@
+use crate::utils::jobs::JobError;
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+use error_stack::Report;
+use std::fmt::{Display, Formatter};
+
+/// How often a job runs.
+///
+/// Parsed from either `every <n><s|m|h|d>` or a five field cron expression
+/// (`minute hour day-of-month month day-of-week`, evaluated in UTC).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Interval(Duration),
+    Cron(CronSchedule),
+}
+
+impl Schedule {
+    pub fn parse(s: &str) -> Result<Self, Report<JobError>> {
+        let s = s.trim();
+        let invalid = || Report::new(JobError::InvalidSchedule(s.to_string()));
+        if let Some(interval) = s.strip_prefix("every ") {
+            let interval = interval.trim();
+            let split = interval
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let (amount, unit) = interval.split_at(split);
+            let amount: i64 = amount.parse().map_err(|_| invalid())?;
+            let duration = match unit {
+                "s" => Duration::try_seconds(amount),
+                "m" => Duration::try_minutes(amount),
+                "h" => Duration::try_hours(amount),
+                "d" => Duration::try_days(amount),
+                _ => None,
+            }
+            .ok_or_else(invalid)?;
+            if duration <= Duration::zero() {
+                return Err(invalid());
+            }
+            return Ok(Self::Interval(duration));
+        }
+        let expression = match s {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            _ => s,
+        };
+        CronSchedule::parse(expression)
+            .map(Self::Cron)
+            .ok_or_else(invalid)
+    }
+
+    /// The first run strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Interval(duration) => after.checked_add_signed(*duration),
+            Self::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Interval(duration) => {
+                let secs = duration.num_seconds();
+                match secs {
+                    _ if secs % 86400 == 0 => write!(f, "every {}d", secs / 86400),
+                    _ if secs % 3600 == 0 => write!(f, "every {}h", secs / 3600),
+                    _ if secs % 60 == 0 => write!(f, "every {}m", secs / 60),
+                    _ => write!(f, "every {}s", secs),
+                }
+            }
+            Self::Cron(cron) => f.write_str(&cron.expression),
+        }
+    }
+}
+
+/// A parsed cron expression, each field stored as a bit set of allowed values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_any: bool,
+    day_of_week_any: bool,
+}
+
+/// Cron expressions that match nothing within this many years are treated as never due.
+const CRON_SEARCH_YEARS: i32 = 5;
+
+impl CronSchedule {
+    fn parse(expression: &str) -> Option<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return None;
+        };
+        let mut days_of_week = parse_field(day_of_week, 0, 7)?;
+        // Both 0 and 7 mean Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Some(Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days_of_month: parse_field(day_of_month, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            days_of_week,
+            day_of_month_any: *day_of_month == "*",
+            day_of_week_any: *day_of_week == "*",
+        })
+    }
+
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = has(self.days_of_month, time.day());
+        let day_of_week = has(self.days_of_week, time.weekday().num_days_from_sunday());
+        // Like cron, a restricted day-of-month and day-of-week match when either does.
+        match (self.day_of_month_any, self.day_of_week_any) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        let give_up = after.year() + CRON_SEARCH_YEARS;
+        while time.year() <= give_up {
+            if !has(self.months, time.month()) {
+                time = start_of_next_month(time)?;
+            } else if !self.day_matches(time) {
+                time = time.duration_trunc(Duration::days(1)).ok()? + Duration::days(1);
+            } else if !has(self.hours, time.hour()) {
+                time = time.duration_trunc(Duration::hours(1)).ok()? + Duration::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn start_of_next_month(time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let (year, month) = match time.month() {
+        12 => (time.year() + 1, 1),
+        month => (time.year(), month + 1),
+    };
+    time.with_day(1)?
+        .with_hour(0)?
+        .with_minute(0)?
+        .with_second(0)?
+        .with_nanosecond(0)?
+        .with_year(year)?
+        .with_month(month)
+}
+
+/// Parses one comma separated cron field made of `*`, `n`, `a-b`, each with an optional `/step`.
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                // `n/step` runs from n to the end of the range.
+                None if step > 1 => (range.parse().ok()?, max),
+                None => {
+                    let value = range.parse().ok()?;
+                    (value, value)
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Some(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        let schedule = Schedule::parse("every 15m").unwrap();
+        assert_eq!(schedule, Schedule::Interval(Duration::minutes(15)));
+        assert_eq!(schedule.to_string(), "every 15m");
+        assert!(Schedule::parse("every 0h").is_err());
+        assert!(Schedule::parse("every 5w").is_err());
+    }
+
+    #[test]
+    fn test_parse_interval_out_of_range() {
+        assert!(Schedule::parse(&format!("every {}d", i64::MAX)).is_err());
+        assert!(Schedule::parse(&format!("every {}s", i64::MAX / 1000 + 1)).is_err());
+        assert!(Schedule::parse("every 99999999999999999999h").is_err());
+        assert_eq!(
+            Schedule::parse("every 365d").unwrap().to_string(),
+            "every 365d"
+        );
+    }
+
+    #[test]
+    fn test_parse_cron_aliases() {
+        assert_eq!(
+            Schedule::parse("@daily").unwrap(),
+            Schedule::parse("0 0 * * *").unwrap()
+        );
+        assert_eq!(
+            Schedule::parse(" @hourly ").unwrap().to_string(),
+            "0 * * * *"
+        );
+    }
+
+    #[test]
+    fn test_interval_next_after() {
+        let schedule = Schedule::parse("every 90m").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 23, 0)),
+            Some(at(2025, 1, 2, 0, 30))
+        );
+        assert_eq!(schedule.next_after(DateTime::<Utc>::MAX_UTC), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_cron() {
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_cron_next_daily() {
+        let schedule = Schedule::parse("30 3 * * *").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 3, 29)),
+            Some(at(2025, 1, 1, 3, 30))
+        );
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 3, 30)),
+            Some(at(2025, 1, 2, 3, 30))
+        );
+    }
+
+    #[test]
+    fn test_cron_next_step_and_month_rollover() {
+        let schedule = Schedule::parse("*/20 0 1 * *").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 12, 31, 23, 59)),
+            Some(at(2026, 1, 1, 0, 0))
+        );
+        assert_eq!(
+            schedule.next_after(at(2026, 1, 1, 0, 0)),
+            Some(at(2026, 1, 1, 0, 20))
+        );
+    }
+
+    #[test]
+    fn test_cron_day_of_week() {
+        // 2025-01-01 is a Wednesday, the next Sunday is the 5th.
+        let schedule = Schedule::parse("0 12 * * 7").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 0, 0)),
+            Some(at(2025, 1, 5, 12, 0))
+        );
+    }
+
+    #[test]
+    fn test_cron_never_due() {
+        let schedule = Schedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(schedule.next_after(at(2025, 1, 1, 0, 0)), None);
+    }
+}
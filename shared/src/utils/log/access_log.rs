@@ -0,0 +1,227 @@
+use crate::utils::client_ip::ClientIp;
+use crate::utils::config::ConfigPointer;
+use crate::utils::config::access_log::{AccessLogConfig, AccessLogFormat};
+use crate::utils::context::fetch_context;
+use crate::utils::htmx::HtmxHeader;
+use crate::utils::log::scope::LogScope;
+use chrono::{DateTime, Utc};
+use http_body_util::combinators::BoxBody;
+use log::info;
+use poem::http::header;
+use poem::{Body, Endpoint, FromRequest, IntoEndpoint, IntoResponse, Request, Response};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// One served request, as written to the access log.
+#[derive(Debug, Serialize)]
+struct AccessLogEntry {
+    timestamp: DateTime<Utc>,
+    client_ip: Option<String>,
+    user_id: Option<i64>,
+    method: String,
+    path: String,
+    version: String,
+    status: u16,
+    size: Option<u64>,
+    latency_ms: f64,
+    referer: Option<String>,
+    user_agent: Option<String>,
+    htmx: bool,
+}
+
+impl AccessLogEntry {
+    fn new(req: &Request, client_ip: ClientIp, htmx: bool) -> Self {
+        let header = |name| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        Self {
+            timestamp: Utc::now(),
+            client_ip: client_ip.ip().map(|ip| ip.to_string()),
+            user_id: None,
+            method: req.method().to_string(),
+            path: req
+                .uri()
+                .path_and_query()
+                .map(|path| path.to_string())
+                .unwrap_or_else(|| req.uri().path().to_string()),
+            version: format!("{:?}", req.version()),
+            status: 0,
+            size: None,
+            latency_ms: 0.0,
+            referer: header(header::REFERER),
+            user_agent: header(header::USER_AGENT),
+            htmx,
+        }
+    }
+
+    fn finish(&mut self, resp: &mut Response, latency: Duration) {
+        self.user_id = LogScope::current().1;
+        self.status = resp.status().as_u16();
+        self.size = body_size(resp);
+        self.latency_ms = latency.as_secs_f64() * 1000.0;
+    }
+
+    /// Common and Combined lines are exactly the NCSA formats, Extended adds the latency,
+    /// plus `htmx` for htmx requests.
+    fn format(&self, format: AccessLogFormat) -> String {
+        if format == AccessLogFormat::Json {
+            return serde_json::to_string(self).unwrap_or_default();
+        }
+        let mut line = format!(
+            "{} - {} [{}] \"{} {} {}\" {} {}",
+            self.client_ip.as_deref().unwrap_or("-"),
+            self.user_id
+                .map(|user_id| user_id.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            self.timestamp.format("%d/%b/%Y:%H:%M:%S %z"),
+            self.method,
+            quoted(&self.path),
+            self.version,
+            self.status,
+            self.size
+                .map(|size| size.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        );
+        if format == AccessLogFormat::Common {
+            return line;
+        }
+        line.push_str(&format!(
+            " \"{}\" \"{}\"",
+            quoted(self.referer.as_deref().unwrap_or("-")),
+            quoted(self.user_agent.as_deref().unwrap_or("-")),
+        ));
+        if format == AccessLogFormat::Extended {
+            line.push_str(&format!(" {:.3}ms", self.latency_ms));
+            if self.htmx {
+                line.push_str(" htmx");
+            }
+        }
+        line
+    }
+}
+
+fn quoted(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Known up front for anything but streamed bodies.
+fn body_size(resp: &mut Response) -> Option<u64> {
+    let body: BoxBody<_, _> = resp.take_body().into();
+    let size = http_body::Body::size_hint(&body).exact();
+    resp.set_body(Body::from(body));
+    size.or_else(|| {
+        resp.headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+    })
+}
+
+impl AccessLogConfig {
+    fn logs(&self, path: &str) -> bool {
+        self.enabled
+            && !self
+                .exclude_paths
+                .iter()
+                .any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
+
+struct AccessLog<EP: Endpoint> {
+    ep: EP,
+}
+
+impl<EP: Endpoint> Endpoint for AccessLog<EP> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        let format = match fetch_context::<ConfigPointer>().await {
+            Ok(config) if config.access_log.logs(req.uri().path()) => config.access_log.format,
+            _ => return self.ep.call(req).await.map(IntoResponse::into_response),
+        };
+        let htmx = HtmxHeader::from_request_without_body(&req)
+            .await
+            .is_ok_and(|htmx_header| htmx_header.request);
+        let client_ip = ClientIp::from_request_without_body(&req)
+            .await
+            .unwrap_or_default();
+        let mut entry = AccessLogEntry::new(&req, client_ip, htmx);
+        let started = Instant::now();
+
+        let mut resp = match self.ep.call(req).await {
+            Ok(resp) => resp.into_response(),
+            Err(err) => err.into_response(),
+        };
+        entry.finish(&mut resp, started.elapsed());
+        info!(target: "access", "{}", entry.format(format));
+        Ok(resp)
+    }
+}
+
+/// Logs every request `ep` serves, inside the `log_scope` of the request so the line
+/// carries its server, request ID and user.
+pub fn access_log<EP: IntoEndpoint>(ep: EP) -> impl Endpoint {
+    AccessLog {
+        ep: ep.into_endpoint(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry() -> AccessLogEntry {
+        AccessLogEntry {
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap(),
+            client_ip: Some("10.0.0.1".to_string()),
+            user_id: Some(7),
+            method: "GET".to_string(),
+            path: "/stack/?search=\"x\"".to_string(),
+            version: "HTTP/1.1".to_string(),
+            status: 200,
+            size: Some(512),
+            latency_ms: 1.5,
+            referer: None,
+            user_agent: Some("curl/8".to_string()),
+            htmx: true,
+        }
+    }
+
+    #[test]
+    fn test_access_log_entry_formats() {
+        let entry = entry();
+        assert_eq!(
+            entry.format(AccessLogFormat::Common),
+            r#"10.0.0.1 - 7 [02/Jan/2025:03:04:05 +0000] "GET /stack/?search=\"x\" HTTP/1.1" 200 512"#
+        );
+        assert_eq!(
+            entry.format(AccessLogFormat::Combined),
+            r#"10.0.0.1 - 7 [02/Jan/2025:03:04:05 +0000] "GET /stack/?search=\"x\" HTTP/1.1" 200 512 "-" "curl/8""#
+        );
+        assert_eq!(
+            entry.format(AccessLogFormat::Extended),
+            r#"10.0.0.1 - 7 [02/Jan/2025:03:04:05 +0000] "GET /stack/?search=\"x\" HTTP/1.1" 200 512 "-" "curl/8" 1.500ms htmx"#
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&entry.format(AccessLogFormat::Json)).unwrap();
+        assert_eq!(json["status"], 200);
+        assert_eq!(json["user_agent"], "curl/8");
+        assert_eq!(json["latency_ms"], 1.5);
+        assert_eq!(json["htmx"], true);
+    }
+
+    #[test]
+    fn test_access_log_config_excludes_paths() {
+        let config = AccessLogConfig::default();
+        assert!(config.logs("/stack/"));
+        assert!(!config.logs("/assets/js/main.js"));
+        let config = AccessLogConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(!config.logs("/stack/"));
+    }
+}
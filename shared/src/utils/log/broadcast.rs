@@ -0,0 +1,46 @@
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::log::model::ErrorStackEvent;
+use error_stack::Report;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+
+/// Events kept for a subscriber that falls behind, older ones are skipped.
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Publishes new `error_stack` entries to the live views of this process.
+#[derive(Clone)]
+pub struct ErrorStackBroadcast(broadcast::Sender<ErrorStackEvent>);
+
+static ERROR_STACK_BROADCAST: OnceLock<ErrorStackBroadcast> = OnceLock::new();
+
+impl ErrorStackBroadcast {
+    pub fn new() -> Self {
+        Self(broadcast::channel(CHANNEL_CAPACITY).0)
+    }
+
+    /// The channel shared by every service of the process.
+    pub fn global() -> Self {
+        ERROR_STACK_BROADCAST.get_or_init(Self::new).clone()
+    }
+
+    /// Sends to the current subscribers, if there are none the event is dropped.
+    pub fn publish(&self, event: ErrorStackEvent) {
+        _ = self.0.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ErrorStackEvent> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for ErrorStackBroadcast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromContext for ErrorStackBroadcast {
+    async fn from_context(_ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::global())
+    }
+}
@@ -0,0 +1,299 @@
+use crate::utils::config::log::{LogConfig, LogFormat};
+use crate::utils::log::scope::LogScope;
+use crate::utils::request_id::RequestId;
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions, remove_file, rename};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Level of each module, by the longest configured prefix of its path.
+struct LevelFilters {
+    default: LevelFilter,
+    /// Longest prefix first.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LevelFilters {
+    fn new(config: &LogConfig) -> Self {
+        let mut modules: Vec<_> = config
+            .modules
+            .iter()
+            .map(|(prefix, level)| (prefix.clone(), *level))
+            .collect();
+        modules.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Self {
+            default: config.level,
+            modules,
+        }
+    }
+
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    fn max(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// A record with the fields every line carries.
+#[derive(Serialize)]
+struct LogLine<'a> {
+    timestamp: String,
+    level: Level,
+    target: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_id: Option<i64>,
+    message: String,
+}
+
+impl<'a> LogLine<'a> {
+    fn new(record: &'a Record) -> Self {
+        let (server, user_id) = LogScope::current();
+        Self {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level: record.level(),
+            target: record.target(),
+            server,
+            request_id: RequestId::current().map(|id| id.to_string()),
+            user_id,
+            message: record.args().to_string(),
+        }
+    }
+
+    fn json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn pretty(&self, colour: bool) -> String {
+        let level = match (colour, self.level) {
+            (false, level) => format!("{:>5}", level),
+            (true, Level::Error) => format!("\u{1b}[31m{:>5}\u{1b}[0m", self.level),
+            (true, Level::Warn) => format!("\u{1b}[33m{:>5}\u{1b}[0m", self.level),
+            (true, Level::Info) => format!("\u{1b}[32m{:>5}\u{1b}[0m", self.level),
+            (true, Level::Debug) => format!("\u{1b}[34m{:>5}\u{1b}[0m", self.level),
+            (true, Level::Trace) => format!("\u{1b}[35m{:>5}\u{1b}[0m", self.level),
+        };
+        let mut line = format!("{} {} {}", self.timestamp, level, self.target);
+        if let Some(server) = self.server {
+            line.push_str(&format!(" server={}", server));
+        }
+        if let Some(request_id) = &self.request_id {
+            line.push_str(&format!(" request_id={}", request_id));
+        }
+        if let Some(user_id) = self.user_id {
+            line.push_str(&format!(" user_id={}", user_id));
+        }
+        line.push_str(": ");
+        line.push_str(&self.message);
+        line
+    }
+}
+
+/// Appends to `path`, moving it to `path.1` (and `path.1` to `path.2` and so on) once
+/// it would grow past `max_bytes`.
+struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, max_files: u32) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files > 0 {
+            for n in (1..self.max_files).rev() {
+                match rename(self.rotated_path(n), self.rotated_path(n + 1)) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => {}
+                }
+            }
+            rename(&self.path, self.rotated_path(1))?;
+        } else {
+            remove_file(&self.path)?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated_path(&self, n: u32) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", n));
+        path.into()
+    }
+}
+
+/// Writes records to stderr and, when configured, to a rotating file.
+pub struct Logger {
+    format: LogFormat,
+    filters: LevelFilters,
+    colour: bool,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl Logger {
+    pub fn new(config: &LogConfig) -> io::Result<Self> {
+        let file = match config.file_path.as_str() {
+            "" => None,
+            path => Some(Mutex::new(RotatingFile::open(
+                PathBuf::from(path),
+                config.file_max_bytes,
+                config.file_max_files,
+            )?)),
+        };
+        Ok(Self {
+            format: config.format,
+            filters: LevelFilters::new(config),
+            colour: config.format == LogFormat::Pretty && io::stderr().is_terminal(),
+            file,
+        })
+    }
+
+    pub fn max_level(&self) -> LevelFilter {
+        self.filters.max()
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filters.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = LogLine::new(record);
+        let (stderr_line, file_line) = match self.format {
+            LogFormat::Pretty => (line.pretty(self.colour), line.pretty(false)),
+            LogFormat::Json => (line.json(), line.json()),
+        };
+        _ = writeln!(io::stderr().lock(), "{}", stderr_line);
+        if let Some(file) = &self.file {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            _ = file.write_line(&file_line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            _ = file.lock().unwrap_or_else(|e| e.into_inner()).file.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::fs::{read_to_string, remove_dir_all};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_level_filters_longest_prefix_wins() {
+        let config = LogConfig {
+            level: LevelFilter::Info,
+            modules: BTreeMap::from([
+                ("rusqlite".to_string(), LevelFilter::Warn),
+                ("shared".to_string(), LevelFilter::Warn),
+                ("shared::utils::jobs".to_string(), LevelFilter::Debug),
+            ]),
+            ..Default::default()
+        };
+        let filters = LevelFilters::new(&config);
+        assert_eq!(filters.level("shared::utils::jobs"), LevelFilter::Debug);
+        assert_eq!(
+            filters.level("shared::utils::jobs::run"),
+            LevelFilter::Debug
+        );
+        assert_eq!(filters.level("shared::utils::db"), LevelFilter::Warn);
+        assert_eq!(filters.level("shared_extra"), LevelFilter::Info);
+        assert_eq!(filters.level("backoffice"), LevelFilter::Info);
+        assert_eq!(filters.max(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn test_log_line_formats() {
+        let line = LogLine {
+            timestamp: "2025-01-02T03:04:05.000Z".to_string(),
+            level: Level::Warn,
+            target: "backoffice",
+            server: Some("backoffice"),
+            request_id: Some("abc".to_string()),
+            user_id: None,
+            message: "Slow \"query\"".to_string(),
+        };
+        assert_eq!(
+            line.json(),
+            r#"{"timestamp":"2025-01-02T03:04:05.000Z","level":"WARN","target":"backoffice","server":"backoffice","request_id":"abc","message":"Slow \"query\""}"#
+        );
+        assert_eq!(
+            line.pretty(false),
+            "2025-01-02T03:04:05.000Z  WARN backoffice server=backoffice request_id=abc: Slow \"query\""
+        );
+    }
+
+    #[test]
+    fn test_rotating_file_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("log-test-{}", Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let mut file = RotatingFile::open(path.clone(), 10, 2).unwrap();
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line).unwrap();
+        }
+
+        assert_eq!(read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(read_to_string(dir.join("app.log.1")).unwrap(), "third\n");
+        assert_eq!(read_to_string(dir.join("app.log.2")).unwrap(), "second\n");
+        assert!(!dir.join("app.log.3").exists());
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,20 +1,64 @@
+pub mod access_log;
+pub mod broadcast;
+pub mod logger;
+pub mod model;
+pub mod panic;
 pub mod repository;
+pub mod scope;
 pub mod service;
 
+use crate::utils::config::Config;
 use crate::utils::context::fetch_context;
-use crate::utils::error::LogData;
+use crate::utils::error::boot_error::MainError;
+use crate::utils::error::{LogData, Severity};
+use crate::utils::log::logger::Logger;
+use crate::utils::log::model::RequestContext;
+use crate::utils::log::panic::install_panic_hook;
 use crate::utils::log::service::error_stack_log_service::ErrorStackLogService;
-use log::error;
+use error_stack::{Report, ResultExt};
+use log::{error, warn};
 
-pub fn init_log() {
-    colog::init();
+/// Installs the logger set up by the `log` config section, once per process.
+pub async fn init_log() -> Result<(), Report<MainError>> {
+    let config = Config::fetch()
+        .await
+        .change_context(MainError::ConfigError)?
+        .upgrade()
+        .ok_or_else(|| Report::new(MainError::ConfigError))?;
+    let logger = Logger::new(&config.log)
+        .change_context(MainError::IoError)
+        .attach_with(|| format!("log file: {}", config.log.file_path))?;
+    log::set_max_level(logger.max_level());
+    log::set_boxed_logger(Box::new(logger)).change_context(MainError::ConfigError)?;
+    install_panic_hook();
+    Ok(())
 }
 
-pub async fn log_poem_error(err: &poem::Error) {
+pub async fn log_poem_error(err: &poem::Error, request_context: &RequestContext) {
     if let Some(log_data) = err.data::<LogData>() {
-        error!("{} - {}", err.status(), &log_data.summary);
-        if let Ok(error_stack_log_service) = fetch_context::<ErrorStackLogService>().await {
-            _ = error_stack_log_service.log_data(log_data);
+        let line = log_line(err.status().as_u16(), log_data, request_context);
+        match log_data.severity {
+            Severity::Warning => warn!("{}", line),
+            _ => error!("{}", line),
         }
+        store_log_data(log_data, request_context).await;
+    }
+}
+
+pub async fn log_panic(log_data: &LogData, request_context: &RequestContext) {
+    error!("{}", log_line(500, log_data, request_context));
+    store_log_data(log_data, request_context).await;
+}
+
+fn log_line(status: u16, log_data: &LogData, request_context: &RequestContext) -> String {
+    format!(
+        "{} {} {} - {}",
+        status, request_context.method, request_context.path, &log_data.summary
+    )
+}
+
+async fn store_log_data(log_data: &LogData, request_context: &RequestContext) {
+    if let Ok(error_stack_log_service) = fetch_context::<ErrorStackLogService>().await {
+        _ = error_stack_log_service.log_data(log_data, Some(request_context));
     }
 }
@@ -0,0 +1,106 @@
+use crate::utils::error::Severity;
+use crate::utils::htmx::HtmxHeaderData;
+use crate::utils::request_id::RequestId;
+use chrono::{DateTime, Utc};
+use poem::Request;
+use std::collections::BTreeMap;
+
+/// Request headers copied into the error log, anything else (cookies, CSRF tokens) is left out.
+const LOGGED_HEADERS: [&str; 10] = [
+    "accept",
+    "accept-language",
+    "content-length",
+    "content-type",
+    "host",
+    "origin",
+    "referer",
+    "user-agent",
+    "x-forwarded-for",
+    "x-real-ip",
+];
+
+/// The request that produced a logged error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    /// The server that handled the request, `public` or `backoffice`.
+    pub server: &'static str,
+    /// Set when the request went through `init_request_id`.
+    pub request_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub user_id: Option<i64>,
+    /// Only set for requests made by htmx.
+    pub htmx: Option<HtmxHeaderData>,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl RequestContext {
+    pub fn new(server: &'static str, req: &Request) -> Self {
+        let htmx = HtmxHeaderData::new(req);
+        Self {
+            server,
+            request_id: req.data::<RequestId>().map(|id| id.to_string()),
+            method: req.method().to_string(),
+            path: req.uri().path().to_string(),
+            query: req.uri().query().map(|query| query.to_string()),
+            user_id: None,
+            htmx: htmx.request.then_some(htmx),
+            headers: LOGGED_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = req.headers().get(*name)?.to_str().ok()?;
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn htmx_json(&self) -> Option<String> {
+        self.htmx
+            .as_ref()
+            .and_then(|htmx| serde_json::to_string(htmx).ok())
+    }
+
+    pub fn headers_json(&self) -> String {
+        serde_json::to_string(&self.headers).unwrap_or_default()
+    }
+}
+
+/// An `error_stack` entry that was just stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorStackEvent {
+    pub id: i64,
+    pub group_id: i64,
+    pub severity: Severity,
+    pub error_summary: String,
+    pub reported_at: DateTime<Utc>,
+    /// The group is ignored, the occurrence is stored but nobody should be told.
+    pub ignored: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_context_filters_headers() {
+        let req = Request::builder()
+            .uri_str("/stack/?severity=error")
+            .header("User-Agent", "test")
+            .header("Cookie", "login-token=secret")
+            .header("X-Csrf-Token", "secret")
+            .header("HX-Request", "true")
+            .header("HX-Target", "main-content")
+            .finish();
+
+        let request_context = RequestContext::new("backoffice", &req);
+        assert_eq!(request_context.path, "/stack/");
+        assert_eq!(request_context.query.as_deref(), Some("severity=error"));
+        assert_eq!(request_context.headers_json(), r#"{"user-agent":"test"}"#);
+        assert_eq!(
+            request_context.htmx.and_then(|htmx| htmx.target).as_deref(),
+            Some("main-content")
+        );
+    }
+}
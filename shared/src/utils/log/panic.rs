@@ -0,0 +1,150 @@
+use crate::utils::error::{LogData, Severity};
+use futures_util::FutureExt;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::poll_fn;
+use std::panic::{AssertUnwindSafe, PanicHookInfo};
+use std::pin::pin;
+
+struct PanicReport {
+    location: String,
+    backtrace: String,
+}
+
+thread_local! {
+    /// Set by the panic hook, the panic is caught on the same thread while unwinding.
+    static LAST_PANIC: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+    /// How many `catch_panic` futures are being polled on this thread right now.
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Marks the thread as inside `catch_panic` until dropped, also while unwinding.
+struct CatchingGuard;
+
+impl CatchingGuard {
+    fn enter() -> Self {
+        CATCHING.with(|catching| catching.set(catching.get() + 1));
+        Self
+    }
+}
+
+impl Drop for CatchingGuard {
+    fn drop(&mut self) {
+        CATCHING.with(|catching| catching.set(catching.get() - 1));
+    }
+}
+
+/// Keeps the location and backtrace of a panic inside `catch_panic`, then runs the
+/// previous hook so the panic is still printed. Panics elsewhere skip the capture.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info: &PanicHookInfo| {
+        if CATCHING.with(Cell::get) > 0 {
+            let report = PanicReport {
+                location: info
+                    .location()
+                    .map(|location| location.to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            LAST_PANIC.with(|last_panic| *last_panic.borrow_mut() = Some(report));
+        }
+        previous(info);
+    }));
+}
+
+/// Runs `fut`, turning a panic into critical `LogData`.
+pub async fn catch_panic<F: Future>(fut: F) -> Result<F::Output, LogData> {
+    let mut fut = pin!(fut);
+    AssertUnwindSafe(poll_fn(move |cx| {
+        let _catching = CatchingGuard::enter();
+        fut.as_mut().poll(cx)
+    }))
+    .catch_unwind()
+    .await
+    .map_err(|payload| panic_log_data(payload.as_ref()))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn panic_log_data(payload: &(dyn Any + Send)) -> LogData {
+    let message = panic_message(payload);
+    let report = LAST_PANIC
+        .with(|last_panic| last_panic.borrow_mut().take())
+        .unwrap_or_else(|| PanicReport {
+            location: "unknown".to_string(),
+            backtrace: "disabled".to_string(),
+        });
+    // Laid out like an error-stack report, so `error_fingerprint` groups by location.
+    LogData {
+        severity: Severity::Critical,
+        name: "Panic".to_string(),
+        summary: format!("Panic: {}, at {}", message, report.location),
+        details: format!(
+            "Panic: {}\n╰╴at {}\n\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n{}",
+            message, report.location, report.backtrace
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::log::service::error_stack_log_service::error_fingerprint;
+
+    #[test]
+    fn test_panic_log_data_is_critical_and_grouped_by_location() {
+        let at_a = |message: &str| {
+            LAST_PANIC.with(|last_panic| {
+                *last_panic.borrow_mut() = Some(PanicReport {
+                    location: "src/a.rs:1:2".to_string(),
+                    backtrace: message.to_string(),
+                })
+            });
+            panic_log_data(&message.to_string())
+        };
+        let first = at_a("index 1 out of range");
+        let second = at_a("index 2 out of range");
+
+        assert_eq!(first.severity, Severity::Critical);
+        assert_eq!(
+            first.summary,
+            "Panic: index 1 out of range, at src/a.rs:1:2"
+        );
+        assert_eq!(
+            error_fingerprint(&first.name, &first.details),
+            error_fingerprint(&second.name, &second.details)
+        );
+    }
+
+    #[test]
+    fn test_backtrace_only_captured_inside_catch_panic() {
+        static INSTALL: std::sync::Once = std::sync::Once::new();
+        INSTALL.call_once(install_panic_hook);
+
+        assert!(std::panic::catch_unwind(|| panic!("outside")).is_err());
+        assert!(LAST_PANIC.with(|last_panic| last_panic.borrow().is_none()));
+        assert_eq!(CATCHING.with(Cell::get), 0);
+
+        let log_data = catch_panic(async {
+            if CATCHING.with(Cell::get) > 0 {
+                panic!("inside");
+            }
+        })
+        .now_or_never()
+        .unwrap()
+        .unwrap_err();
+        assert!(log_data.summary.starts_with("Panic: inside, at "));
+        assert!(log_data.summary.contains("panic.rs"));
+        assert_eq!(CATCHING.with(Cell::get), 0);
+    }
+}
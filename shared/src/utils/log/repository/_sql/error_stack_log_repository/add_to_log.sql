@@ -1,2 +1,5 @@
-insert into error_stack(error_name, error_summary, error_stack, reported_at)
-VALUES (:error_name, :error_summary, :error_stack, datetime());
\ No newline at end of file
+insert into error_stack(group_id, severity, error_name, error_summary, error_stack, reported_at,
+                        server, request_id, method, path, query, user_id, htmx, headers)
+VALUES (:group_id, :severity, :error_name, :error_summary, :error_stack, datetime(),
+        :server, :request_id, :method, :path, :query, :user_id, :htmx, :headers)
+returning id, reported_at;
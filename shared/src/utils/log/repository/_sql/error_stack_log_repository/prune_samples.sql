@@ -0,0 +1,8 @@
+delete
+from error_stack
+where group_id = :group_id
+  and id not in (select id
+                 from error_stack
+                 where group_id = :group_id
+                 order by id desc
+                 limit :samples_per_group)
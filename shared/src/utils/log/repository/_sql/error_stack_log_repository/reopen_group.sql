@@ -0,0 +1,5 @@
+update error_group
+set status = 'new'
+where fingerprint = :fingerprint
+  and status = 'resolved'
+returning id
@@ -0,0 +1,7 @@
+insert into error_group(fingerprint, severity, error_name, error_summary, first_seen, last_seen, occurrences)
+values (:fingerprint, :severity, :error_name, :error_summary, datetime(), datetime(), 1)
+on conflict (fingerprint) do update set severity      = excluded.severity,
+                                        error_summary = excluded.error_summary,
+                                        last_seen     = excluded.last_seen,
+                                        occurrences   = occurrences + 1
+returning id, status = 'ignored' as ignored
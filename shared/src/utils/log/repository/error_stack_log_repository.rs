@@ -1,7 +1,9 @@
 use crate::utils::context::{Context, ContextError, FromContext};
 use crate::utils::db::{BorrowConnectionExt, SqliteClient};
+use crate::utils::error::LogData;
+use crate::utils::log::model::{ErrorStackEvent, RequestContext};
 use error_stack::{Report, ResultExt};
-use rusqlite::{Connection, named_params};
+use rusqlite::{Connection, OptionalExtension, named_params};
 use std::sync::MutexGuard;
 use thiserror::Error;
 
@@ -37,25 +39,97 @@ impl ErrorStackLogRepository {
 
 #[mry::mry]
 impl ErrorStackLogRepository {
+    /// Counts an occurrence against the group of `fingerprint` and stores it as a sample,
+    /// keeping only the latest `samples_per_group` samples of the group.
     pub fn add_to_log(
         &self,
-        error_name: &str,
-        error_summary: &str,
-        error_stack: &str,
-    ) -> Result<(), Report<ErrorStackLogRepositoryError>> {
-        let conn = self.borrow_conn()?;
-
-        conn.execute(
-            include_str!("_sql/error_stack_log_repository/add_to_log.sql"),
+        fingerprint: &str,
+        log_data: &LogData,
+        samples_per_group: u32,
+        request_context: Option<RequestContext>,
+    ) -> Result<ErrorStackEvent, Report<ErrorStackLogRepositoryError>> {
+        let mut conn = self.borrow_conn()?;
+        let tx = conn
+            .transaction()
+            .change_context(ErrorStackLogRepositoryError::QueryError)?;
+
+        // A resolved error that happens again needs another look.
+        let reopened: Option<i64> = tx
+            .query_one(
+                include_str!("_sql/error_stack_log_repository/reopen_group.sql"),
+                named_params! {
+                    ":fingerprint": fingerprint,
+                },
+                |row| row.get("id"),
+            )
+            .optional()
+            .change_context(ErrorStackLogRepositoryError::QueryError)?;
+        if let Some(group_id) = reopened {
+            tx.execute(
+                include_str!("_sql/error_stack_log_repository/record_reopen.sql"),
+                named_params! {
+                    ":group_id": group_id,
+                },
+            )
+            .change_context(ErrorStackLogRepositoryError::QueryError)?;
+        }
+
+        let (group_id, ignored): (i64, bool) = tx
+            .query_one(
+                include_str!("_sql/error_stack_log_repository/upsert_group.sql"),
+                named_params! {
+                    ":fingerprint": fingerprint,
+                    ":severity": log_data.severity.as_str(),
+                    ":error_name": log_data.name,
+                    ":error_summary": log_data.summary,
+                },
+                |row| Ok((row.get("id")?, row.get("ignored")?)),
+            )
+            .change_context(ErrorStackLogRepositoryError::QueryError)?;
+
+        let event = tx
+            .query_one(
+                include_str!("_sql/error_stack_log_repository/add_to_log.sql"),
+                named_params! {
+                    ":group_id": group_id,
+                    ":severity": log_data.severity.as_str(),
+                    ":error_name": log_data.name,
+                    ":error_summary": log_data.summary,
+                    ":error_stack": log_data.details,
+                    ":server": request_context.as_ref().map(|r| r.server),
+                    ":request_id": request_context.as_ref().and_then(|r| r.request_id.as_deref()),
+                    ":method": request_context.as_ref().map(|r| r.method.as_str()),
+                    ":path": request_context.as_ref().map(|r| r.path.as_str()),
+                    ":query": request_context.as_ref().and_then(|r| r.query.as_deref()),
+                    ":user_id": request_context.as_ref().and_then(|r| r.user_id),
+                    ":htmx": request_context.as_ref().and_then(|r| r.htmx_json()),
+                    ":headers": request_context.as_ref().map(|r| r.headers_json()),
+                },
+                |row| {
+                    Ok(ErrorStackEvent {
+                        id: row.get("id")?,
+                        group_id,
+                        severity: log_data.severity,
+                        error_summary: log_data.summary.clone(),
+                        reported_at: row.get("reported_at")?,
+                        ignored,
+                    })
+                },
+            )
+            .change_context(ErrorStackLogRepositoryError::QueryError)?;
+
+        tx.execute(
+            include_str!("_sql/error_stack_log_repository/prune_samples.sql"),
             named_params! {
-                ":error_name": error_name,
-                ":error_summary": error_summary,
-                ":error_stack": error_stack,
+                ":group_id": group_id,
+                ":samples_per_group": samples_per_group,
             },
         )
         .change_context(ErrorStackLogRepositoryError::QueryError)?;
 
-        Ok(())
+        tx.commit()
+            .change_context(ErrorStackLogRepositoryError::QueryError)?;
+        Ok(event)
     }
 }
 
@@ -73,3 +147,105 @@ impl FromContext for ErrorStackLogRepository {
         Ok(Self::new(ctx.inject().await?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::migration::SHARED_MIGRATIONS;
+    use crate::utils::db::tests::client;
+    use crate::utils::error::Severity;
+    use std::fs::remove_dir_all;
+
+    fn repository() -> (ErrorStackLogRepository, std::path::PathBuf) {
+        let (client, dir) = client(1, 1000);
+        client.migrate(&SHARED_MIGRATIONS).unwrap();
+        (ErrorStackLogRepository::new(client), dir)
+    }
+
+    fn log_data() -> LogData {
+        LogData {
+            severity: Severity::Error,
+            name: "Name".to_string(),
+            summary: "Summary".to_string(),
+            details: "Details".to_string(),
+        }
+    }
+
+    fn set_status(repository: &ErrorStackLogRepository, group_id: i64, status: &str) {
+        repository
+            .borrow_conn()
+            .unwrap()
+            .execute(
+                "update error_group set status = ?1 where id = ?2",
+                (status, group_id),
+            )
+            .unwrap();
+    }
+
+    /// Status of the group and the statuses recorded for it, newest first.
+    fn status_and_changes(
+        repository: &ErrorStackLogRepository,
+        group_id: i64,
+    ) -> (String, Vec<(String, Option<i64>)>) {
+        let conn = repository.borrow_conn().unwrap();
+        let status = conn
+            .query_one(
+                "select status from error_group where id = ?1",
+                [group_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let mut stmt = conn
+            .prepare(
+                "select status, user_id from error_group_status_change where group_id = ?1 order by id desc",
+            )
+            .unwrap();
+        let changes = stmt
+            .query_map([group_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        (status, changes)
+    }
+
+    #[test]
+    fn test_add_to_log_reopens_resolved_group() {
+        let (repository, dir) = repository();
+        let first = repository
+            .add_to_log("fingerprint", &log_data(), 10, None)
+            .unwrap();
+        set_status(&repository, first.group_id, "resolved");
+
+        let second = repository
+            .add_to_log("fingerprint", &log_data(), 10, None)
+            .unwrap();
+        assert_eq!(second.group_id, first.group_id);
+        assert!(!second.ignored);
+        assert_eq!(
+            status_and_changes(&repository, first.group_id),
+            ("new".to_string(), vec![("new".to_string(), None)])
+        );
+
+        remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_add_to_log_keeps_ignored_group_ignored() {
+        let (repository, dir) = repository();
+        let first = repository
+            .add_to_log("fingerprint", &log_data(), 10, None)
+            .unwrap();
+        set_status(&repository, first.group_id, "ignored");
+
+        let second = repository
+            .add_to_log("fingerprint", &log_data(), 10, None)
+            .unwrap();
+        assert!(second.ignored);
+        assert_eq!(
+            status_and_changes(&repository, first.group_id),
+            ("ignored".to_string(), vec![])
+        );
+
+        remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use crate::utils::request_id::RequestId;
+use poem::{Endpoint, IntoEndpoint, Request};
+use std::sync::OnceLock;
+
+tokio::task_local! {
+    static LOG_SCOPE: LogScope;
+}
+
+/// Fields added to every record logged while a server handles a request.
+pub struct LogScope {
+    server: &'static str,
+    request_id: Option<RequestId>,
+    user_id: OnceLock<i64>,
+}
+
+impl LogScope {
+    /// Runs `fut` with its records attributed to `server`, `public` or `backoffice`, and
+    /// to the request `request_id` when `init_request_id` assigned one.
+    pub async fn run<F: Future>(
+        server: &'static str,
+        request_id: Option<RequestId>,
+        fut: F,
+    ) -> F::Output {
+        let scope = Self {
+            server,
+            request_id,
+            user_id: OnceLock::new(),
+        };
+        LOG_SCOPE.scope(scope, fut).await
+    }
+
+    /// Attributes the rest of the request to a signed in user, the first call wins.
+    pub fn set_user_id(user_id: i64) {
+        _ = LOG_SCOPE.try_with(|scope| scope.user_id.set(user_id));
+    }
+
+    /// The server and user of the request being handled by this task, if any.
+    pub(crate) fn current() -> (Option<&'static str>, Option<i64>) {
+        LOG_SCOPE
+            .try_with(|scope| (Some(scope.server), scope.user_id.get().copied()))
+            .unwrap_or_default()
+    }
+
+    /// The ID of the request being handled by this task, if any.
+    pub(crate) fn request_id() -> Option<RequestId> {
+        LOG_SCOPE
+            .try_with(|scope| scope.request_id.clone())
+            .ok()
+            .flatten()
+    }
+}
+
+struct LogScopeEndpoint<EP: Endpoint> {
+    server: &'static str,
+    ep: EP,
+}
+
+impl<EP: Endpoint> Endpoint for LogScopeEndpoint<EP> {
+    type Output = EP::Output;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        let request_id = req.data::<RequestId>().cloned();
+        LogScope::run(self.server, request_id, self.ep.call(req)).await
+    }
+}
+
+/// Opens the `LogScope` of every request `ep` serves for `server`. It goes right inside
+/// `init_request_id`, whose ID it carries, and outside every layer that logs.
+pub fn log_scope<EP: IntoEndpoint>(server: &'static str, ep: EP) -> impl Endpoint {
+    LogScopeEndpoint {
+        server,
+        ep: ep.into_endpoint(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    #[test]
+    fn test_log_scope_fields() {
+        let request_id = RequestId::generate();
+        let (current, id) = LogScope::run("backoffice", Some(request_id.clone()), async {
+            LogScope::set_user_id(7);
+            LogScope::set_user_id(8);
+            (LogScope::current(), RequestId::current())
+        })
+        .now_or_never()
+        .unwrap();
+        assert_eq!(current, (Some("backoffice"), Some(7)));
+        assert_eq!(id, Some(request_id));
+
+        assert_eq!(LogScope::current(), (None, None));
+        assert_eq!(RequestId::current(), None);
+    }
+}
@@ -1,81 +1,194 @@
+use crate::utils::cipher::encode_hex;
+use crate::utils::config::ConfigPointer;
+use crate::utils::config::error_stack_log::ErrorStackConfig;
 use crate::utils::context::{Context, ContextError, FromContext};
 use crate::utils::error::LogData;
+use crate::utils::log::broadcast::ErrorStackBroadcast;
+use crate::utils::log::model::RequestContext;
 use crate::utils::log::repository::error_stack_log_repository::ErrorStackLogRepository;
 use error_stack::{Report, ResultExt};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 #[error("Error Stack Log Service Error")]
 pub struct ErrorStackLogServiceError;
 
+/// Identifies an error by its name and the source locations of its frames.
+///
+/// The backtrace below the report is left out, it differs between otherwise equal errors.
+pub fn error_fingerprint(name: &str, details: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(name.as_bytes());
+    for line in details.lines().take_while(|line| !line.starts_with('━')) {
+        if let Some((_, location)) = line.split_once("╴at ") {
+            hasher.update(b"\n");
+            hasher.update(strip_ansi(location).as_bytes());
+        }
+    }
+    encode_hex(&hasher.finalize())
+}
+
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 pub struct ErrorStackLogService {
     error_stack_log_repository: ErrorStackLogRepository,
+    error_stack_config: Arc<ErrorStackConfig>,
+    error_stack_broadcast: ErrorStackBroadcast,
 }
 
 impl ErrorStackLogService {
-    pub fn new(error_stack_log_repository: ErrorStackLogRepository) -> Self {
+    pub fn new(
+        error_stack_log_repository: ErrorStackLogRepository,
+        error_stack_config: Arc<ErrorStackConfig>,
+        error_stack_broadcast: ErrorStackBroadcast,
+    ) -> Self {
         Self {
             error_stack_log_repository,
+            error_stack_config,
+            error_stack_broadcast,
         }
     }
 
-    pub fn log_data(&self, log_data: &LogData) -> Result<(), Report<ErrorStackLogServiceError>> {
-        self.error_stack_log_repository
-            .add_to_log(&log_data.name, &log_data.summary, &log_data.details)
-            .change_context(ErrorStackLogServiceError)
+    /// Stores the error and publishes it to the live views.
+    pub fn log_data(
+        &self,
+        log_data: &LogData,
+        request_context: Option<&RequestContext>,
+    ) -> Result<(), Report<ErrorStackLogServiceError>> {
+        let event = self
+            .error_stack_log_repository
+            .add_to_log(
+                &error_fingerprint(&log_data.name, &log_data.details),
+                log_data,
+                self.error_stack_config.samples_per_group,
+                request_context.cloned(),
+            )
+            .change_context(ErrorStackLogServiceError)?;
+        self.error_stack_broadcast.publish(event);
+        Ok(())
     }
 }
 
 impl FromContext for ErrorStackLogService {
     async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
-        Ok(Self::new(ctx.inject().await?))
+        let config: ConfigPointer = ctx.inject().await?;
+        Ok(Self::new(
+            ctx.inject().await?,
+            Arc::clone(&config.error_stack),
+            ctx.inject().await?,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::error::Severity;
+    use crate::utils::log::model::ErrorStackEvent;
     use crate::utils::log::repository::error_stack_log_repository::ErrorStackLogRepositoryError;
 
     #[test]
     fn test_error_stack_log_service_success() {
         let mut error_stack_log_repository = ErrorStackLogRepository::new_mock();
         let log_data = LogData {
+            severity: Severity::Warning,
             name: "abc".to_string(),
             summary: "efg".to_string(),
             details: "123".to_string(),
         };
+        let event = ErrorStackEvent {
+            id: 1,
+            group_id: 1,
+            severity: log_data.severity,
+            error_summary: log_data.summary.clone(),
+            reported_at: chrono::Utc::now(),
+            ignored: false,
+        };
         error_stack_log_repository
             .mock_add_to_log(
-                log_data.name.clone(),
-                log_data.summary.clone(),
-                log_data.details.clone(),
+                error_fingerprint(&log_data.name, &log_data.details),
+                log_data.clone(),
+                20,
+                None,
             )
-            .returns_once(Ok(()));
+            .returns_once(Ok(event.clone()));
 
-        let service = ErrorStackLogService::new(error_stack_log_repository);
-        let result = service.log_data(&log_data);
+        let error_stack_broadcast = ErrorStackBroadcast::new();
+        let mut receiver = error_stack_broadcast.subscribe();
+        let service = ErrorStackLogService::new(
+            error_stack_log_repository,
+            Arc::new(ErrorStackConfig::default()),
+            error_stack_broadcast,
+        );
+        let result = service.log_data(&log_data, None);
         assert!(result.is_ok());
+        assert_eq!(receiver.try_recv().ok(), Some(event));
     }
 
     #[test]
     fn test_error_stack_log_service_error() {
         let mut error_stack_log_repository = ErrorStackLogRepository::new_mock();
         let log_data = LogData {
+            severity: Severity::Warning,
             name: "abc".to_string(),
             summary: "efg".to_string(),
             details: "123".to_string(),
         };
         error_stack_log_repository
             .mock_add_to_log(
-                log_data.name.clone(),
-                log_data.summary.clone(),
-                log_data.details.clone(),
+                error_fingerprint(&log_data.name, &log_data.details),
+                log_data.clone(),
+                20,
+                None,
             )
             .returns_once(Err(Report::new(ErrorStackLogRepositoryError::QueryError)));
 
-        let service = ErrorStackLogService::new(error_stack_log_repository);
-        let result = service.log_data(&log_data);
+        let error_stack_broadcast = ErrorStackBroadcast::new();
+        let mut receiver = error_stack_broadcast.subscribe();
+        let service = ErrorStackLogService::new(
+            error_stack_log_repository,
+            Arc::new(ErrorStackConfig::default()),
+            error_stack_broadcast,
+        );
+        let result = service.log_data(&log_data, None);
         assert!(result.is_err());
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_error_fingerprint_ignores_backtrace_and_colour() {
+        let plain = "DB error\n├╴at src/a.rs:1:2\n╰─▶ Query error\n    ╰╴at src/b.rs:3:4\n\n━━━━\n   0: x\n             at /a/b.rs:1:1";
+        let coloured = "\u{1b}[1mDB error\u{1b}[22m\n├╴at \u{1b}[3msrc/a.rs:1:2\u{1b}[23m\n╰─▶ Query error\n    ╰╴at \u{1b}[3msrc/b.rs:3:4\u{1b}[23m\n\n━━━━\n   0: y\n             at /c/d.rs:9:9";
+        assert_eq!(
+            error_fingerprint("DB error", plain),
+            error_fingerprint("DB error", coloured)
+        );
+    }
+
+    #[test]
+    fn test_error_fingerprint_differs_by_location_and_name() {
+        let details = "DB error\n├╴at src/a.rs:1:2";
+        let moved = "DB error\n├╴at src/a.rs:7:2";
+        assert_ne!(
+            error_fingerprint("DB error", details),
+            error_fingerprint("DB error", moved)
+        );
+        assert_ne!(
+            error_fingerprint("DB error", details),
+            error_fingerprint("Query error", details)
+        );
     }
 }
@@ -1,4 +1,6 @@
 pub mod adapter;
+pub mod cipher;
+pub mod client_ip;
 pub mod config;
 pub mod consts;
 pub mod context;
@@ -10,8 +12,11 @@ pub mod error;
 pub mod flag;
 pub mod flash;
 pub mod htmx;
+pub mod jobs;
 pub mod locale;
 pub mod log;
 pub mod password;
 pub mod query_string;
 pub mod request_cache;
+pub mod request_id;
+pub mod session;
@@ -1,6 +1,7 @@
+use crate::utils::config::password::PasswordConfig;
 use argon2::password_hash::SaltString;
 use argon2::password_hash::rand_core::OsRng;
-use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
 use error_stack::Report;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -44,10 +45,42 @@ pub enum Password {
 }
 
 impl Password {
-    pub fn hash_password(password: String) -> Result<Self, Report<PasswordError>> {
+    fn argon2(config: &PasswordConfig) -> Result<Argon2<'static>, Report<PasswordError>> {
+        let params = Params::new(
+            config.argon2_memory_kib,
+            config.argon2_iterations,
+            config.argon2_parallelism,
+            None,
+        )
+        .map_err(|e| PasswordError(format!("Invalid argon2 params: {}", e)))?;
+
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    /// Whether a stored argon2 hash was made with weaker settings than the config asks for.
+    fn is_weaker_than(parsed_hash: &PasswordHash, config: &PasswordConfig) -> bool {
+        if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+        match Params::try_from(parsed_hash) {
+            Ok(params) => {
+                params.m_cost() < config.argon2_memory_kib
+                    || params.t_cost() < config.argon2_iterations
+                    || params.p_cost() < config.argon2_parallelism
+            }
+            Err(_) => true,
+        }
+    }
+
+    pub fn hash_password(
+        password: String,
+        config: &PasswordConfig,
+    ) -> Result<Self, Report<PasswordError>> {
         let salt = SaltString::generate(&mut OsRng);
 
-        let argon2 = Argon2::default();
+        let argon2 = Self::argon2(config)?;
 
         let password_hash = argon2
             .hash_password(password.as_bytes(), &salt)
@@ -59,9 +92,14 @@ impl Password {
         })
     }
 
+    /// Checks `password` against the stored hash.
+    ///
+    /// A correct password stored with an older `Password` version or weaker argon2 params
+    /// than `config` comes back as `ValidRehashed` with a fresh hash to persist.
     pub fn verify_password(
         password_hash: Box<[u8]>,
         password: String,
+        config: &PasswordConfig,
     ) -> Result<PasswordState, Report<PasswordError>> {
         let password_data = rmp_serde::from_slice::<Password>(&password_hash)
             .map_err(|_| PasswordError("Failed to deserialize password hash".to_string()))?;
@@ -71,7 +109,11 @@ impl Password {
                 let parsed_hash = PasswordHash::new(&argon2)
                     .map_err(|_| PasswordError("Failed to parse password hash".to_string()))?;
 
+                // Argon2 reads the cost params from the hash itself when verifying.
                 match Argon2::default().verify_password(password.as_bytes(), &parsed_hash) {
+                    Ok(_) if Self::is_weaker_than(&parsed_hash, config) => Ok(
+                        PasswordState::ValidRehashed(Self::hash_password(password, config)?),
+                    ),
                     Ok(_) => Ok(PasswordState::Valid),
                     Err(_) => Ok(PasswordState::Invalid),
                 }
@@ -90,3 +132,64 @@ impl Password {
             .into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(argon2_memory_kib: u32, argon2_iterations: u32) -> PasswordConfig {
+        PasswordConfig {
+            argon2_memory_kib,
+            argon2_iterations,
+            argon2_parallelism: 1,
+        }
+    }
+
+    fn stored(password: &str, config: &PasswordConfig) -> Box<[u8]> {
+        Password::hash_password(password.to_string(), config)
+            .unwrap()
+            .encode_to_msg_pack()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verify_password_rejects_wrong_password() {
+        let config = config(64, 1);
+        let hash = stored("Banana#Split9", &config);
+
+        let state = Password::verify_password(hash.clone(), "banana#split9".to_string(), &config);
+        assert!(state.unwrap().is_invalid());
+        let state = Password::verify_password(hash, "Banana#Split9".to_string(), &config);
+        assert!(matches!(state.unwrap(), PasswordState::Valid));
+    }
+
+    #[test]
+    fn test_is_weaker_than_boundary() {
+        let Password::Version1 { argon2 } =
+            Password::hash_password("Banana#Split9".to_string(), &config(64, 2)).unwrap();
+        let parsed_hash = PasswordHash::new(&argon2).unwrap();
+
+        assert!(!Password::is_weaker_than(&parsed_hash, &config(64, 2)));
+        assert!(!Password::is_weaker_than(&parsed_hash, &config(32, 1)));
+        assert!(Password::is_weaker_than(&parsed_hash, &config(65, 2)));
+        assert!(Password::is_weaker_than(&parsed_hash, &config(64, 3)));
+    }
+
+    #[test]
+    fn test_verify_password_rehashes_weaker_hash() {
+        let hash = stored("Banana#Split9", &config(64, 1));
+        let stronger = config(128, 1);
+
+        let state =
+            Password::verify_password(hash, "Banana#Split9".to_string(), &stronger).unwrap();
+        let PasswordState::ValidRehashed(rehashed) = state else {
+            panic!("expected a rehash");
+        };
+        let state = Password::verify_password(
+            rehashed.encode_to_msg_pack().unwrap(),
+            "Banana#Split9".to_string(),
+            &stronger,
+        );
+        assert!(matches!(state.unwrap(), PasswordState::Valid));
+    }
+}
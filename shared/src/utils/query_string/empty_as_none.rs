@@ -0,0 +1,136 @@
+use serde::de::{self, IntoDeserializer, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, forward_to_deserialize_any};
+use std::marker::PhantomData;
+
+/// Reads a blank value, as sent by an empty form field, as `None`.
+///
+/// Use with `#[serde(default, deserialize_with = "empty_as_none")]`.
+pub fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => T::deserialize(ValueDeserializer(value, PhantomData)).map(Some),
+    }
+}
+
+/// Hands the text of a value to `T`, parsing it when `T` asks for a number or bool.
+struct ValueDeserializer<'a, E>(&'a str, PhantomData<E>);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),* $(,)?) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+            match self.0.parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+            }
+        }
+    )*};
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for ValueDeserializer<'_, E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        self.0
+            .into_deserializer()
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::error::Severity;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Query {
+        #[serde(default, deserialize_with = "empty_as_none")]
+        search: Option<String>,
+        #[serde(default, deserialize_with = "empty_as_none")]
+        severity: Option<Severity>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct PageQuery {
+        #[serde(default, deserialize_with = "empty_as_none")]
+        page: Option<u32>,
+    }
+
+    #[test]
+    fn test_empty_as_none() {
+        let query: Query = serde_qs::from_str("search=&severity=").unwrap();
+        assert_eq!(
+            query,
+            Query {
+                search: None,
+                severity: None
+            }
+        );
+
+        let query: Query = serde_qs::from_str("search=+boom+&severity=warning").unwrap();
+        assert_eq!(
+            query,
+            Query {
+                search: Some("boom".to_owned()),
+                severity: Some(Severity::Warning)
+            }
+        );
+
+        let query: Query = serde_qs::from_str("").unwrap();
+        assert_eq!(
+            query,
+            Query {
+                search: None,
+                severity: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_as_none_number() {
+        let query: PageQuery = serde_qs::from_str("page=").unwrap();
+        assert_eq!(query, PageQuery { page: None });
+
+        let query: PageQuery = serde_qs::from_str("page=+3+").unwrap();
+        assert_eq!(query, PageQuery { page: Some(3) });
+
+        assert!(serde_qs::from_str::<PageQuery>("page=-1").is_err());
+        assert!(serde_qs::from_str::<PageQuery>("page=x").is_err());
+    }
+}
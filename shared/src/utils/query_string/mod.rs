@@ -1,3 +1,4 @@
+pub mod empty_as_none;
 pub mod form;
 pub mod query;
 pub mod serde_qs_config;
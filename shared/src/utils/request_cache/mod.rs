@@ -4,13 +4,25 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-struct RequestCache(Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>);
+pub struct RequestCache(Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>);
 
 impl RequestCache {
     fn new() -> Self {
         Self(Arc::new(Mutex::new(HashMap::new())))
     }
 
+    /// Returns a value the request already cached, without initialising it.
+    pub async fn get<T>(&self) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let req_cache = self.0.lock().await;
+        req_cache
+            .get(&TypeId::of::<T>())?
+            .downcast_ref::<T>()
+            .cloned()
+    }
+
     async fn get_or_init_cache<T, F, Fut, E>(&self, f: F) -> Result<T, E>
     where
         T: Clone + Send + Sync + 'static,
@@ -45,6 +57,9 @@ pub async fn init_request_cache<EP: Endpoint>(
 }
 
 pub trait RequestCacheExt {
+    /// The cache of this request, which stays usable after the request is handed on.
+    fn request_cache(&self) -> Option<RequestCache>;
+
     fn get_or_init_cache<T, F, Fut, E>(&self, f: F) -> impl Future<Output = Result<T, E>>
     where
         T: Clone + Send + Sync + 'static,
@@ -53,6 +68,10 @@ pub trait RequestCacheExt {
 }
 
 impl RequestCacheExt for poem::Request {
+    fn request_cache(&self) -> Option<RequestCache> {
+        self.data::<RequestCache>().cloned()
+    }
+
     async fn get_or_init_cache<T, F, Fut, E>(&self, f: F) -> Result<T, E>
     where
         T: Clone + Send + Sync + 'static,
@@ -0,0 +1,148 @@
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::log::scope::LogScope;
+use crate::utils::request_cache::RequestCacheExt;
+use error_stack::Report;
+use poem::http::HeaderValue;
+use poem::{Endpoint, IntoResponse, Request, Response};
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Longest incoming request ID that is propagated instead of replaced.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Correlates a response with its log lines and `error_stack` rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(Arc<str>);
+
+impl RequestId {
+    pub(crate) fn generate() -> Self {
+        Self(Uuid::new_v4().simple().to_string().into())
+    }
+
+    /// Keeps the ID set by a proxy in front of the app, when it looks like one.
+    fn from_header(req: &Request) -> Option<Self> {
+        let value = req.header(REQUEST_ID_HEADER)?;
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LEN
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+        valid.then(|| Self(value.into()))
+    }
+
+    /// The ID of the request being handled by this task, if any. It is carried by the
+    /// `LogScope` of the request, with the other fields of its log lines.
+    pub fn current() -> Option<Self> {
+        LogScope::request_id()
+    }
+}
+
+impl Deref for RequestId {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Assigns or propagates `X-Request-Id` and echoes it on the response. The `LogScope`
+/// opened inside picks it up from the request data.
+pub async fn init_request_id<EP: Endpoint>(next: EP, mut req: Request) -> poem::Result<Response> {
+    let request_id = RequestId::from_header(&req).unwrap_or_else(RequestId::generate);
+    req.set_data(request_id.clone());
+    let mut resp = match next.call(req).await {
+        Ok(resp) => resp.into_response(),
+        Err(err) => err.into_response(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        resp.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    Ok(resp)
+}
+
+impl FromContext for RequestId {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let req = ctx.req_result()?;
+        req.get_or_init_cache(|| async {
+            Ok(req
+                .data::<RequestId>()
+                .cloned()
+                .unwrap_or_else(RequestId::generate))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use poem::EndpointExt;
+    use poem::endpoint::make_sync;
+
+    fn call_with_header(value: &str) -> (String, String) {
+        let ep = make_sync(|req| {
+            req.data::<RequestId>()
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+        })
+        .around(init_request_id);
+        let resp = ep
+            .call(Request::builder().header(REQUEST_ID_HEADER, value).finish())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let header = resp
+            .header(REQUEST_ID_HEADER)
+            .unwrap_or_default()
+            .to_string();
+        let body = resp
+            .into_body()
+            .into_string()
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        (header, body)
+    }
+
+    #[test]
+    fn test_request_id_from_header() {
+        let req = Request::builder()
+            .header(REQUEST_ID_HEADER, "abc-123")
+            .finish();
+        assert_eq!(RequestId::from_header(&req).as_deref(), Some("abc-123"));
+
+        let req = Request::builder()
+            .header(REQUEST_ID_HEADER, "<script>")
+            .finish();
+        assert_eq!(RequestId::from_header(&req), None);
+
+        let req = Request::builder()
+            .header(REQUEST_ID_HEADER, "a".repeat(MAX_REQUEST_ID_LEN + 1))
+            .finish();
+        assert_eq!(RequestId::from_header(&req), None);
+    }
+
+    #[test]
+    fn test_init_request_id_replaces_invalid_inbound_id() {
+        let (header, body) = call_with_header("bad id <script>");
+        assert_ne!(header, "bad id <script>");
+        assert_eq!(header.len(), 32);
+        assert!(header.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(body, header);
+
+        let (header, body) = call_with_header("proxy-id.1");
+        assert_eq!(header, "proxy-id.1");
+        assert_eq!(body, "proxy-id.1");
+    }
+}
@@ -0,0 +1,31 @@
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::jobs::{Job, JobError};
+use crate::utils::session::repository::session_store_repository::SessionStoreRepository;
+use chrono::Utc;
+use error_stack::{Report, ResultExt};
+
+pub const SESSION_STORE_CLEANUP_JOB: &str = "session_store_cleanup";
+
+/// Deletes `sqlite` sessions past their idle or absolute timeout, which are otherwise
+/// only skipped on load.
+pub struct SessionStoreCleanupJob {
+    session_store_repository: SessionStoreRepository,
+}
+
+impl Job for SessionStoreCleanupJob {
+    async fn run(&self) -> Result<String, Report<JobError>> {
+        let deleted = self
+            .session_store_repository
+            .delete_expired(Utc::now())
+            .change_context(JobError::Failed)?;
+        Ok(format!("Deleted {} expired sessions", deleted))
+    }
+}
+
+impl FromContext for SessionStoreCleanupJob {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self {
+            session_store_repository: ctx.inject().await?,
+        })
+    }
+}
@@ -0,0 +1,171 @@
+use crate::utils::cipher::decode_hex;
+use crate::utils::config::ConfigPointer;
+use crate::utils::config::session::{
+    SessionConfig, SessionSameSite, SessionSecurity, SessionStore,
+};
+use crate::utils::context::{Context, ContextError, FromContext};
+use error_stack::{Report, ResultExt};
+use log::warn;
+use poem::endpoint::EitherEndpoint;
+use poem::session::{CookieConfig, CookieSession, ServerSession};
+use poem::web::cookie::{CookieKey, SameSite};
+use poem::{Endpoint, Middleware};
+use std::env::var;
+use std::fs;
+use std::time::Duration;
+use storage::SqliteSessionStorage;
+use thiserror::Error;
+
+pub mod job;
+pub mod repository;
+pub mod storage;
+
+const KEY_LEN: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Missing key")]
+    MissingKey,
+    #[error("Key file error")]
+    KeyFileError,
+    #[error("Invalid key")]
+    InvalidKey,
+    #[error("Invalid timeout")]
+    InvalidTimeout,
+    #[error("Store error")]
+    StoreError,
+}
+
+/// Builds the session cookie from `config`, keyed from `config.key_env` or else
+/// `config.key_file`.
+///
+/// Without a key, debug builds fall back to a random one, so sessions do not survive a
+/// restart; release builds fail instead.
+pub fn cookie_config(config: &SessionConfig) -> Result<CookieConfig, Report<SessionError>> {
+    let key = match load_key(config, var(&config.key_env).ok())? {
+        Some(key) => key,
+        None if cfg!(debug_assertions) => {
+            warn!(
+                "No session key in {} or session.key_file, using a random key for this run.",
+                config.key_env
+            );
+            CookieKey::generate()
+        }
+        None => {
+            return Err(Report::new(SessionError::MissingKey).attach(format!(
+                "set {} or session.key_file to a key from `openssl rand -hex 64`",
+                config.key_env
+            )));
+        }
+    };
+
+    let cookie_config = match config.security {
+        SessionSecurity::Private => CookieConfig::private(key),
+        SessionSecurity::Signed => CookieConfig::signed(key),
+    };
+    Ok(cookie_config
+        .name(&config.cookie_name)
+        .same_site(match config.same_site {
+            SessionSameSite::Strict => SameSite::Strict,
+            SessionSameSite::Lax => SameSite::Lax,
+            SessionSameSite::None => SameSite::None,
+        })
+        .secure(config.secure)
+        .max_age((config.ttl_secs > 0).then(|| Duration::from_secs(config.ttl_secs))))
+}
+
+/// `env_value` wins over the key file, an empty value counts as unset.
+fn load_key(
+    config: &SessionConfig,
+    env_value: Option<String>,
+) -> Result<Option<CookieKey>, Report<SessionError>> {
+    if let Some(key_hex) = env_value.filter(|value| !value.trim().is_empty()) {
+        return parse_key(&key_hex)
+            .attach_with(|| format!("environment variable: {}", config.key_env))
+            .map(Some);
+    }
+    if config.key_file.is_empty() {
+        return Ok(None);
+    }
+    let key_hex = fs::read_to_string(&config.key_file)
+        .change_context(SessionError::KeyFileError)
+        .attach_with(|| format!("key file: {}", config.key_file))?;
+    parse_key(&key_hex)
+        .attach_with(|| format!("key file: {}", config.key_file))
+        .map(Some)
+}
+
+fn parse_key(key_hex: &str) -> Result<CookieKey, Report<SessionError>> {
+    decode_hex(key_hex.trim())
+        .filter(|key| key.len() == KEY_LEN)
+        .map(|key| CookieKey::from(&key))
+        .ok_or_else(|| {
+            Report::new(SessionError::InvalidKey)
+                .attach(format!("expected {} hex characters", KEY_LEN * 2))
+        })
+}
+
+/// The session middleware picked by `session.store`.
+pub enum SessionMiddleware {
+    Cookie(CookieSession),
+    Sqlite(ServerSession<SqliteSessionStorage>),
+}
+
+impl<E: Endpoint> Middleware<E> for SessionMiddleware {
+    type Output = EitherEndpoint<
+        <CookieSession as Middleware<E>>::Output,
+        <ServerSession<SqliteSessionStorage> as Middleware<E>>::Output,
+    >;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        match self {
+            Self::Cookie(session) => EitherEndpoint::A(session.transform(ep)),
+            Self::Sqlite(session) => EitherEndpoint::B(session.transform(ep)),
+        }
+    }
+}
+
+impl FromContext for SessionMiddleware {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let config: ConfigPointer = ctx.inject().await?;
+        let cookie_config =
+            cookie_config(&config.session).change_context(ContextError::ConfigError)?;
+        Ok(match config.session.store {
+            SessionStore::Cookie => Self::Cookie(CookieSession::new(cookie_config)),
+            SessionStore::Sqlite => {
+                Self::Sqlite(ServerSession::new(cookie_config, ctx.inject().await?))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cipher::encode_hex;
+    use std::fs::{remove_dir_all, write};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_key_prefers_env_over_file() {
+        let dir = std::env::temp_dir().join(format!("session-test-{}", Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key_file = dir.join("session.key");
+        let file_key = encode_hex(&[1u8; KEY_LEN]);
+        write(&key_file, format!("{}\n", file_key)).unwrap();
+        let config = SessionConfig {
+            key_file: key_file.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+
+        let env_key = encode_hex(&[2u8; KEY_LEN]);
+        let key = load_key(&config, Some(env_key)).unwrap().unwrap();
+        assert_eq!(key.master(), [2u8; KEY_LEN].as_slice());
+        let key = load_key(&config, Some(String::new())).unwrap().unwrap();
+        assert_eq!(key.master(), [1u8; KEY_LEN].as_slice());
+
+        assert!(load_key(&SessionConfig::default(), None).unwrap().is_none());
+        assert!(load_key(&config, Some("abcd".to_string())).is_err());
+        remove_dir_all(&dir).unwrap();
+    }
+}
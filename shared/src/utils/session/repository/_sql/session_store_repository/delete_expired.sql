@@ -0,0 +1,3 @@
+delete
+from sessions
+where expire_after <= :now
@@ -0,0 +1,6 @@
+update sessions
+set last_seen_at = :now,
+    expire_after = min(:idle_expire_after, absolute_expire_after)
+where id_hash = :id_hash
+  and expire_after > :now
+returning entries
@@ -0,0 +1,3 @@
+delete
+from sessions
+where id_hash = :id_hash
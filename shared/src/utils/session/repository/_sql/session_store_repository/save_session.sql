@@ -0,0 +1,6 @@
+insert into sessions (id_hash, entries, created_at, last_seen_at, expire_after, absolute_expire_after)
+values (:id_hash, :entries, :now, :now, min(:idle_expire_after, :absolute_expire_after), :absolute_expire_after)
+on conflict (id_hash) do update
+    set entries      = excluded.entries,
+        last_seen_at = excluded.last_seen_at,
+        expire_after = min(excluded.expire_after, sessions.absolute_expire_after)
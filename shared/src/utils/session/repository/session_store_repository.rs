@@ -0,0 +1,203 @@
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::db::{BorrowConnectionExt, SqliteClient};
+use chrono::{DateTime, Utc};
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use rusqlite::{Connection, OptionalExtension, named_params};
+use std::sync::MutexGuard;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SessionStoreRepositoryError {
+    #[error("Query error")]
+    QueryError,
+    #[error("Borrow Conn error")]
+    BorrowConnError,
+}
+
+#[mry::mry]
+pub struct SessionStoreRepository {
+    sqlite_client: Option<SqliteClient>,
+}
+
+impl SessionStoreRepository {
+    pub fn new(sqlite_client: SqliteClient) -> Self {
+        Self {
+            sqlite_client: Some(sqlite_client),
+            mry: Default::default(),
+        }
+    }
+
+    fn borrow_conn(
+        &'_ self,
+    ) -> Result<MutexGuard<'_, Connection>, Report<SessionStoreRepositoryError>> {
+        self.sqlite_client
+            .borrow_conn()
+            .change_context(SessionStoreRepositoryError::BorrowConnError)
+    }
+}
+
+#[mry::mry]
+impl SessionStoreRepository {
+    /// Returns the JSON entries of a live session and pushes its idle expiry out to
+    /// `idle_expire_after`, never past its absolute expiry.
+    pub fn load_session(
+        &self,
+        id_hash: &str,
+        now: DateTime<Utc>,
+        idle_expire_after: DateTime<Utc>,
+    ) -> Result<Option<String>, Report<SessionStoreRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.query_one(
+            include_str!("_sql/session_store_repository/load_session.sql"),
+            named_params! {
+                ":id_hash": id_hash,
+                ":now": now,
+                ":idle_expire_after": idle_expire_after,
+            },
+            |row| row.get("entries"),
+        )
+        .optional()
+        .change_context(SessionStoreRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Creates or replaces a session, an existing session keeps its absolute expiry.
+    pub fn save_session(
+        &self,
+        id_hash: &str,
+        entries: &str,
+        now: DateTime<Utc>,
+        idle_expire_after: DateTime<Utc>,
+        absolute_expire_after: DateTime<Utc>,
+    ) -> Result<(), Report<SessionStoreRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/session_store_repository/save_session.sql"),
+            named_params! {
+                ":id_hash": id_hash,
+                ":entries": entries,
+                ":now": now,
+                ":idle_expire_after": idle_expire_after,
+                ":absolute_expire_after": absolute_expire_after,
+            },
+        )
+        .change_context(SessionStoreRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(())
+    }
+
+    pub fn remove_session(&self, id_hash: &str) -> Result<(), Report<SessionStoreRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/session_store_repository/remove_session.sql"),
+            named_params! {
+                ":id_hash": id_hash,
+            },
+        )
+        .change_context(SessionStoreRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(())
+    }
+
+    /// Removes every session past its idle or absolute expiry, returns how many were deleted.
+    pub fn delete_expired(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<usize, Report<SessionStoreRepositoryError>> {
+        let conn = self.borrow_conn()?;
+
+        conn.execute(
+            include_str!("_sql/session_store_repository/delete_expired.sql"),
+            named_params! {
+                ":now": now,
+            },
+        )
+        .change_context(SessionStoreRepositoryError::QueryError)
+        .attach(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[cfg(test)]
+impl SessionStoreRepository {
+    pub fn new_mock() -> Self {
+        mry::new!(Self {
+            sqlite_client: None
+        })
+    }
+}
+
+impl FromContext for SessionStoreRepository {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        Ok(Self::new(ctx.inject().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::migration::SHARED_MIGRATIONS;
+    use crate::utils::db::tests::client;
+    use chrono::{TimeDelta, TimeZone};
+    use std::fs::remove_dir_all;
+
+    fn repository() -> (SessionStoreRepository, std::path::PathBuf) {
+        let (client, dir) = client(1, 1000);
+        client.migrate(&SHARED_MIGRATIONS).unwrap();
+        (SessionStoreRepository::new(client), dir)
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 2, 3, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    #[test]
+    fn test_load_session_skips_expired() {
+        let (repository, dir) = repository();
+        repository
+            .save_session("id", "{}", at(0), at(10), at(60))
+            .unwrap();
+
+        // Loading pushes the idle expiry out to 15.
+        assert_eq!(
+            repository.load_session("id", at(5), at(15)).unwrap(),
+            Some("{}".to_string())
+        );
+        assert_eq!(
+            repository.load_session("id", at(14), at(24)).unwrap(),
+            Some("{}".to_string())
+        );
+        assert_eq!(repository.load_session("id", at(25), at(35)).unwrap(), None);
+        assert_eq!(
+            repository.load_session("other", at(0), at(10)).unwrap(),
+            None
+        );
+
+        remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_session_keeps_absolute_expiry() {
+        let (repository, dir) = repository();
+        repository
+            .save_session("id", "{}", at(0), at(30), at(60))
+            .unwrap();
+        repository
+            .save_session("id", r#"{"a":1}"#, at(50), at(80), at(110))
+            .unwrap();
+
+        // Neither the upsert nor loading goes past the absolute expiry set at 0.
+        assert_eq!(
+            repository.load_session("id", at(55), at(85)).unwrap(),
+            Some(r#"{"a":1}"#.to_string())
+        );
+        assert_eq!(repository.load_session("id", at(61), at(91)).unwrap(), None);
+
+        remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,204 @@
+use crate::utils::cipher::encode_hex;
+use crate::utils::config::ConfigPointer;
+use crate::utils::config::session::SessionConfig;
+use crate::utils::context::{Context, ContextError, FromContext};
+use crate::utils::error::{ExtraResultExt, FromErrorStack};
+use crate::utils::session::SessionError;
+use crate::utils::session::repository::session_store_repository::SessionStoreRepository;
+use chrono::{DateTime, TimeDelta, Utc};
+use error_stack::{Report, ResultExt};
+use poem::http::StatusCode;
+use poem::session::SessionStorage;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// The idle and absolute timeouts of `sqlite` sessions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionTimeouts {
+    idle: TimeDelta,
+    absolute: TimeDelta,
+}
+
+impl SessionTimeouts {
+    /// Refuses a timeout that does not fit a `TimeDelta` or a date.
+    pub fn new(config: &SessionConfig) -> Result<Self, Report<SessionError>> {
+        Ok(Self {
+            idle: timeout(config.idle_timeout_secs, "idle_timeout_secs")?,
+            absolute: timeout(config.absolute_timeout_secs, "absolute_timeout_secs")?,
+        })
+    }
+
+    fn idle_expire_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        expire_after(now, self.idle)
+    }
+
+    /// The absolute timeout, cut short by the cookie TTL when that is shorter.
+    fn absolute_expire_after(
+        &self,
+        now: DateTime<Utc>,
+        expires: Option<Duration>,
+    ) -> DateTime<Utc> {
+        let absolute = expire_after(now, self.absolute);
+        match expires.and_then(|ttl| TimeDelta::from_std(ttl).ok()) {
+            Some(ttl) => absolute.min(expire_after(now, ttl)),
+            None => absolute,
+        }
+    }
+}
+
+fn timeout(secs: u64, key: &str) -> Result<TimeDelta, Report<SessionError>> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .filter(|timeout| Utc::now().checked_add_signed(*timeout).is_some())
+        .ok_or_else(|| {
+            Report::new(SessionError::InvalidTimeout)
+                .attach(format!("session.{} is out of range: {}", key, secs))
+        })
+}
+
+fn expire_after(now: DateTime<Utc>, timeout: TimeDelta) -> DateTime<Utc> {
+    now.checked_add_signed(timeout)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// `SessionStorage` on the `sessions` table, for `ServerSession`.
+///
+/// Session ids are stored hashed, so the table cannot be used to take over a session.
+pub struct SqliteSessionStorage {
+    repository: SessionStoreRepository,
+    timeouts: SessionTimeouts,
+}
+
+impl SqliteSessionStorage {
+    pub fn new(repository: SessionStoreRepository, timeouts: SessionTimeouts) -> Self {
+        Self {
+            repository,
+            timeouts,
+        }
+    }
+}
+
+fn id_hash(session_id: &str) -> String {
+    encode_hex(&Sha256::digest(session_id.as_bytes()))
+}
+
+impl SessionStorage for SqliteSessionStorage {
+    async fn load_session<'a>(
+        &'a self,
+        session_id: &'a str,
+    ) -> poem::Result<Option<BTreeMap<String, Value>>> {
+        let now = Utc::now();
+        let entries = self
+            .repository
+            .load_session(
+                &id_hash(session_id),
+                now,
+                self.timeouts.idle_expire_after(now),
+            )
+            .change_context(SessionError::StoreError)
+            .log_it()
+            .map_err(poem::Error::from_error_stack)?;
+
+        entries
+            .map(|entries| serde_json::from_str(&entries))
+            .transpose()
+            .change_context(SessionError::StoreError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)
+            .log_it()
+            .map_err(poem::Error::from_error_stack)
+    }
+
+    async fn update_session<'a>(
+        &'a self,
+        session_id: &'a str,
+        entries: &'a BTreeMap<String, Value>,
+        expires: Option<Duration>,
+    ) -> poem::Result<()> {
+        let entries = serde_json::to_string(entries)
+            .change_context(SessionError::StoreError)
+            .attach(StatusCode::INTERNAL_SERVER_ERROR)
+            .log_it()
+            .map_err(poem::Error::from_error_stack)?;
+        let now = Utc::now();
+        self.repository
+            .save_session(
+                &id_hash(session_id),
+                &entries,
+                now,
+                self.timeouts.idle_expire_after(now),
+                self.timeouts.absolute_expire_after(now, expires),
+            )
+            .change_context(SessionError::StoreError)
+            .log_it()
+            .map_err(poem::Error::from_error_stack)
+    }
+
+    async fn remove_session<'a>(&'a self, session_id: &'a str) -> poem::Result<()> {
+        self.repository
+            .remove_session(&id_hash(session_id))
+            .change_context(SessionError::StoreError)
+            .log_it()
+            .map_err(poem::Error::from_error_stack)
+    }
+}
+
+impl FromContext for SqliteSessionStorage {
+    async fn from_context(ctx: &'_ Context<'_>) -> Result<Self, Report<ContextError>> {
+        let config: ConfigPointer = ctx.inject().await?;
+        let timeouts =
+            SessionTimeouts::new(&config.session).change_context(ContextError::ConfigError)?;
+        Ok(Self::new(ctx.inject().await?, timeouts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_expiry_respects_timeouts_and_cookie_ttl() {
+        let config = SessionConfig {
+            idle_timeout_secs: 600,
+            absolute_timeout_secs: 3600,
+            ..Default::default()
+        };
+        let timeouts = SessionTimeouts::new(&config).unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 1, 2, 3, 0, 0).unwrap();
+
+        assert_eq!(
+            timeouts.idle_expire_after(now),
+            Utc.with_ymd_and_hms(2025, 1, 2, 3, 10, 0).unwrap()
+        );
+        assert_eq!(
+            timeouts.absolute_expire_after(now, None),
+            Utc.with_ymd_and_hms(2025, 1, 2, 4, 0, 0).unwrap()
+        );
+        assert_eq!(
+            timeouts.absolute_expire_after(now, Some(Duration::from_secs(1800))),
+            Utc.with_ymd_and_hms(2025, 1, 2, 3, 30, 0).unwrap()
+        );
+        assert_eq!(
+            timeouts.absolute_expire_after(now, Some(Duration::from_secs(7200))),
+            Utc.with_ymd_and_hms(2025, 1, 2, 4, 0, 0).unwrap()
+        );
+        assert_eq!(
+            timeouts.absolute_expire_after(now, Some(Duration::MAX)),
+            Utc.with_ymd_and_hms(2025, 1, 2, 4, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_session_timeouts_out_of_range() {
+        for secs in [u64::MAX, i64::MAX as u64, 1 << 60] {
+            let config = SessionConfig {
+                absolute_timeout_secs: secs,
+                ..Default::default()
+            };
+            assert!(SessionTimeouts::new(&config).is_err());
+        }
+    }
+}
@@ -1,27 +1,162 @@
-use backoffice::export::{MainError, init_log};
-use error_stack::Report;
+use backoffice::export::{
+    BACKOFFICE_MIGRATIONS, MainError, check, create_user, init_log, print_config, reset_password,
+    revoke_tokens, run_migrations,
+};
+use clap::{Parser, Subcommand};
 use error_stack::fmt::ColorMode;
+use error_stack::{Report, ResultExt};
+use public::export::PUBLIC_MIGRATIONS;
+use std::io::{self, BufRead, IsTerminal};
 use tokio::task::JoinHandle;
 
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run migrations and start the web servers (default)
+    Serve {
+        /// Only start the public server
+        #[arg(long, conflicts_with = "backoffice_only")]
+        public_only: bool,
+        /// Only start the backoffice server
+        #[arg(long)]
+        backoffice_only: bool,
+    },
+    /// Apply pending schema migrations
+    Migrate,
+    /// Create a backoffice user, the password is prompted for or read from stdin
+    CreateUser {
+        username: String,
+        /// `root` or `user`
+        #[arg(long, default_value = "user")]
+        role: String,
+    },
+    /// Set a new password for a backoffice user and revoke their login tokens
+    ResetPassword { username: String },
+    /// Revoke every login token of a backoffice user
+    RevokeTokens { username: String },
+    /// Print the effective config with secrets redacted
+    PrintConfig,
+    /// Check config, database and schema without starting the servers
+    Check,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Report<MainError>> {
-    init_log();
     Report::set_color_mode(ColorMode::None);
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve {
+        public_only: false,
+        backoffice_only: false,
+    });
+
+    // The logger is set up from the config, `check` runs without it so it can still
+    // report a config that does not load.
+    let logger = init_log().await;
+    if !matches!(command, Command::Check) {
+        logger?;
+    }
 
-    let backoffice_handle = tokio::spawn(backoffice::boot());
-    let public_handle = tokio::spawn(public::boot());
+    let registered = [&BACKOFFICE_MIGRATIONS, &PUBLIC_MIGRATIONS];
+
+    match command {
+        Command::Serve {
+            public_only,
+            backoffice_only,
+        } => {
+            run_migrations(&registered)
+                .await
+                .change_context(MainError::MigrationError)?;
+            serve(!backoffice_only, !public_only).await
+        }
+        Command::Migrate => {
+            let applied = run_migrations(&registered)
+                .await
+                .change_context(MainError::MigrationError)?;
+            println!("Applied {} migration(s)", applied);
+            Ok(())
+        }
+        Command::CreateUser { username, role } => create_user(username, read_password()?, role)
+            .await
+            .change_context(MainError::CommandError),
+        Command::ResetPassword { username } => reset_password(username, read_password()?)
+            .await
+            .change_context(MainError::CommandError),
+        Command::RevokeTokens { username } => revoke_tokens(username)
+            .await
+            .change_context(MainError::CommandError),
+        Command::PrintConfig => print_config().await.change_context(MainError::CommandError),
+        Command::Check => check(&registered)
+            .await
+            .change_context(MainError::CommandError),
+    }
+}
+
+async fn serve(public: bool, backoffice: bool) -> Result<(), Report<MainError>> {
+    let backoffice_handle = backoffice.then(|| tokio::spawn(backoffice::boot()));
+    let public_handle = public.then(|| tokio::spawn(public::boot()));
     match tokio::try_join!(flatten(backoffice_handle), flatten(public_handle)) {
         Ok(_) => Ok(()),
         Err(err) => Err(err),
     }
 }
 
+/// Prompts twice on a terminal; otherwise reads the first line of stdin, so scripts can
+/// pipe the password in without it showing up in argv.
+fn read_password() -> Result<String, Report<MainError>> {
+    if !io::stdin().is_terminal() {
+        return read_password_line(io::stdin().lock());
+    }
+    let password = rpassword::prompt_password("Password: ").change_context(MainError::IoError)?;
+    let password_confirm =
+        rpassword::prompt_password("Confirm password: ").change_context(MainError::IoError)?;
+    if password != password_confirm {
+        return Err(Report::new(MainError::CommandError).attach("Passwords do not match"));
+    }
+    Ok(password)
+}
+
+fn read_password_line(mut input: impl BufRead) -> Result<String, Report<MainError>> {
+    let mut line = String::new();
+    input
+        .read_line(&mut line)
+        .change_context(MainError::IoError)?;
+    let password = line.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        return Err(Report::new(MainError::CommandError).attach("No password on stdin"));
+    }
+    Ok(password.to_string())
+}
+
 async fn flatten(
-    handle: JoinHandle<Result<(), Report<MainError>>>,
+    handle: Option<JoinHandle<Result<(), Report<MainError>>>>,
 ) -> Result<(), Report<MainError>> {
-    match handle.await {
-        Ok(Ok(result)) => Ok(result),
-        Ok(Err(err)) => Err(err),
-        Err(err) => Err(Report::new(err).change_context(MainError::ThreadError)),
+    match handle {
+        None => Ok(()),
+        Some(handle) => match handle.await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(err)) => Err(err),
+            Err(err) => Err(Report::new(err).change_context(MainError::ThreadError)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_password_line() {
+        assert_eq!(
+            read_password_line("Banana#Split9 \r\nnext\n".as_bytes()).unwrap(),
+            "Banana#Split9 "
+        );
+        assert!(read_password_line("\n".as_bytes()).is_err());
+        assert!(read_password_line("".as_bytes()).is_err());
     }
 }